@@ -0,0 +1,2 @@
+/// Lives in a hidden directory.
+struct Hidden;
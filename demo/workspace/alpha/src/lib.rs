@@ -0,0 +1 @@
+//! The alpha member.
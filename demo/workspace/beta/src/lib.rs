@@ -0,0 +1 @@
+//! The beta member.
@@ -0,0 +1,94 @@
+//! Serialize suggestions as checkstyle XML, as consumed by many CI dashboards.
+
+use crate::SuggestionSet;
+
+use std::fmt::Write;
+
+/// Escape the characters with special meaning within xml attribute values.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// One `<file>` element per origin, with one `<error>` per suggestion.
+///
+/// Lines and columns are one based, as expected by checkstyle consumers.
+pub fn checkstyle(suggestions_per_path: &SuggestionSet) -> String {
+    let mut xml = String::with_capacity(1024);
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<checkstyle version=\"4.3\">\n");
+    for (origin, suggestions) in suggestions_per_path.iter() {
+        let _ = writeln!(
+            xml,
+            "<file name=\"{}\">",
+            escape(origin.as_path().display().to_string().as_str())
+        );
+        for suggestion in suggestions {
+            let (line, _) = suggestion.lines();
+            // the rule which fired, if any, qualified by the detector
+            let source = if let Some(ref rule_id) = suggestion.rule_id {
+                escape(format!("{}.{}", suggestion.detector, rule_id).as_str())
+            } else {
+                suggestion.detector.to_string()
+            };
+            let _ = writeln!(
+                xml,
+                "<error line=\"{}\" column=\"{}\" severity=\"error\" message=\"{}\" source=\"{}\"/>",
+                line,
+                suggestion.span.start.column + 1,
+                escape(suggestion.message().as_str()),
+                source,
+            );
+        }
+        xml.push_str("</file>\n");
+    }
+    xml.push_str("</checkstyle>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::suggestion::{dummy_suggestion, single_line_chunk};
+    use crate::{ContentOrigin, Suggestion};
+
+    #[test]
+    fn serialize() {
+        let chunk = single_line_chunk("A \"wrold\"", 3, 4);
+        let origin = ContentOrigin::TestEntity;
+        let mut set = SuggestionSet::new();
+        set.add(
+            origin.clone(),
+            Suggestion {
+                replacements: vec!["world".to_owned()],
+                description: Some("Possible spelling mistake found.".to_owned()),
+                ..dummy_suggestion(origin.clone(), &chunk, 3..8)
+            },
+        );
+        assert_eq!(
+            checkstyle(&set),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<checkstyle version="4.3">
+<file name="/tmp/test/entity">
+<error line="3" column="8" severity="error" message="Possible spelling mistake found. `wrold`" source="dummy"/>
+</file>
+</checkstyle>
+"#
+        );
+        assert_eq!(
+            escape("<\"a\" & 'b'>"),
+            "&lt;&quot;a&quot; &amp; &apos;b&apos;&gt;"
+        );
+    }
+}
@@ -0,0 +1,84 @@
+//! Print suggestions as GitHub Actions workflow commands, which are shown
+//! as inline annotations of pull requests.
+
+use crate::SuggestionSet;
+
+use std::fmt::Write;
+
+/// Escape the message of a workflow command.
+fn escape_data(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '%' => escaped.push_str("%25"),
+            '\r' => escaped.push_str("%0D"),
+            '\n' => escaped.push_str("%0A"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escape a property value of a workflow command, which additionally
+/// must not contain the property separators.
+fn escape_property(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in escape_data(s).chars() {
+        match c {
+            ':' => escaped.push_str("%3A"),
+            ',' => escaped.push_str("%2C"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// One `::warning` line per suggestion.
+///
+/// Lines and columns are one based, as expected by GitHub.
+pub fn github(suggestions_per_path: &SuggestionSet) -> String {
+    let mut out = String::with_capacity(1024);
+    for (origin, suggestions) in suggestions_per_path.iter() {
+        let file = escape_property(origin.as_path().display().to_string().as_str());
+        for suggestion in suggestions {
+            let (line, _) = suggestion.lines();
+            let _ = writeln!(
+                out,
+                "::warning file={},line={},col={},title={}::{}",
+                file,
+                line,
+                suggestion.span.start.column + 1,
+                escape_property(format!("spellcheck({})", suggestion.detector).as_str()),
+                escape_data(suggestion.message().as_str()),
+            );
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::suggestion::{dummy_suggestion, single_line_chunk};
+    use crate::{ContentOrigin, Suggestion};
+
+    #[test]
+    fn serialize() {
+        let chunk = single_line_chunk("A wrold", 3, 4);
+        let origin = ContentOrigin::TestEntity;
+        let mut set = SuggestionSet::new();
+        set.add(
+            origin.clone(),
+            Suggestion {
+                replacements: vec!["world".to_owned()],
+                description: Some("100% wrong,\nreally.".to_owned()),
+                ..dummy_suggestion(origin.clone(), &chunk, 2..7)
+            },
+        );
+        assert_eq!(
+            github(&set),
+            "::warning file=/tmp/test/entity,line=3,col=7,title=spellcheck(dummy)::100%25 wrong,%0Areally. `wrold`\n"
+        );
+        assert_eq!(escape_property("a:b,c%\r"), "a%3Ab%2Cc%25%0D");
+    }
+}
@@ -13,6 +13,7 @@ use crossterm::{
     terminal, QueueableCommand,
 };
 
+use std::collections::HashSet;
 use std::convert::TryFrom;
 use std::io::stdout;
 
@@ -24,6 +25,7 @@ g - select a suggestion to go to
 j - leave this hunk undecided, see next undecided hunk
 J - leave this hunk undecided, see next hunk
 e - manually edit the current hunk
+s - save the progress and quit, continue later with `fix --resume`
 ? - print help
 
 
@@ -31,6 +33,19 @@ e - manually edit the current hunk
 
 "##;
 
+/// Locate a suggestion as `[nth/of_n] path:line:column`, where `nth`
+/// is zero based and the column is printed one based.
+fn location_header(suggestion: &Suggestion, nth: usize, of_n: usize) -> String {
+    format!(
+        "[{nth}/{of_n}] {path}:{line}:{column}",
+        nth = nth + 1,
+        of_n = of_n,
+        path = suggestion.origin.as_path().display(),
+        line = suggestion.span.start.line,
+        column = suggestion.span.start.column + 1,
+    )
+}
+
 /// Helper strict to assure we leave the terminals raw mode
 pub struct ScopedRaw;
 
@@ -74,6 +89,8 @@ pub(super) enum UserSelection {
     SkipFile,
     /// Stop execution.
     Abort,
+    /// Save the decisions so far and stop execution.
+    Save,
     /// continue as if whatever returned this was never called.
     Nop,
 }
@@ -350,7 +367,7 @@ impl UserPicked {
                 .attribute(Attribute::Bold);
 
             let question = format!(
-                "({nth}/{of_n}) Apply this suggestion [y,n,q,a,d,j,e,?]?",
+                "({nth}/{of_n}) Apply this suggestion [y,n,q,a,d,j,e,s,?]?",
                 nth = running_idx.0 + 1,
                 of_n = running_idx.1
             );
@@ -456,6 +473,7 @@ impl UserPicked {
                     return Ok(UserSelection::Abort)
                 }
                 KeyCode::Char('d') => return Ok(UserSelection::SkipFile),
+                KeyCode::Char('s') => return Ok(UserSelection::Save),
                 KeyCode::Char('e') => {
                     // jump to the user input entry
                     state.select_custom();
@@ -469,15 +487,29 @@ impl UserPicked {
         unreachable!("Unexpected return when dealing with user input")
     }
 
+    /// Let the user pick the replacements to apply, skipping all
+    /// suggestions decided upon in the `resumed` session.
+    ///
+    /// Returns the decisions of this and the resumed session, which are
+    /// to be saved if the user asked to.
     pub(super) fn select_interactive<'s>(
         suggestions_per_path: SuggestionSet<'s>,
         _config: &Config,
-    ) -> Result<(Self, UserSelection)> {
+        resumed: Option<&Progress>,
+    ) -> Result<(Self, UserSelection, Progress)> {
         let mut picked = UserPicked::default();
+        let mut progress = Progress::default();
+        // saved picks which were found again
+        let mut found = HashSet::new();
 
         trace!("Select the ones to actully use");
 
-        for (origin, suggestions) in suggestions_per_path {
+        let total = suggestions_per_path.count();
+        // number of suggestions of all previously processed files
+        let mut preceding = 0usize;
+
+        let mut files = suggestions_per_path.into_iter();
+        while let Some((origin, suggestions)) = files.next() {
             let count = suggestions.len();
             trace!("Path is {} and has {}", origin, count);
 
@@ -511,6 +543,38 @@ impl UserPicked {
                     trace!("Suggestion did not contain a replacement, skip");
                     continue;
                 }
+                if let Some(resumed) = resumed {
+                    let fingerprint = Fingerprint::from(&suggestion);
+                    match resumed.decision(&fingerprint) {
+                        Some(Decision::Picked(replacement)) => {
+                            trace!("Resuming the pick of {} for {}", replacement, &fingerprint);
+                            picked
+                                .add_bandaid(&origin, BandAid::new(replacement, &suggestion.span));
+                            progress.pick(&suggestion, replacement);
+                            found.insert(fingerprint);
+                            continue;
+                        }
+                        Some(Decision::Skipped) => {
+                            trace!("Resuming the skip of {}", &fingerprint);
+                            progress.skip(&suggestion);
+                            continue;
+                        }
+                        None if !resumed.knows(&fingerprint) => {
+                            debug!("{} is new since the progress was saved", &fingerprint);
+                        }
+                        None => {}
+                    }
+                }
+                let header = ContentStyle::new()
+                    .foreground(Color::Cyan)
+                    .attribute(Attribute::Bold);
+                println!(
+                    "{}",
+                    StyledContent::new(
+                        header,
+                        location_header(&suggestion, preceding + idx, total)
+                    )
+                );
                 println!("{}", suggestion);
 
                 let mut state = State::from(&suggestion);
@@ -521,8 +585,26 @@ impl UserPicked {
                     pick = picked.user_input(&mut state, (idx, count))?;
                 }
                 match pick {
-                    UserSelection::Abort => return Ok((picked, UserSelection::Abort)),
-                    UserSelection::SkipFile => break, // break the inner loop
+                    UserSelection::Abort => return Ok((picked, UserSelection::Abort, progress)),
+                    UserSelection::Save => {
+                        progress.remain(&suggestion);
+                        for (_idx, suggestion) in suggestions_it {
+                            progress.remain(&suggestion);
+                        }
+                        for (_origin, suggestions) in files {
+                            for suggestion in suggestions.iter() {
+                                progress.remain(suggestion);
+                            }
+                        }
+                        return Ok((picked, UserSelection::Save, progress));
+                    }
+                    UserSelection::SkipFile => {
+                        progress.skip(&suggestion);
+                        for (_idx, suggestion) in suggestions_it {
+                            progress.skip(&suggestion);
+                        }
+                        break; // break the inner loop
+                    }
                     UserSelection::Previous => {
                         unimplemented!("Requires a iterator which works bidrectionally")
                     }
@@ -530,14 +612,122 @@ impl UserPicked {
                         unreachable!("Help must not be reachable here, it is handled before")
                     }
                     UserSelection::Replacement(bandaid) => {
+                        progress.pick(&suggestion, &bandaid.replacement);
                         picked.add_bandaid(&origin, bandaid);
                     }
+                    UserSelection::Skip => progress.skip(&suggestion),
                     _ => continue,
                 };
 
                 direction = Direction::Forward;
             }
+            preceding += count;
         }
-        Ok((picked, UserSelection::Nop))
+        if let Some(resumed) = resumed {
+            for fingerprint in resumed.stale_picks(&found) {
+                warn!(
+                    "Dropping the picked replacement of {}, the file changed since the progress was saved",
+                    fingerprint
+                );
+            }
+        }
+        Ok((picked, UserSelection::Nop, progress))
+    }
+}
+
+impl UserPicked {
+    /// Pick the first replacement of every suggestion, without asking.
+    ///
+    /// A suggestion overlapping one of another detector is ambiguous and
+    /// skipped, as is one overlapping an already picked one, so the band
+    /// aids of a file never overlap and follow the order of their spans,
+    /// as required to apply them.
+    pub(super) fn select_first(suggestions_per_path: SuggestionSet) -> Self {
+        let mut picked = UserPicked::default();
+        let overlaps = |a: &Span, b: &Span| {
+            (a.start.line, a.start.column) <= (b.end.line, b.end.column)
+                && (b.start.line, b.start.column) <= (a.end.line, a.end.column)
+        };
+        for (origin, mut suggestions) in suggestions_per_path {
+            suggestions.sort_by_key(|suggestion| {
+                (suggestion.span.start.line, suggestion.span.start.column)
+            });
+            let mut last: Option<Span> = None;
+            for suggestion in suggestions.iter() {
+                let replacement = if let Some(replacement) = suggestion.replacements.first() {
+                    replacement
+                } else {
+                    continue;
+                };
+                if suggestions.iter().any(|other| {
+                    other.detector != suggestion.detector && overlaps(&other.span, &suggestion.span)
+                }) {
+                    debug!("Skipping the ambiguous suggestion {:?}", suggestion);
+                    continue;
+                }
+                if let Some(ref last) = last {
+                    if overlaps(last, &suggestion.span) {
+                        debug!("Skipping the overlapping suggestion {:?}", suggestion);
+                        continue;
+                    }
+                }
+                picked.add_bandaid(&origin, BandAid::new(replacement, &suggestion.span));
+                last = Some(suggestion.span);
+            }
+        }
+        picked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::suggestion::{dummy_suggestion, single_line_chunk};
+    use crate::LineColumn;
+
+    #[test]
+    fn header_locates_suggestion() {
+        let chunk = single_line_chunk("abc", 3, 4);
+        let suggestion = dummy_suggestion(
+            ContentOrigin::CommonMarkFile(PathBuf::from("/tmp/README.md")),
+            &chunk,
+            0..3,
+        );
+        assert_eq!(
+            location_header(&suggestion, 11, 87),
+            "[12/87] /tmp/README.md:3:5"
+        );
+    }
+
+    #[test]
+    fn first_replacements() {
+        let chunk = single_line_chunk("A wrold and wrods", 1, 4);
+        let origin = ContentOrigin::CommonMarkFile(PathBuf::from("/tmp/README.md"));
+        let suggestion = |range: Range, replacements: &[&str], detector: Detector| Suggestion {
+            detector,
+            replacements: replacements.iter().map(|r| (*r).to_owned()).collect(),
+            ..dummy_suggestion(origin.clone(), &chunk, range)
+        };
+        let mut set = SuggestionSet::new();
+        // contested by two detectors
+        set.add(
+            origin.clone(),
+            suggestion(12..17, &["words", "swords"], Detector::Hunspell),
+        );
+        set.add(
+            origin.clone(),
+            suggestion(8..17, &["and words"], Detector::LanguageTool),
+        );
+        set.add(
+            origin.clone(),
+            suggestion(2..7, &["world", "wold"], Detector::Hunspell),
+        );
+        set.add(origin.clone(), suggestion(0..1, &[], Detector::Hunspell));
+
+        let picked = UserPicked::select_first(set);
+        assert_eq!(picked.total_count(), 1);
+        let bandaids = picked.bandaids.get(&origin).unwrap();
+        assert_eq!(bandaids[0].replacement, "world");
+        assert_eq!(bandaids[0].span.start, LineColumn { line: 1, column: 6 });
     }
 }
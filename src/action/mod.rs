@@ -1,19 +1,24 @@
 use super::*;
 use anyhow::{anyhow, Result};
-use log::{debug, trace};
+use log::{debug, info, trace, warn};
 use std::convert::TryInto;
 use std::fs::{self, OpenOptions};
-use std::io::{BufRead, Read, Write};
+use std::io::{Read, Write};
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 pub mod bandaid;
+pub mod checkstyle;
+pub mod github;
 pub mod interactive;
+pub mod resume;
 
 pub(crate) use bandaid::*;
 use interactive::*;
+use resume::*;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Finish {
     Abort,
     MistakeCount(usize),
@@ -28,19 +33,73 @@ impl Finish {
     }
 }
 
+/// Output format of the mistakes found by `check`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Annotated source excerpts, meant to be read by humans.
+    Human,
+    /// Checkstyle XML, for consumption by CI dashboards.
+    Checkstyle,
+    /// GitHub Actions workflow commands, shown as inline annotations.
+    GitHub,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Self::Human
+    }
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "human" => Self::Human,
+            "checkstyle" => Self::Checkstyle,
+            "github" => Self::GitHub,
+            other => {
+                return Err(anyhow!(
+                    "Unknown format {}, expected one of human, checkstyle, github",
+                    other
+                ))
+            }
+        })
+    }
+}
+
+/// Split `content` into numbered lines, each retaining its line ending,
+/// be it `\n` or `\r\n`, so writing them back yields `content` unchanged.
+fn lines_with_endings(content: &str) -> impl Iterator<Item = (usize, String)> + '_ {
+    let mut rest = content;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let end = rest.find('\n').map(|idx| idx + 1).unwrap_or(rest.len());
+        let (line, remainder) = rest.split_at(end);
+        rest = remainder;
+        Some(line.to_owned())
+    })
+    .enumerate()
+    .map(|(lineno, content)| (lineno + 1, content))
+}
+
 /// correct all lines
 /// `bandaids` are the fixes to be applied to the lines
 ///
-/// Note that `Lines` as created by `(x as BufLines).lines()` does
-/// not preserve trailing newlines, so either the iterator
-/// needs to be modified to yield an extra (i.e. with `.chain("".to_owned())`)
-/// or a manual newlines has to be written to the `sink`.
+/// Every line of `source` must retain its line ending, as yielded by
+/// `lines_with_endings`. Only the spans of the `bandaids` are replaced,
+/// everything else, including line endings and trailing whitespace, is
+/// written to `sink` as it is.
 fn correct_lines<'s>(
     mut bandaids: impl Iterator<Item = BandAid>,
     source: impl Iterator<Item = (usize, String)>,
     mut sink: impl Write,
 ) -> Result<()> {
     let mut nxt: Option<BandAid> = bandaids.next();
+    // line and column at which the source continues after a replacement
+    // spanning multiple lines
+    let mut resume: Option<(usize, usize)> = None;
     for (line_number, content) in source {
         trace!("Processing line {}", line_number);
         let mut remainder_column = 0usize;
@@ -48,41 +107,54 @@ fn correct_lines<'s>(
         //     anyhow!("Line {} contains invalid utf8 characters", line_number).context(e)
         // })?;
 
+        if let Some((line, column)) = resume {
+            if line_number < line {
+                // entirely covered by the replacement
+                continue;
+            }
+            resume = None;
+            remainder_column = column;
+        }
+        let content_len = content.chars().count();
+
         if nxt.is_none() {
             // no candidates remaining, just keep going
-            sink.write(content.as_bytes())?;
-            sink.write("\n".as_bytes())?;
+            sink.write(util::sub_chars(&content, remainder_column..content_len).as_bytes())?;
             continue;
         }
 
         if let Some(ref bandaid) = nxt {
-            if !bandaid.span.covers_line(line_number) {
-                sink.write(content.as_bytes())?;
-                sink.write("\n".as_bytes())?;
+            if bandaid.span.start.line != line_number {
+                sink.write(util::sub_chars(&content, remainder_column..content_len).as_bytes())?;
                 continue;
             }
         }
 
-        let content_len = content.chars().count();
         while let Some(bandaid) = nxt.take() {
             trace!("Applying next bandaid {:?}", bandaid);
             trace!("where line {} is: >{}<", line_number, content);
-            let range: Range = bandaid
-                .span
-                .try_into()
-                .expect("There should be no multiline strings as of today");
+            let start = bandaid.span.start.column;
             // write prelude for this line between start or previous replacement
-            if range.start > remainder_column {
-                sink.write(util::sub_chars(&content, remainder_column..range.start).as_bytes())?;
+            if start > remainder_column {
+                sink.write(util::sub_chars(&content, remainder_column..start).as_bytes())?;
             }
             // write the replacement chunk
             sink.write(bandaid.replacement.as_bytes())?;
 
-            remainder_column = range.end;
             nxt = bandaids.next();
+            if bandaid.span.end.line != line_number {
+                // the remaining lines are skipped until the end of the replacement
+                resume = Some((bandaid.span.end.line, bandaid.span.end.column + 1));
+                break;
+            }
+            let range: Range = bandaid
+                .span
+                .try_into()
+                .expect("Single line spans always convert to a range");
+            remainder_column = range.end;
             let complete_current_line = if let Some(ref bandaid) = nxt {
                 // if `nxt` is also targeting the current line, don't complete the line
-                !bandaid.span.covers_line(line_number)
+                bandaid.span.start.line != line_number
             } else {
                 true
             };
@@ -104,7 +176,6 @@ fn correct_lines<'s>(
                         line_number, content_len, remainder_column
                     );
                 }
-                sink.write("\n".as_bytes())?;
                 // break the inner loop
                 break;
                 // } else {
@@ -115,16 +186,110 @@ fn correct_lines<'s>(
     Ok(())
 }
 
+/// Copy `path` to `path<suffix>`, before it is modified.
+///
+/// An existing backup is never overwritten, since it holds the content
+/// before an earlier fix, the first free `path<suffix>.<n>` is used instead.
+fn backup_file(path: &Path, suffix: &str) -> Result<PathBuf> {
+    let mut file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("Failed to back up {}, not a file", path.display()))?
+        .to_owned();
+    file_name.push(suffix);
+    let first = path.with_file_name(&file_name);
+    let backup = std::iter::once(first.clone())
+        .chain((1..).map(|n| {
+            let mut numbered = file_name.clone();
+            numbered.push(format!(".{}", n));
+            path.with_file_name(numbered)
+        }))
+        .find(|backup| !backup.exists())
+        .unwrap_or(first);
+    fs::copy(path, &backup).map_err(|e| {
+        anyhow!(
+            "Failed to back up {} to {}",
+            path.display(),
+            backup.display()
+        )
+        .context(e)
+    })?;
+    Ok(backup)
+}
+
+/// Check that backups with `suffix` are not checked themselves, as they
+/// would be if their name ended with the extension of a checked file.
+pub fn validate_backup_suffix(suffix: &str) -> Result<()> {
+    if suffix.is_empty() || suffix.contains(std::path::is_separator) {
+        return Err(anyhow!(
+            "The backup suffix must not be empty or contain a path separator: {:?}",
+            suffix
+        ));
+    }
+    if let Some((extension, _kind)) =
+        traverse::CHECKED_EXTENSIONS
+            .iter()
+            .find(|(extension, _kind)| {
+                suffix
+                    .strip_suffix(extension)
+                    .map(|rest| rest.ends_with('.'))
+                    .unwrap_or(false)
+            })
+    {
+        return Err(anyhow!(
+            "The backup suffix must not end with `.{}`, backups would be checked as well: {:?}",
+            extension,
+            suffix
+        ));
+    }
+    Ok(())
+}
+
+/// The distinct paths of the `checked` origins, without any suggestion for
+/// any of the origins of the same path, in the order they were checked.
+fn clean_files<'a>(suggestions: &SuggestionSet, checked: &'a [ContentOrigin]) -> Vec<&'a Path> {
+    let with_mistakes = suggestions
+        .iter()
+        .filter(|(_origin, suggestions)| !suggestions.is_empty())
+        .map(|(origin, _suggestions)| origin.as_path())
+        .collect::<std::collections::HashSet<_>>();
+    let mut clean = indexmap::IndexSet::new();
+    for path in checked.iter().map(|origin| origin.as_path()) {
+        if !with_mistakes.contains(path) {
+            clean.insert(path);
+        }
+    }
+    clean.into_iter().collect()
+}
+
 /// Mode in which `cargo-spellcheck` operates
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Action {
     /// Only show errors
     Check,
+    /// Only print the number of errors, for use in scripts.
+    CountOnly,
     /// Interactively choose from checker provided suggestions.
-    Fix,
+    Fix {
+        /// Continue the session saved to [`RESUME_FILE`].
+        resume: bool,
+        /// Suffix of the backups of modified files, if any.
+        backup: Option<String>,
+    },
+    /// Apply the first replacement of every suggestion, without asking.
+    ReplaceWithFirst {
+        /// Suffix of the backups of modified files, if any.
+        backup: Option<String>,
+    },
 }
 
 impl Action {
+    fn backup_suffix(&self) -> Option<&str> {
+        match self {
+            Self::Fix { backup, .. } | Self::ReplaceWithFirst { backup } => backup.as_deref(),
+            _ => None,
+        }
+    }
+
     fn correction<'s>(
         &self,
         origin: ContentOrigin,
@@ -132,6 +297,10 @@ impl Action {
     ) -> Result<()> {
         match origin {
             ContentOrigin::CommonMarkFile(path) => self.correct_file(path, bandaids),
+            ContentOrigin::AsciiDocFile(path) => self.correct_file(path, bandaids),
+            ContentOrigin::RestructuredTextFile(path) => self.correct_file(path, bandaids),
+            ContentOrigin::PlainTextFile(path) => self.correct_file(path, bandaids),
+            ContentOrigin::CargoManifest(path) => self.correct_file(path, bandaids),
             ContentOrigin::RustSourceFile(path) => self.correct_file(path, bandaids),
             //@todo bandaids are relative to the doc-test, so fix the span with the one provided
             ContentOrigin::RustDocTest(path, _span) => self.correct_file(path, bandaids),
@@ -157,7 +326,10 @@ impl Action {
             .open(path)
             .map_err(|e| anyhow!("Failed to open {}", path.display()).context(e))?;
 
-        let mut reader = std::io::BufReader::new(ro);
+        let mut content = String::with_capacity(4096);
+        std::io::BufReader::new(ro)
+            .read_to_string(&mut content)
+            .map_err(|e| anyhow!("Failed to read {}", path.display()).context(e))?;
 
         const TEMPORARY: &'static str = ".spellcheck.tmp";
 
@@ -177,11 +349,7 @@ impl Action {
 
         correct_lines(
             bandaids.into_iter(),
-            (&mut reader)
-                .lines()
-                .filter_map(|line| line.ok())
-                .enumerate()
-                .map(|(lineno, content)| (lineno + 1, content)),
+            lines_with_endings(content.as_str()),
             &mut writer,
         )?;
 
@@ -196,6 +364,21 @@ impl Action {
     pub fn write_changes_to_disk(&self, userpicked: UserPicked, _config: &Config) -> Result<()> {
         if userpicked.total_count() > 0 {
             debug!("Writing changes back to disk");
+            if let Some(suffix) = self.backup_suffix() {
+                // a file may be the origin of multiple chunks, i.e. doc tests
+                let mut paths = userpicked
+                    .bandaids
+                    .iter()
+                    .filter(|(_origin, bandaids)| !bandaids.is_empty())
+                    .map(|(origin, _bandaids)| origin.as_path().to_owned())
+                    .collect::<Vec<_>>();
+                paths.sort();
+                paths.dedup();
+                for path in paths {
+                    let backup = backup_file(&path, suffix)?;
+                    info!("Backed up {} to {}", path.display(), backup.display());
+                }
+            }
             for (path, bandaids) in userpicked.bandaids.into_iter() {
                 self.correction(path, bandaids.into_iter())?;
             }
@@ -206,39 +389,165 @@ impl Action {
     }
 
     /// Purpose was to check, check complete, so print the results.
-    fn check(&self, suggestions_per_path: SuggestionSet, _config: &Config) -> Result<Finish> {
-        let mut count = 0usize;
-        for (_path, suggestions) in suggestions_per_path {
-            count += suggestions.len();
+    ///
+    /// Each suggestion is followed by `context_lines` lines of source
+    /// before and after the mistake, if non-zero. Machine readable
+    /// formats ignore `context_lines`. If `verbose`, the files of the
+    /// `checked` origins without any mistakes are listed to stderr.
+    fn check(
+        &self,
+        suggestions_per_path: SuggestionSet,
+        checked: &[ContentOrigin],
+        _config: &Config,
+        context_lines: usize,
+        format: Format,
+        verbose: bool,
+    ) -> Result<Finish> {
+        let count = suggestions_per_path.count();
+        match format {
+            Format::Checkstyle => {
+                print!("{}", checkstyle::checkstyle(&suggestions_per_path));
+                return Ok(Finish::MistakeCount(count));
+            }
+            Format::GitHub => {
+                print!("{}", github::github(&suggestions_per_path));
+                return Ok(Finish::MistakeCount(count));
+            }
+            Format::Human => {}
+        }
+        for (_path, suggestions) in suggestions_per_path.iter() {
             for suggestion in suggestions {
-                println!("{}", suggestion);
+                if context_lines == 0 {
+                    println!("{}", suggestion);
+                    continue;
+                }
+                match suggestion.with_context(context_lines) {
+                    Ok(with_context) => println!("{}", with_context),
+                    Err(e) => {
+                        warn!("Failed to load context lines: {}", e);
+                        println!("{}", suggestion);
+                    }
+                }
             }
         }
+        if verbose {
+            let clean = clean_files(&suggestions_per_path, checked);
+            for path in clean.iter() {
+                eprintln!("clean: {}", path.display());
+            }
+            eprintln!("clean: {} files", clean.len());
+        }
         Ok(Finish::MistakeCount(count))
     }
 
-    /// Run the requested action.
-    pub fn run(self, suggestions: SuggestionSet, config: &Config) -> Result<Finish> {
+    /// Run the requested action, where `checked` are the origins of all
+    /// checked chunks, including those without any suggestion.
+    pub fn run(
+        self,
+        suggestions: SuggestionSet,
+        checked: &[ContentOrigin],
+        config: &Config,
+        context_lines: usize,
+        format: Format,
+        verbose: bool,
+    ) -> Result<Finish> {
         match self {
-            Self::Check => self.check(suggestions, config),
-            Self::Fix => {
-                let (picked, user_sel) =
-                    interactive::UserPicked::select_interactive(suggestions, config)?;
-                if user_sel == UserSelection::Abort {
-                    Ok(Finish::Abort)
+            Self::Check => self.check(suggestions, checked, config, context_lines, format, verbose),
+            Self::CountOnly => {
+                let count = suggestions.total_count();
+                println!("{}", count);
+                Ok(Finish::MistakeCount(count))
+            }
+            Self::ReplaceWithFirst { .. } => self.replace_with_first(suggestions, config),
+            Self::Fix { .. } if suggestions.total_count() == 0 => {
+                // never touch the terminal if there is nothing to ask about
+                info!("Nothing to fix");
+                Ok(Finish::MistakeCount(0))
+            }
+            Self::Fix { resume, .. } => {
+                let resume_path = std::env::current_dir()?.join(RESUME_FILE);
+                let resumed = if resume {
+                    if !resume_path.is_file() {
+                        return Err(anyhow!(
+                            "No saved progress to resume, {} does not exist",
+                            resume_path.display()
+                        ));
+                    }
+                    let resumed = Progress::load(&resume_path)?;
+                    info!(
+                        "Resuming with {} decisions of the saved session",
+                        resumed.decided_count()
+                    );
+                    Some(resumed)
                 } else {
-                    let n = picked.total_count();
+                    None
+                };
+                let found = suggestions.count();
+                let (picked, user_sel, progress) = interactive::UserPicked::select_interactive(
+                    suggestions,
+                    config,
+                    resumed.as_ref(),
+                )?;
+                if user_sel == UserSelection::Save {
+                    progress.store(&resume_path)?;
+                    println!(
+                        "Saved the progress to {}, continue with `cargo spellcheck fix --resume`",
+                        resume_path.display()
+                    );
+                    return Ok(Finish::MistakeCount(found));
+                }
+                let finish = Self::finish_fix(found, &picked, user_sel);
+                if let Finish::MistakeCount(_) = finish {
                     self.write_changes_to_disk(picked, config)?;
-                    Ok(Finish::MistakeCount(n))
+                    if resume {
+                        fs::remove_file(&resume_path).map_err(|e| {
+                            anyhow!("Failed to remove {}", resume_path.display()).context(e)
+                        })?;
+                    }
                 }
+                Ok(finish)
             }
         }
     }
+
+    /// Apply the first replacement of all suggestions and print how many
+    /// were replaced per file.
+    fn replace_with_first(&self, suggestions: SuggestionSet, config: &Config) -> Result<Finish> {
+        let found = suggestions.count();
+        let picked = UserPicked::select_first(suggestions);
+        let replaced = picked
+            .bandaids
+            .iter()
+            .map(|(origin, bandaids)| (origin.as_path().display().to_string(), bandaids.len()))
+            .collect::<Vec<_>>();
+        let finish = Self::finish_fix(found, &picked, UserSelection::Nop);
+        self.write_changes_to_disk(picked, config)?;
+        for (path, count) in replaced.iter() {
+            println!("Replaced {} mistakes in {}", count, path);
+        }
+        println!(
+            "Replaced {} of {} mistakes",
+            replaced.iter().map(|(_path, count)| count).sum::<usize>(),
+            found
+        );
+        Ok(finish)
+    }
+
+    /// Determine how a fix session finished. Every one of the `found`
+    /// suggestions the user did not pick a replacement for remains a mistake.
+    fn finish_fix(found: usize, picked: &UserPicked, user_sel: UserSelection) -> Finish {
+        if user_sel == UserSelection::Abort {
+            Finish::Abort
+        } else {
+            Finish::MistakeCount(found.saturating_sub(picked.total_count()))
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::suggestion::{dummy_suggestion, single_line_chunk};
 
     const TEXT: &'static str = r#"
 I like unicorns every second Mondays.
@@ -273,14 +582,204 @@ I like banana icecream every third day.
             },
         ];
 
-        let lines = TEXT
-            .lines()
-            .map(|line| line.to_owned())
-            .enumerate()
-            .map(|(lineno, content)| (lineno + 1, content));
+        let lines = lines_with_endings(TEXT);
 
         correct_lines(bandaids.into_iter(), lines, &mut sink).expect("should be able to");
 
         assert_eq!(String::from_utf8_lossy(sink.as_slice()), CORRECTED);
     }
+
+    #[test]
+    fn replace_preserves_crlf() {
+        const SOURCE: &str = "/// A wrold  \r\n/// with trailing\t \r\n\r\nstruct A;";
+        let mut sink: Vec<u8> = Vec::with_capacity(1024);
+        let bandaids = vec![BandAid {
+            span: (1usize, 6..11).try_into().unwrap(),
+            replacement: "world".to_owned(),
+        }];
+
+        correct_lines(bandaids.into_iter(), lines_with_endings(SOURCE), &mut sink)
+            .expect("should be able to");
+
+        assert_eq!(
+            sink.as_slice(),
+            "/// A world  \r\n/// with trailing\t \r\n\r\nstruct A;".as_bytes()
+        );
+
+        // without any bandaids, the content is retained byte for byte
+        let mut sink: Vec<u8> = Vec::with_capacity(1024);
+        correct_lines(std::iter::empty(), lines_with_endings(SOURCE), &mut sink)
+            .expect("should be able to");
+        assert_eq!(sink.as_slice(), SOURCE.as_bytes());
+    }
+
+    #[test]
+    fn replace_multiline() {
+        const SOURCE: &str = "/// One two\n/// three four\n/// five\nstruct A;\n";
+        let mut sink: Vec<u8> = Vec::with_capacity(1024);
+        let bandaids = vec![
+            BandAid {
+                span: Span {
+                    start: LineColumn { line: 1, column: 3 },
+                    end: LineColumn {
+                        line: 2,
+                        column: 13,
+                    },
+                },
+                replacement: " One two three\n/// four".to_owned(),
+            },
+            BandAid {
+                span: (3usize, 4..8).try_into().unwrap(),
+                replacement: "six".to_owned(),
+            },
+        ];
+        let lines = lines_with_endings(SOURCE);
+
+        correct_lines(bandaids.into_iter(), lines, &mut sink).expect("should be able to");
+
+        assert_eq!(
+            String::from_utf8_lossy(sink.as_slice()),
+            "/// One two three\n/// four\n/// six\nstruct A;\n"
+        );
+    }
+
+    #[test]
+    fn replace_plain_text() {
+        const SOURCE: &str = "Release notes.\n\n  A *wrold* of `chagnes`.\n";
+        let chunk = CheckableChunk::from_plain_text(SOURCE);
+        let plain = chunk.erase_markdown();
+        let bandaids = vec![("wrold", "world"), ("chagnes", "changes")]
+            .into_iter()
+            .map(|(word, replacement)| {
+                let start = chunk.as_str().find(word).unwrap();
+                let spans = plain.find_spans(start..(start + word.len()));
+                assert_eq!(spans.len(), 1);
+                BandAid {
+                    span: *spans.values().next().unwrap(),
+                    replacement: replacement.to_owned(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut sink: Vec<u8> = Vec::with_capacity(1024);
+        correct_lines(bandaids.into_iter(), lines_with_endings(SOURCE), &mut sink)
+            .expect("should be able to");
+        assert_eq!(
+            String::from_utf8_lossy(sink.as_slice()),
+            "Release notes.\n\n  A *world* of `changes`.\n"
+        );
+    }
+
+    #[test]
+    fn fix_counts_skipped() {
+        let origin = ContentOrigin::TestEntity;
+        let mut picked = UserPicked::default();
+        picked.bandaids.insert(
+            origin,
+            vec![BandAid {
+                span: (2usize, 7..15).try_into().unwrap(),
+                replacement: "banana icecream".to_owned(),
+            }],
+        );
+
+        assert_eq!(
+            Action::finish_fix(1, &picked, UserSelection::Nop),
+            Finish::MistakeCount(0)
+        );
+        assert_eq!(
+            Action::finish_fix(3, &picked, UserSelection::Nop),
+            Finish::MistakeCount(2)
+        );
+        assert_eq!(
+            Action::finish_fix(3, &UserPicked::default(), UserSelection::Nop),
+            Finish::MistakeCount(3)
+        );
+        assert_eq!(
+            Action::finish_fix(3, &picked, UserSelection::Abort),
+            Finish::Abort
+        );
+    }
+
+    #[test]
+    fn clean() {
+        let chunk = single_line_chunk("A wrold", 1, 4);
+        let lib = PathBuf::from("/project/src/lib.rs");
+        let span = *chunk.find_spans(0..7).values().next().unwrap();
+        let doc_test = ContentOrigin::RustDocTest(lib.clone(), span);
+        let mut suggestions = SuggestionSet::new();
+        suggestions.add(
+            doc_test.clone(),
+            dummy_suggestion(doc_test.clone(), &chunk, 2..7),
+        );
+        let checked = vec![
+            ContentOrigin::CommonMarkFile(PathBuf::from("/project/README.md")),
+            ContentOrigin::RustSourceFile(lib),
+            doc_test,
+            ContentOrigin::RustSourceFile(PathBuf::from("/project/src/main.rs")),
+            ContentOrigin::CommonMarkFile(PathBuf::from("/project/README.md")),
+        ];
+        // `lib.rs` has a mistake within its doc test
+        assert_eq!(
+            clean_files(&suggestions, &checked),
+            vec![
+                Path::new("/project/README.md"),
+                Path::new("/project/src/main.rs")
+            ]
+        );
+        assert_eq!(clean_files(&SuggestionSet::new(), &checked).len(), 3);
+    }
+
+    #[test]
+    fn backup() {
+        let dir =
+            std::env::temp_dir().join(format!("cargo-spellcheck-backup-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("README.md");
+        fs::write(&path, "A wrold\n").unwrap();
+        let first = backup_file(&path, ".bak");
+        fs::write(&path, "A world\n").unwrap();
+        let second = backup_file(&path, ".bak");
+        let contents = [dir.join("README.md.bak"), dir.join("README.md.bak.1")]
+            .iter()
+            .map(|backup| fs::read_to_string(backup).ok())
+            .collect::<Vec<_>>();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(first.unwrap(), dir.join("README.md.bak"));
+        // the first backup is kept as it is
+        assert_eq!(second.unwrap(), dir.join("README.md.bak.1"));
+        assert_eq!(
+            contents,
+            vec![Some("A wrold\n".to_owned()), Some("A world\n".to_owned())]
+        );
+
+        assert!(validate_backup_suffix(".bak").is_ok());
+        assert!(validate_backup_suffix("~").is_ok());
+        assert!(validate_backup_suffix("").is_err());
+        assert!(validate_backup_suffix(".orig.md").is_err());
+        assert!(validate_backup_suffix(".asciidoc").is_err());
+        assert!(validate_backup_suffix("md").is_ok());
+        assert!(validate_backup_suffix("/bak").is_err());
+    }
+
+    #[test]
+    fn fix_nothing() {
+        // would fail to enter raw mode without a terminal
+        assert_eq!(
+            Action::Fix {
+                resume: false,
+                backup: None,
+            }
+            .run(
+                SuggestionSet::new(),
+                &[],
+                &Config::default(),
+                0,
+                Format::Human,
+                false
+            )
+            .unwrap(),
+            Finish::MistakeCount(0)
+        );
+    }
 }
@@ -0,0 +1,205 @@
+//! Save the progress of an interactive fix session, to resume it later
+//! with `fix --resume`.
+//!
+//! Suggestions are identified across runs by a fingerprint of their
+//! location and the flagged text. Files may change in between sessions, so
+//! a saved decision only applies if the very same suggestion is found
+//! again, everything else is asked anew.
+
+use super::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+/// Name of the file the progress is saved to, within the current directory.
+pub const RESUME_FILE: &str = ".spellcheck-resume.json";
+
+/// Identifies a suggestion across runs.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Fingerprint {
+    path: PathBuf,
+    /// Line and column of the first character of the span.
+    start: (usize, usize),
+    /// Line and column of the last character of the span.
+    end: (usize, usize),
+    /// The text the suggestion was made for.
+    flagged: String,
+}
+
+impl<'s> From<&Suggestion<'s>> for Fingerprint {
+    fn from(suggestion: &Suggestion<'s>) -> Self {
+        Self {
+            path: suggestion.origin.as_path().to_owned(),
+            start: (suggestion.span.start.line, suggestion.span.start.column),
+            end: (suggestion.span.end.line, suggestion.span.end.column),
+            flagged: suggestion.word(),
+        }
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "`{}` at {}:{}:{}",
+            self.flagged,
+            self.path.display(),
+            self.start.0,
+            self.start.1 + 1
+        )
+    }
+}
+
+/// A replacement picked in a previous session, which is not applied yet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedPick {
+    fingerprint: Fingerprint,
+    replacement: String,
+}
+
+/// What the user decided about a suggestion in a previous session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Decision<'p> {
+    Skipped,
+    Picked(&'p str),
+}
+
+/// The decisions of an interactive session.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Progress {
+    /// Suggestions the user did not want to apply.
+    skipped: Vec<Fingerprint>,
+    /// Replacements the user picked.
+    picked: Vec<SavedPick>,
+    /// Suggestions not decided upon when the session was saved.
+    remaining: Vec<Fingerprint>,
+}
+
+impl Progress {
+    /// Load the progress saved to `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = util::read_file(path)?;
+        serde_json::from_str(&content)
+            .map_err(|e| anyhow!("Failed to parse saved progress {}", path.display()).context(e))
+    }
+
+    /// Save the progress to `path`, replacing any previously saved one.
+    pub fn store(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| anyhow!("Failed to serialize progress").context(e))?;
+        fs::write(path, content)
+            .map_err(|e| anyhow!("Failed to write progress to {}", path.display()).context(e))
+    }
+
+    pub(super) fn skip(&mut self, suggestion: &Suggestion) {
+        self.skipped.push(Fingerprint::from(suggestion));
+    }
+
+    pub(super) fn pick(&mut self, suggestion: &Suggestion, replacement: &str) {
+        self.picked.push(SavedPick {
+            fingerprint: Fingerprint::from(suggestion),
+            replacement: replacement.to_owned(),
+        });
+    }
+
+    pub(super) fn remain(&mut self, suggestion: &Suggestion) {
+        self.remaining.push(Fingerprint::from(suggestion));
+    }
+
+    /// Number of suggestions decided upon.
+    pub fn decided_count(&self) -> usize {
+        self.skipped.len() + self.picked.len()
+    }
+
+    /// The decision about the suggestion with the given fingerprint.
+    pub(super) fn decision(&self, fingerprint: &Fingerprint) -> Option<Decision<'_>> {
+        if let Some(saved) = self
+            .picked
+            .iter()
+            .find(|saved| &saved.fingerprint == fingerprint)
+        {
+            Some(Decision::Picked(saved.replacement.as_str()))
+        } else if self.skipped.contains(fingerprint) {
+            Some(Decision::Skipped)
+        } else {
+            None
+        }
+    }
+
+    /// Check if the suggestion was known when the progress was saved.
+    pub(super) fn knows(&self, fingerprint: &Fingerprint) -> bool {
+        self.decision(fingerprint).is_some() || self.remaining.contains(fingerprint)
+    }
+
+    /// The picked replacements whose suggestion is not among `found`,
+    /// which happens if the file changed since the progress was saved.
+    pub(super) fn stale_picks<'p>(
+        &'p self,
+        found: &'p HashSet<Fingerprint>,
+    ) -> impl Iterator<Item = &'p Fingerprint> {
+        self.picked
+            .iter()
+            .map(|saved| &saved.fingerprint)
+            .filter(move |fingerprint| !found.contains(fingerprint))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::suggestion::{dummy_suggestion, single_line_chunk};
+
+    #[test]
+    fn roundtrip() {
+        let chunk = single_line_chunk("A wrold and a wrod", 1, 4);
+        let suggestion = |range: Range| Suggestion {
+            replacements: vec!["world".to_owned(), "word".to_owned()],
+            ..dummy_suggestion(
+                ContentOrigin::CommonMarkFile(PathBuf::from("/tmp/README.md")),
+                &chunk,
+                range,
+            )
+        };
+        let wrold = suggestion(2..7);
+        let wrod = suggestion(14..18);
+        let unknown = suggestion(8..11);
+
+        let mut progress = Progress::default();
+        progress.pick(&wrold, "world");
+        progress.skip(&wrod);
+        assert_eq!(progress.decided_count(), 2);
+
+        let path = std::env::temp_dir().join(format!(
+            "cargo-spellcheck-resume-{}.json",
+            std::process::id()
+        ));
+        progress.store(&path).unwrap();
+        let loaded = Progress::load(&path);
+        let _ = fs::remove_file(&path);
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded, progress);
+
+        assert_eq!(
+            loaded.decision(&Fingerprint::from(&wrold)),
+            Some(Decision::Picked("world"))
+        );
+        assert_eq!(
+            loaded.decision(&Fingerprint::from(&wrod)),
+            Some(Decision::Skipped)
+        );
+        assert_eq!(loaded.decision(&Fingerprint::from(&unknown)), None);
+        assert!(!loaded.knows(&Fingerprint::from(&unknown)));
+        assert_eq!(
+            Fingerprint::from(&wrold).to_string(),
+            "`wrold` at /tmp/README.md:1:7"
+        );
+
+        // the flagged text moved, so the pick does not apply anymore
+        let found = std::iter::once(Fingerprint::from(&wrod)).collect::<HashSet<_>>();
+        assert_eq!(
+            loaded.stale_picks(&found).collect::<Vec<_>>(),
+            vec![&Fingerprint::from(&wrold)]
+        );
+    }
+}
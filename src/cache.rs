@@ -0,0 +1,317 @@
+//! Reuse the suggestions of unchanged chunks across runs, enabled with
+//! `--cache`.
+//!
+//! A chunk is identified by its hash, which covers its content and the
+//! spans of its fragments, along with the words accepted within its origin.
+//! The path of the origin is not part of it, so a moved file is not checked
+//! again. The whole cache is discarded if the configuration, one of the
+//! dictionaries or the version of `cargo-spellcheck` changes.
+
+use crate::{
+    CheckableChunk, Config, ContentOrigin, Detector, Documentation, Range, Score, Suggestion,
+    SuggestionSet,
+};
+use anyhow::{anyhow, Result};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+/// A suggestion, relative to the chunk it was made for.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CachedSuggestion {
+    detector: Detector,
+    range: Range,
+    replacements: Vec<String>,
+    description: Option<String>,
+    score: Option<f32>,
+    rule_id: Option<String>,
+}
+
+impl<'s> From<&Suggestion<'s>> for CachedSuggestion {
+    fn from(suggestion: &Suggestion<'s>) -> Self {
+        Self {
+            detector: suggestion.detector,
+            range: suggestion.range.clone(),
+            replacements: suggestion.replacements.clone(),
+            description: suggestion.description.clone(),
+            score: suggestion.score.map(|score| score.get()),
+            rule_id: suggestion.rule_id.clone(),
+        }
+    }
+}
+
+/// All dictionary files hunspell might consult, whether present or not.
+fn dictionaries(config: &Config) -> Vec<PathBuf> {
+    let hunspell = if let Some(ref hunspell) = config.hunspell {
+        hunspell
+    } else {
+        return Vec::new();
+    };
+    let mut paths = Vec::with_capacity(8);
+    for search_dir in hunspell.search_dirs() {
+        for lang in hunspell.langs() {
+            paths.push(search_dir.join(lang).with_extension("dic"));
+            paths.push(search_dir.join(lang).with_extension("aff"));
+        }
+    }
+    for extra in hunspell.extra_dictonaries() {
+        paths.push(extra.dic().to_owned());
+        paths.extend(extra.aff().map(Path::to_owned));
+    }
+    paths.extend(hunspell.user_dictionary());
+    paths
+}
+
+/// Hash everything besides the chunks the suggestions depend on.
+fn fingerprint(config: &Config) -> Result<u64> {
+    let mut hasher = DefaultHasher::new();
+    env!("CARGO_PKG_VERSION").hash(&mut hasher);
+    serde_json::to_string(config)
+        .map_err(|e| anyhow!("Failed to serialize the configuration").context(e))?
+        .hash(&mut hasher);
+    for path in dictionaries(config) {
+        path.hash(&mut hasher);
+        // a changed dictionary is told apart by its size and modification time
+        if let Ok(metadata) = path.metadata() {
+            metadata.len().hash(&mut hasher);
+            metadata.modified().ok().hash(&mut hasher);
+        }
+    }
+    Ok(hasher.finish())
+}
+
+/// Suggestions of the chunks checked in previous runs.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Cache {
+    fingerprint: u64,
+    /// Suggestions by the key of their chunk, an empty list for chunks
+    /// without any mistake.
+    chunks: HashMap<u64, Vec<CachedSuggestion>>,
+    /// Keys of the chunks whose suggestions were reused during this run.
+    #[serde(skip)]
+    reused: HashSet<u64>,
+    /// The language of a markdown file is detected from all of its chunks.
+    #[serde(skip)]
+    auto_detect_language: bool,
+}
+
+impl Cache {
+    /// Load the cache at `path`, which is empty if it does not exist yet,
+    /// can not be parsed, or was created with a different `config`.
+    pub fn load(path: &Path, config: &Config) -> Result<Self> {
+        let fingerprint = fingerprint(config)?;
+        let mut cache = if path.is_file() {
+            let loaded = crate::util::read_file(path).and_then(|content| {
+                serde_json::from_str::<Self>(&content)
+                    .map_err(|e| anyhow!("Failed to parse cache {}", path.display()).context(e))
+            });
+            match loaded {
+                Ok(cache) if cache.fingerprint == fingerprint => cache,
+                Ok(_cache) => {
+                    info!(
+                        "The configuration or a dictionary changed, discarding the cache {}",
+                        path.display()
+                    );
+                    Self::default()
+                }
+                Err(e) => {
+                    warn!("Discarding the cache {}: {:?}", path.display(), e);
+                    Self::default()
+                }
+            }
+        } else {
+            Self::default()
+        };
+        cache.fingerprint = fingerprint;
+        cache.auto_detect_language = config
+            .hunspell
+            .as_ref()
+            .map(|hunspell| hunspell.auto_detect_language())
+            .unwrap_or(false);
+        debug!("Loaded {} chunks from the cache", cache.chunks.len());
+        Ok(cache)
+    }
+
+    /// Save the cache to `path`, replacing the previous one.
+    pub fn store(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string(self)
+            .map_err(|e| anyhow!("Failed to serialize cache").context(e))?;
+        fs::write(path, content)
+            .map_err(|e| anyhow!("Failed to write cache to {}", path.display()).context(e))
+    }
+
+    /// Identify `chunk`, one of the `chunks` of `origin` within `docs`.
+    fn key(
+        &self,
+        docs: &Documentation,
+        origin: &ContentOrigin,
+        chunks: &[CheckableChunk],
+        chunk: &CheckableChunk,
+    ) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        origin.kind().hash(&mut hasher);
+        chunk.hash(&mut hasher);
+        if let Some(accepted) = docs.accepted_words(origin) {
+            let mut accepted = accepted.iter().collect::<Vec<_>>();
+            accepted.sort();
+            accepted.hash(&mut hasher);
+        }
+        if self.auto_detect_language {
+            if let ContentOrigin::CommonMarkFile(_) = origin {
+                chunks.hash(&mut hasher);
+            }
+        }
+        hasher.finish()
+    }
+
+    /// Split `docs` into the chunks which must be checked, and the
+    /// suggestions of all others, restored from the cache.
+    pub fn split<'a>(&mut self, docs: &'a Documentation) -> (Documentation, SuggestionSet<'a>) {
+        let mut restored = SuggestionSet::new();
+        let mut hits = HashSet::<*const CheckableChunk>::new();
+        for (origin, chunks) in docs.iter() {
+            for chunk in chunks {
+                let key = self.key(docs, origin, chunks, chunk);
+                let cached = if let Some(cached) = self.chunks.get(&key) {
+                    cached
+                } else {
+                    continue;
+                };
+                hits.insert(chunk);
+                self.reused.insert(key);
+                for cached in cached {
+                    // the chunk may have moved to another origin
+                    for (range, span) in chunk.find_spans(cached.range.clone()) {
+                        restored.add(
+                            origin.clone(),
+                            Suggestion {
+                                detector: cached.detector,
+                                origin: origin.clone(),
+                                chunk,
+                                span,
+                                range,
+                                replacements: cached.replacements.clone(),
+                                description: cached.description.clone(),
+                                score: cached.score.and_then(Score::new),
+                                rule_id: cached.rule_id.clone(),
+                            },
+                        );
+                    }
+                }
+            }
+        }
+        info!(
+            "Reusing the suggestions of {} cached chunks",
+            self.reused.len()
+        );
+        let to_check =
+            docs.filter_chunks(|_origin, chunk| !hits.contains(&(chunk as *const CheckableChunk)));
+        (to_check, restored)
+    }
+
+    /// Remember the `suggestions` for the chunks of `checked`, and forget
+    /// all chunks which were neither checked nor reused during this run.
+    pub fn update(&mut self, checked: &Documentation, suggestions: &SuggestionSet) {
+        let mut by_chunk = HashMap::<*const CheckableChunk, Vec<CachedSuggestion>>::new();
+        for (_origin, suggestions) in suggestions.iter() {
+            for suggestion in suggestions {
+                by_chunk
+                    .entry(suggestion.chunk)
+                    .or_insert_with(Vec::new)
+                    .push(CachedSuggestion::from(suggestion));
+            }
+        }
+        let reused = std::mem::take(&mut self.reused);
+        self.chunks.retain(|key, _suggestions| reused.contains(key));
+        for (origin, chunks) in checked.iter() {
+            for chunk in chunks {
+                let key = self.key(checked, origin, chunks, chunk);
+                let suggestions = by_chunk
+                    .remove(&(chunk as *const CheckableChunk))
+                    .unwrap_or_default();
+                self.chunks.insert(key, suggestions);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "/// A wrold\nstruct A;\n\n/// Fine\nstruct B;\n";
+
+    #[test]
+    fn reuse() {
+        let config = Config::default();
+        let origin = ContentOrigin::RustSourceFile(PathBuf::from("/tmp/cached.rs"));
+        let docs = Documentation::from((origin.clone(), SOURCE));
+        let mut cache = Cache::load(Path::new("/does/not/exist.json"), &config).unwrap();
+        let (to_check, restored) = cache.split(&docs);
+        assert_eq!(to_check.iter().next().unwrap().1.len(), 2);
+        assert_eq!(restored.total_count(), 0);
+
+        let (_origin, chunks) = to_check.iter().next().unwrap();
+        let chunk = &chunks[0];
+        let (range, span) = chunk.find_spans(3..8).into_iter().next().unwrap();
+        let mut suggestions = SuggestionSet::new();
+        suggestions.add(
+            origin.clone(),
+            Suggestion {
+                detector: Detector::Hunspell,
+                origin: origin.clone(),
+                chunk,
+                span,
+                range,
+                replacements: vec!["world".to_owned()],
+                description: None,
+                score: Score::new(0.5),
+                rule_id: None,
+            },
+        );
+        cache.update(&to_check, &suggestions);
+
+        let path = std::env::temp_dir().join(format!(
+            "cargo-spellcheck-cache-{}.json",
+            std::process::id()
+        ));
+        cache.store(&path).unwrap();
+        let loaded = Cache::load(&path, &config);
+        let mut other = Config::default();
+        other.hunspell.as_mut().unwrap().min_word_length = Some(3);
+        let discarded = Cache::load(&path, &other);
+        let _ = fs::remove_file(&path);
+        let mut cache = loaded.unwrap();
+        assert_eq!(cache.chunks.len(), 2);
+        assert!(discarded.unwrap().chunks.is_empty());
+
+        // the second doc comment changed, the file was renamed
+        let moved = ContentOrigin::RustSourceFile(PathBuf::from("/tmp/moved.rs"));
+        let docs = Documentation::from((moved.clone(), SOURCE.replace("Fine", "Fien").as_str()));
+        let (to_check, restored) = cache.split(&docs);
+        let (_origin, chunks) = to_check.iter().next().unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].as_str(), " Fien");
+        let (origin, restored) = restored.iter().next().unwrap();
+        assert_eq!(origin, &moved);
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].origin, moved);
+        assert_eq!(restored[0].span, span);
+        assert_eq!(restored[0].replacements, vec!["world"]);
+        assert_eq!(restored[0].score, Score::new(0.5));
+
+        // only the chunks of this run are kept
+        cache.update(&to_check, &SuggestionSet::new());
+        assert_eq!(cache.chunks.len(), 2);
+        assert_eq!(cache.chunks.values().filter(|v| v.is_empty()).count(), 1);
+
+        // words accepted within the origin are part of the key
+        let mut docs = docs;
+        docs.accept_words(&moved, vec!["wrold".to_owned()]);
+        assert_eq!(cache.split(&docs).1.total_count(), 0);
+    }
+}
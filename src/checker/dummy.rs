@@ -2,8 +2,8 @@
 //!
 //! A test checker, only available for unit tests.
 
-use super::tokenize;
 use super::Checker;
+use super::Tokenizer;
 use crate::documentation::Documentation;
 use crate::suggestion::{Detector, Suggestion, SuggestionSet};
 use crate::util::sub_chars;
@@ -27,8 +27,11 @@ impl Checker for DummyChecker {
                     .iter()
                     .next()
                     .expect("DummyChecker expects at least one chunk");
-                let plain = chunk.erase_markdown();
-                for (index, range) in dbg!(tokenize(plain.as_str())).into_iter().enumerate() {
+                let plain = docu.erase_markdown(chunk);
+                for (index, range) in dbg!(Tokenizer::default().tokenize(plain.as_str()))
+                    .into_iter()
+                    .enumerate()
+                {
                     trace!("Token: >{}<", sub_chars(plain.as_str(), range.clone()));
                     let detector = Detector::Dummy;
                     let range2span = plain.find_spans(range.clone());
@@ -47,6 +50,8 @@ impl Checker for DummyChecker {
                             replacements,
                             chunk,
                             description: None,
+                            score: None,
+                            rule_id: None,
                         };
                         acc.add(origin.clone(), suggestion);
                     }
@@ -0,0 +1,266 @@
+//! External
+//!
+//! Shell out to an arbitrary command, which allows to plug in domain
+//! specific tools without recompiling.
+//!
+//! The command is spawned once per file. It receives a single json
+//! object on stdin, containing the path of the file and the plain text
+//! of all chunks of the file, with markdown already erased:
+//!
+//! ```json
+//! { "path": "src/lib.rs", "chunks": [ { "content": "A wrold" } ] }
+//! ```
+//!
+//! It must exit successfully and print a json array of the mistakes it
+//! found to stdout. `chunk` is the index within `chunks`, `range` the
+//! character (not byte) offsets within its `content`, with an exclusive
+//! end. `replacements`, `description` and `score`, the confidence between
+//! `0.0` and `1.0`, may be omitted:
+//!
+//! ```json
+//! [ { "chunk": 0, "range": { "start": 2, "end": 7 }, "replacements": ["world"], "description": "Unknown word.", "score": 0.9 } ]
+//! ```
+
+use super::Checker;
+use crate::config::ExternalConfig;
+use crate::documentation::PlainOverlay;
+use crate::{Detector, Documentation, Range, Score, Suggestion, SuggestionSet};
+
+use anyhow::{anyhow, bail, Result};
+use log::{debug, trace};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+pub(crate) struct ExternalChecker;
+
+/// What is written to the stdin of the command.
+#[derive(Debug, Serialize)]
+struct Request<'a> {
+    path: &'a Path,
+    chunks: Vec<RequestChunk<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct RequestChunk<'a> {
+    content: &'a str,
+}
+
+/// A single mistake as reported by the command.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct Flagged {
+    chunk: usize,
+    range: Range,
+    #[serde(default)]
+    replacements: Vec<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    score: Option<f32>,
+}
+
+/// Parse the output of the command, rejecting references to chunks or
+/// characters which do not exist.
+fn parse_response(stdout: &[u8], chunks: &[&str]) -> Result<Vec<Flagged>> {
+    let flagged: Vec<Flagged> = serde_json::from_slice(stdout)
+        .map_err(|e| anyhow!("Failed to parse the output of the external checker").context(e))?;
+    for item in flagged.iter() {
+        let content = if let Some(content) = chunks.get(item.chunk) {
+            content
+        } else {
+            bail!(
+                "External checker flagged chunk {}, but only {} chunks exist",
+                item.chunk,
+                chunks.len()
+            )
+        };
+        if item.range.start >= item.range.end || item.range.end > content.chars().count() {
+            bail!(
+                "External checker flagged the invalid range {:?} of chunk {}",
+                item.range,
+                item.chunk
+            )
+        }
+        if let Some(score) = item.score {
+            if Score::new(score).is_none() {
+                bail!(
+                    "External checker flagged chunk {} with the score {}, which is not within 0.0..=1.0",
+                    item.chunk,
+                    score
+                )
+            }
+        }
+    }
+    Ok(flagged)
+}
+
+/// Spawn the configured command, feed it the `request` and collect its output.
+fn run(config: &ExternalConfig, request: &Request) -> Result<Vec<u8>> {
+    let input = serde_json::to_vec(request)
+        .map_err(|e| anyhow!("Failed to serialize the external checker input").context(e))?;
+    let mut child = Command::new(config.command.as_str())
+        .args(config.args())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| anyhow!("Failed to spawn external checker `{}`", config.command).context(e))?;
+    // write from a separate thread, so a command which writes before it
+    // read all of its input can not dead lock
+    let mut stdin = child
+        .stdin
+        .take()
+        .expect("stdin is piped, it must be present. qed");
+    let writer = std::thread::spawn(move || stdin.write_all(&input));
+    let output = child
+        .wait_with_output()
+        .map_err(|e| anyhow!("Failed to obtain the output of `{}`", config.command).context(e))?;
+    if let Ok(Err(e)) = writer.join() {
+        debug!("External checker did not consume all input: {}", e);
+    }
+    if !output.status.success() {
+        bail!(
+            "External checker `{}` failed with {}",
+            config.command,
+            output.status
+        )
+    }
+    Ok(output.stdout)
+}
+
+impl Checker for ExternalChecker {
+    type Config = ExternalConfig;
+
+    fn check<'a, 's>(docu: &'a Documentation, config: &Self::Config) -> Result<SuggestionSet<'s>>
+    where
+        'a: 's,
+    {
+        let mut acc = SuggestionSet::new();
+        for (origin, chunks) in docu.iter() {
+            let plains = chunks
+                .iter()
+                .map(|chunk| docu.erase_markdown(chunk))
+                .collect::<Vec<PlainOverlay>>();
+            let contents = plains
+                .iter()
+                .map(|plain| plain.as_str())
+                .collect::<Vec<_>>();
+            let request = Request {
+                path: origin.as_path(),
+                chunks: contents
+                    .iter()
+                    .map(|content| RequestChunk { content })
+                    .collect(),
+            };
+            debug!(
+                "Running `{}` on {}",
+                config.command,
+                origin.as_path().display()
+            );
+            let stdout = run(config, &request)?;
+            let flagged = parse_response(&stdout, &contents).map_err(|e| {
+                e.context(anyhow!(
+                    "External checker failed on {}",
+                    origin.as_path().display()
+                ))
+            })?;
+            for item in flagged {
+                trace!("External checker flagged {:?}", &item);
+                let plain = &plains[item.chunk];
+                for (range, span) in plain.find_spans(item.range.clone()) {
+                    acc.add(
+                        origin.clone(),
+                        Suggestion {
+                            detector: Detector::External,
+                            range,
+                            span,
+                            origin: origin.clone(),
+                            replacements: item.replacements.clone(),
+                            chunk: &chunks[item.chunk],
+                            description: item.description.clone(),
+                            score: item.score.and_then(Score::new),
+                            rule_id: None,
+                        },
+                    )
+                }
+            }
+        }
+        Ok(acc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn response() {
+        let chunks = ["A wrold", "Fine"];
+        assert_eq!(
+            parse_response(
+                br#"[{"chunk":0,"range":{"start":2,"end":7},"replacements":["world"]}]"#,
+                &chunks
+            )
+            .unwrap(),
+            vec![Flagged {
+                chunk: 0,
+                range: 2..7,
+                replacements: vec!["world".to_owned()],
+                description: None,
+                score: None,
+            }]
+        );
+        assert_eq!(
+            parse_response(
+                br#"[{"chunk":0,"range":{"start":2,"end":7},"score":0.25}]"#,
+                &chunks
+            )
+            .unwrap()[0]
+                .score,
+            Some(0.25)
+        );
+        assert!(parse_response(
+            br#"[{"chunk":0,"range":{"start":2,"end":7},"score":1.5}]"#,
+            &chunks
+        )
+        .is_err());
+        assert!(parse_response(b"[]", &chunks).unwrap().is_empty());
+        assert!(parse_response(br#"[{"chunk":2,"range":{"start":0,"end":1}}]"#, &chunks).is_err());
+        assert!(parse_response(br#"[{"chunk":1,"range":{"start":0,"end":5}}]"#, &chunks).is_err());
+        assert!(parse_response(b"not json", &chunks).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn shell_out() {
+        let docs = Documentation::from((
+            crate::ContentOrigin::RustSourceFile(std::path::PathBuf::from("/tmp/external.rs")),
+            "/// A wrold\nstruct A;\n",
+        ));
+        let config = ExternalConfig {
+            enabled: None,
+            command: "sh".to_owned(),
+            args: Some(vec![
+                "-c".to_owned(),
+                r#"cat > /dev/null; echo '[{"chunk":0,"range":{"start":2,"end":7},"replacements":["world"]}]'"#
+                    .to_owned(),
+            ]),
+        };
+        let suggestions = ExternalChecker::check(&docs, &config).unwrap();
+        assert_eq!(suggestions.total_count(), 1);
+        let (_, suggestions) = suggestions.iter().next().unwrap();
+        assert_eq!(suggestions[0].detector, Detector::External);
+        assert_eq!(suggestions[0].replacements, vec!["world".to_owned()]);
+        assert_eq!(suggestions[0].span.start.line, 1);
+        assert_eq!(suggestions[0].span.start.column, 6);
+        assert_eq!(suggestions[0].span.end.column, 10);
+
+        let failing = ExternalConfig {
+            enabled: None,
+            command: "false".to_owned(),
+            args: None,
+        };
+        assert!(ExternalChecker::check(&docs, &failing).is_err());
+    }
+}
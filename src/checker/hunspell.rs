@@ -4,11 +4,27 @@
 //! and checks the individual tokens against the dictionary using
 //! the defined affixes.
 //! Can handle multiple dictionaries.
+//! With language auto detection, the language of each markdown file is
+//! picked among the configured ones.
+//! Origins are checked in parallel by a pool of workers, each with its
+//! own set of dictionaries.
+//! If hunspell has no suggestions for a mistake and `fallback_suggestions`
+//! is enabled, the words listed in the `.dic` files are ranked by their
+//! edit distance to the mistake instead, since `libhunspell` provides no
+//! way to enumerate the words of a dictionary.
 
-use super::{tokenize, Checker, Detector, Documentation, Suggestion, SuggestionSet};
+use super::language::detect;
+use super::{
+    edit_distance, Checker, Detector, Documentation, Quirks, Ranking, Suggestion, SuggestionSet,
+    Tokenizer,
+};
+use crate::config::{ExtraDictionary, HunspellConfig};
 use crate::util::sub_chars;
+use crate::{CheckableChunk, ContentOrigin};
 use log::{debug, trace};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Mutex;
 
 use hunspell_rs::Hunspell;
 
@@ -16,66 +32,263 @@ use anyhow::{anyhow, bail, Result};
 
 pub struct HunspellChecker;
 
-impl Checker for HunspellChecker {
-    type Config = crate::config::HunspellConfig;
-    fn check<'a, 's>(docu: &'a Documentation, config: &Self::Config) -> Result<SuggestionSet<'s>>
-    where
-        'a: 's,
+/// Words known to be part of the dictionary of a language, used to
+/// detect broken dictionaries right after loading them.
+///
+/// Covers `en_US`, `en_GB`, `en_AU`, `en_CA`, `de_DE`, `fr_FR` and
+/// `es_ES`. To cover another language, add a pair of the language code
+/// (as used for the `lang` config value) and a common word, which is
+/// valid as is, without any affixes applied.
+const SANITY_SENTINELS: &[(&str, &str)] = &[
+    ("en_US", "Test"),
+    ("en_GB", "Test"),
+    ("en_AU", "Test"),
+    ("en_CA", "Test"),
+    ("de_DE", "Haus"),
+    ("fr_FR", "maison"),
+    ("es_ES", "casa"),
+];
+
+/// Verify the dictionary loaded for `lang` from `dic` accepts and
+/// suggests the sentinel word of the language, if there is one.
+fn sanity_check(hunspell: &Hunspell, lang: &str, dic: &str) -> Result<()> {
+    let sentinel = if let Some((_, sentinel)) = SANITY_SENTINELS
+        .iter()
+        .find(|(sentinel_lang, _)| *sentinel_lang == lang)
     {
-        // let hunspell = lazy_static::lazy_static!{
-        //     static ref HUNSPELL_CTX: Result<Hunspell> = {
+        sentinel
+    } else {
+        trace!("No sanity sentinel for {}, skipping dictionary check", lang);
+        return Ok(());
+    };
+    if !hunspell.check(sentinel) {
+        bail!(
+            "Dictionary {} for {} appears to be broken, it does not contain >{}<. Verify the .dic and .aff files are valid and belong together.",
+            dic,
+            lang,
+            sentinel
+        )
+    }
+    // suggestion must contain the word itself if it is valid
+    if !hunspell
+        .suggest(sentinel)
+        .iter()
+        .any(|word| word == sentinel)
+    {
+        bail!(
+            "Dictionary {} for {} appears to be broken, suggestions for >{}< do not contain the word itself. Verify the .aff file is valid.",
+            dic,
+            lang,
+            sentinel
+        )
+    }
+    Ok(())
+}
 
-        //     }
-        // };
+/// An acronym consists of at least two letters, all of them uppercase.
+fn is_acronym(word: &str) -> bool {
+    word.chars().count() >= 2 && word.chars().all(|c| c.is_alphabetic() && c.is_uppercase())
+}
 
-        let search_dirs = config.search_dirs();
+/// Number of words offered by the fallback at most.
+const FALLBACK_COUNT: usize = 5;
 
-        let lang = config.lang();
+/// Number of edits a word offered by the fallback requires at most, short
+/// words allow fewer edits.
+const FALLBACK_MAX_DISTANCE: usize = 3;
 
-        // lookup paths are really just an attempt to provide a dictionary, so be more forgiving
-        // when encountering errors here
-        let (dic, aff): (PathBuf, PathBuf) = search_dirs
-            .into_iter()
-            .filter(|search_dir| {
-                let keep = search_dir.is_dir();
-                if !keep {
-                    // search_dir also contains the default paths, so just silently ignore these
-                    debug!(
-                        "Dictionary search path is not a directory {}",
-                        search_dir.display()
-                    );
-                } else {
-                    debug!(
-                        "Found dictionary search path {}",
-                        search_dir.display()
-                    );
-                }
-                keep
-            })
-            .find_map(|search_dir| {
-                let dic = search_dir.join(lang).with_extension("dic");
-                if !dic.is_file() {
-                    debug!(
-                        "Dictionary path dervied from search dir is not a file {}",
-                        dic.display()
-                    );
-                    return None;
-                }
-                let aff = search_dir.join(lang).with_extension("aff");
-                if !aff.is_file() {
-                    debug!(
-                        "Affixes path dervied from search dir is not a file {}",
-                        aff.display()
-                    );
-                    return None;
+/// The words listed in the `.dic` file at `path`.
+///
+/// The first line holds the number of entries, every following line a
+/// word, optionally followed by `/` and its affix flags and by a tab and
+/// morphological fields. Only the words as listed are returned, without
+/// any affixes applied.
+fn dictionary_words(path: &str) -> Result<Vec<String>> {
+    let content = std::fs::read(path)
+        .map_err(|e| anyhow!("Failed to read dictionary {}", path).context(e))?;
+    // dictionaries are not necessarily utf-8 encoded, see `SET` of the .aff file
+    let content = String::from_utf8_lossy(&content);
+    Ok(content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let entry = line.split('\t').next()?;
+            let mut word = String::with_capacity(entry.len());
+            let mut escaped = false;
+            for c in entry.chars() {
+                match c {
+                    '\\' if !escaped => escaped = true,
+                    '/' if !escaped => break,
+                    c => {
+                        word.push(c);
+                        escaped = false;
+                    }
                 }
-                debug!("Using dic {} and aff {}", dic.display(), aff.display());
-                Some((dic, aff))
-            })
-            .ok_or_else(|| {
-                anyhow!("Failed to find any {lang}.dic / {lang}.aff in any search dir or no search provided",
-                lang = lang)
-            })?;
+            }
+            let word = word.trim();
+            if word.is_empty() || word.starts_with('#') {
+                None
+            } else {
+                Some(word.to_owned())
+            }
+        })
+        .collect())
+}
+
+/// The words closest to `word` by edit distance, ignoring case, the
+/// closest first and otherwise in the order of `words`.
+fn closest_words(word: &str, words: &[String]) -> Vec<String> {
+    let lowercase = word.to_lowercase();
+    let len = lowercase.chars().count();
+    let max_distance = FALLBACK_MAX_DISTANCE.min((len + 1) / 2);
+    let mut candidates = words
+        .iter()
+        .filter(|candidate| {
+            let candidate_len = candidate.chars().count();
+            candidate_len.max(len) - candidate_len.min(len) <= max_distance
+        })
+        .filter(|candidate| candidate.as_str() != word)
+        .filter_map(|candidate| {
+            let distance = edit_distance(&lowercase, &candidate.to_lowercase());
+            if distance <= max_distance {
+                Some((distance, candidate))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|(distance, _candidate)| *distance);
+    let mut closest = Vec::with_capacity(FALLBACK_COUNT);
+    for (_distance, candidate) in candidates {
+        if closest.len() == FALLBACK_COUNT {
+            break;
+        }
+        if !closest.contains(candidate) {
+            closest.push(candidate.to_owned());
+        }
+    }
+    closest
+}
+
+/// Where dictionaries are commonly installed to and how.
+const INSTALL_HINT: &str = if cfg!(target_os = "linux") {
+    "Install the hunspell dictionary package of your distribution, i.e. `hunspell-en-us` on Debian and Ubuntu or `hunspell-en-US` on Fedora."
+} else if cfg!(target_os = "macos") {
+    "Place the .dic and .aff files in `~/Library/Spelling` or `/Library/Spelling`."
+} else {
+    "Place the .dic and .aff files in a directory and add it to `search_dirs`."
+};
+
+/// Explain that no dictionary for `lang` was found, listing every
+/// search dir which was tried and why it was rejected.
+fn missing_dictionary(lang: &str, attempts: &[(PathBuf, &str)]) -> String {
+    let mut message = format!("Failed to find {lang}.dic and {lang}.aff", lang = lang);
+    if attempts.is_empty() {
+        message.push_str(", no search dirs are configured.");
+    } else {
+        message.push_str(", searched:");
+        for (search_dir, reason) in attempts {
+            message.push_str(&format!("\n  {} ({})", search_dir.display(), reason));
+        }
+    }
+    message.push('\n');
+    message.push_str(INSTALL_HINT);
+    message.push_str("\nAdditional directories can be listed as `search_dirs` in the `[hunspell]` section of the configuration file, use `cargo spellcheck config --user` to create one.");
+    message
+}
+
+/// Log why `word` was not accepted, at debug level with target `explain`.
+fn explain(
+    word: &str,
+    dictionaries: &[&str],
+    quirks: &Quirks,
+    accepted_count: usize,
+    replacements: &[String],
+) {
+    debug!(target: "explain", ">{}< was not accepted", word);
+    for dictionary in dictionaries {
+        debug!(target: "explain", "  consulted dictionary {}", dictionary);
+    }
+    debug!(
+        target: "explain",
+        "  consulted {} accepted words of the origin", accepted_count
+    );
+    for (quirk, transformed) in quirks.transformations(word) {
+        debug!(target: "explain", "  quirk {} yielded >{}<, not accepted either", quirk, transformed);
+    }
+    debug!(target: "explain", "  candidates: {:?}", replacements);
+}
+
+/// A loaded dictionary of a single language, including all extra dictionaries.
+struct Dictionary {
+    lang: String,
+    hunspell: Hunspell,
+    /// Extra dictionaries with affixes of their own, which can not be
+    /// added to `hunspell`, since it only applies its own affixes.
+    affixed: Vec<Hunspell>,
+    /// Paths of all consulted dictionaries, the language dictionary first.
+    paths: Vec<String>,
+    /// The words of all dictionaries for the fallback suggestions, empty
+    /// unless enabled.
+    words: Vec<String>,
+}
+
+/// Convert a dictionary path to `str`, as required by `libhunspell`.
+fn path_to_str(path: &Path) -> Result<&str> {
+    path.to_str().ok_or_else(|| {
+        anyhow!(
+            "Failed to convert extra dictionary path to str {}",
+            path.display()
+        )
+    })
+}
+
+impl Dictionary {
+    /// Find the dictionary of `lang` within the search dirs and add all
+    /// extra dictionaries to it.
+    fn load(config: &HunspellConfig, lang: &str) -> Result<Self> {
+        let search_dirs = config.search_dirs();
+        debug!("Effective dictionary search dirs {:?}", search_dirs);
+
+        // lookup paths are really just an attempt to provide a dictionary, so be more forgiving
+        // when encountering errors here, but keep track of what was tried
+        let mut attempts = Vec::with_capacity(search_dirs.len());
+        let mut found = None;
+        for search_dir in search_dirs.iter().cloned() {
+            if !search_dir.is_dir() {
+                // search_dir also contains the default paths, so just silently ignore these
+                debug!(
+                    "Dictionary search path is not a directory {}",
+                    search_dir.display()
+                );
+                attempts.push((search_dir, "not a directory"));
+                continue;
+            }
+            debug!("Found dictionary search path {}", search_dir.display());
+            let dic = search_dir.join(lang).with_extension("dic");
+            if !dic.is_file() {
+                debug!(
+                    "Dictionary path dervied from search dir is not a file {}",
+                    dic.display()
+                );
+                attempts.push((search_dir, "no .dic file"));
+                continue;
+            }
+            let aff = search_dir.join(lang).with_extension("aff");
+            if !aff.is_file() {
+                debug!(
+                    "Affixes path dervied from search dir is not a file {}",
+                    aff.display()
+                );
+                attempts.push((search_dir, "no .aff file"));
+                continue;
+            }
+            debug!("Using dic {} and aff {}", dic.display(), aff.display());
+            found = Some((dic, aff));
+            break;
+        }
+        let (dic, aff): (PathBuf, PathBuf) =
+            found.ok_or_else(|| anyhow!("{}", missing_dictionary(lang, &attempts)))?;
 
         let dic = dic.to_str().unwrap();
         let aff = aff.to_str().unwrap();
@@ -83,84 +296,588 @@ impl Checker for HunspellChecker {
         let mut hunspell = Hunspell::new(aff, dic);
         hunspell.add_dictionary(dic);
 
-        if cfg!(debug_assertions) && lang == "en_US" {
-            // "Test" is a valid word
-            assert!(hunspell.check("Test"));
-            // suggestion must contain the word itself if it is valid
-            assert!(hunspell.suggest("Test").contains(&"Test".to_string()));
-        }
+        sanity_check(&hunspell, lang, dic)?;
+
+        // the user dictionary is treated just like any other plain extra dictionary
+        let user_dic = config.user_dictionary().map(ExtraDictionary::Plain);
+        let extra_dictonaries = config
+            .extra_dictonaries()
+            .iter()
+            .chain(user_dic.iter())
+            .collect::<Vec<_>>();
 
-        // suggestion must contain the word itself if it is valid extra dictionary
         // be more strict about the extra dictionaries, they have to exist
-        for extra_dic in config.extra_dictonaries().iter() {
-            trace!("Adding extra dictionary {}", extra_dic.display());
-            if !extra_dic.is_file() {
-                bail!("Extra dictionary {} is not a file", extra_dic.display())
+        let mut affixed = Vec::new();
+        for extra_dic in extra_dictonaries.iter() {
+            let dic = extra_dic.dic();
+            if !dic.is_file() {
+                bail!("Extra dictionary {} is not a file", dic.display())
             }
-            if let Some(extra_dic) = extra_dic.to_str() {
-                if !hunspell.add_dictionary(extra_dic) {
+            if let Some(aff) = extra_dic.aff() {
+                trace!(
+                    "Loading extra dictionary {} with affixes {}",
+                    dic.display(),
+                    aff.display()
+                );
+                if !aff.is_file() {
                     bail!(
-                        "Failed to add extra dictionary path to context {}",
-                        extra_dic
+                        "Affixes {} of extra dictionary {} are not a file",
+                        aff.display(),
+                        dic.display()
                     )
                 }
+                affixed.push(Hunspell::new(path_to_str(aff)?, path_to_str(dic)?));
+            } else {
+                trace!("Adding extra dictionary {}", dic.display());
+                let dic = path_to_str(dic)?;
+                if !hunspell.add_dictionary(dic) {
+                    bail!("Failed to add extra dictionary path to context {}", dic)
+                }
+            }
+        }
+
+        let paths = std::iter::once(dic)
+            .chain(
+                extra_dictonaries
+                    .iter()
+                    .filter_map(|extra_dic| extra_dic.dic().to_str()),
+            )
+            .map(|path| path.to_owned())
+            .collect::<Vec<_>>();
+
+        let mut words = Vec::new();
+        if config.fallback_suggestions() {
+            for path in paths.iter() {
+                words.extend(dictionary_words(path)?);
+            }
+            debug!(
+                "Loaded {} words of {} for fallback suggestions",
+                words.len(),
+                lang
+            );
+        }
+
+        Ok(Self {
+            lang: lang.to_owned(),
+            hunspell,
+            affixed,
+            paths,
+            words,
+        })
+    }
+
+    /// Check if any of the dictionaries accepts `word`.
+    fn check(&self, word: &str) -> bool {
+        self.hunspell.check(word) || self.affixed.iter().any(|hunspell| hunspell.check(word))
+    }
+
+    /// Suggestions of all dictionaries, the ones of the language
+    /// dictionary first, without duplicates.
+    fn suggest(&self, word: &str) -> Vec<String> {
+        let mut suggestions = self.hunspell.suggest(word);
+        for hunspell in self.affixed.iter() {
+            for suggestion in hunspell.suggest(word) {
+                if !suggestions.contains(&suggestion) {
+                    suggestions.push(suggestion);
+                }
+            }
+        }
+        suggestions
+    }
+
+    /// Suggestions of all dictionaries, or the closest dictionary words if
+    /// there are none and the fallback is enabled.
+    fn suggest_or_closest(&self, word: &str) -> Vec<String> {
+        let suggestions = self.suggest(word);
+        if suggestions.is_empty() && !self.words.is_empty() {
+            trace!(
+                "No suggestions for >{}<, falling back to the closest words",
+                word
+            );
+            closest_words(word, &self.words)
+        } else {
+            suggestions
+        }
+    }
+}
+
+/// Everything required to check an origin besides the dictionaries,
+/// shared by all workers.
+struct Settings<'c> {
+    tokenizer: Tokenizer,
+    quirks: Quirks<'c>,
+    min_word_length: usize,
+    accept_acronyms: bool,
+    ranking: Ranking,
+    auto_detect_language: bool,
+    /// The languages to load dictionaries for, the primary one first.
+    langs: Vec<&'c str>,
+}
+
+/// Check all chunks of `origin` against the dictionary of its language.
+fn check_origin<'a>(
+    docu: &'a Documentation,
+    origin: &'a ContentOrigin,
+    chunks: &'a [CheckableChunk],
+    dictionaries: &[Dictionary],
+    settings: &Settings,
+) -> Vec<Suggestion<'a>> {
+    debug!("Processing {}", origin.as_path().display());
+    let Settings {
+        tokenizer,
+        quirks,
+        min_word_length,
+        accept_acronyms,
+        ranking,
+        auto_detect_language,
+        langs,
+    } = settings;
+    let mut acc = Vec::new();
+    let accepted = docu.accepted_words(origin);
+    let plains = chunks
+        .iter()
+        .map(|chunk| (chunk, docu.erase_markdown(chunk)))
+        .collect::<Vec<_>>();
+    let dictionary = match origin {
+        ContentOrigin::CommonMarkFile(_) if *auto_detect_language => {
+            let text = plains
+                .iter()
+                .map(|(_, plain)| plain.as_str())
+                .collect::<Vec<_>>()
+                .join("\n");
+            let detected = detect(&text, langs);
+            if detected.is_none() {
+                debug!(
+                    "Language of {} is not certain, assuming {}",
+                    origin.as_path().display(),
+                    langs[0]
+                );
+            }
+            &dictionaries[detected.unwrap_or(0)]
+        }
+        _ => &dictionaries[0],
+    };
+    debug!(
+        "Checking {} as {}",
+        origin.as_path().display(),
+        dictionary.lang
+    );
+    // the detected language may differ from the primary one
+    let tokenizer = tokenizer.clone().with_language(&dictionary.lang);
+    let consulted = dictionary
+        .paths
+        .iter()
+        .map(|path| path.as_str())
+        .collect::<Vec<_>>();
+    for (chunk, plain) in plains.iter() {
+        let chunk = *chunk;
+        trace!("{:?}", &plain);
+        let txt = plain.as_str();
+        for range in tokenizer.tokenize(txt) {
+            let word = sub_chars(txt, range.clone());
+            if word.chars().count() < *min_word_length {
+                trace!(
+                    "Skipping short word (plain range: {:?}): >{}<",
+                    &range,
+                    &word
+                );
+                continue;
+            }
+            if *accept_acronyms && is_acronym(&word) {
+                trace!("Accepting acronym (plain range: {:?}): >{}<", &range, &word);
+                continue;
+            }
+            if !quirks.check_quirk(&word, |word| {
+                // accepted words in lower case match regardless of case
+                accepted
+                    .map(|accepted| {
+                        accepted.contains(word) || accepted.contains(&word.to_lowercase())
+                    })
+                    .unwrap_or(false)
+                    || dictionary.check(word)
+            }) {
+                trace!("No match for word (plain range: {:?}): >{}<", &range, &word);
+                // get rid of single character suggestions
+                let replacements = dictionary
+                    .suggest_or_closest(&word)
+                    .into_iter()
+                    .filter(|x| x.len() > 1) // single char suggestions tend to be useless
+                    .collect::<Vec<_>>();
+                let replacements = ranking.rank(&word, replacements);
+
+                if log::log_enabled!(target: "explain", log::Level::Debug) {
+                    explain(
+                        &word,
+                        &consulted,
+                        quirks,
+                        accepted.map(|accepted| accepted.len()).unwrap_or(0),
+                        &replacements,
+                    );
+                }
+
+                if let Some((range, span)) = plain.find_span(range.clone()) {
+                    acc.push(Suggestion {
+                        detector: Detector::Hunspell,
+                        range,
+                        span,
+                        origin: origin.clone(),
+                        replacements: replacements.clone(),
+                        chunk,
+                        description: Some("Possible spelling mistake found.".to_owned()),
+                        score: None,
+                        rule_id: None,
+                    })
+                }
             } else {
+                trace!(
+                    "Found a match for word (plain range: {:?}): >{}<",
+                    &range,
+                    word
+                );
+            }
+        }
+    }
+    acc
+}
+
+/// The index of an origin within the documentation, as dispatched to the workers.
+type Job = usize;
+
+/// The suggestions of the origin with the index of the job.
+type JobResult<'a> = Result<(Job, Vec<Suggestion<'a>>)>;
+
+/// Check the origins received from `jobs` until the channel is closed.
+///
+/// `Hunspell` is neither `Send` nor `Sync`, so every worker loads its own
+/// dictionaries once and keeps them for all origins it checks.
+fn worker<'a>(
+    origins: &[(&'a ContentOrigin, &'a Vec<CheckableChunk>)],
+    docu: &'a Documentation,
+    config: &HunspellConfig,
+    settings: &Settings,
+    jobs: &Mutex<Receiver<Job>>,
+    results: Sender<JobResult<'a>>,
+) {
+    let dictionaries = match settings
+        .langs
+        .iter()
+        .map(|lang| Dictionary::load(config, lang))
+        .collect::<Result<Vec<_>>>()
+    {
+        Ok(dictionaries) => dictionaries,
+        Err(e) => {
+            let _ = results.send(Err(e));
+            return;
+        }
+    };
+    loop {
+        // the lock is only held while waiting for the next job
+        let job = match jobs.lock() {
+            Ok(jobs) => jobs.recv(),
+            Err(_) => return,
+        };
+        let job = match job {
+            Ok(job) => job,
+            Err(_) => return,
+        };
+        let (origin, chunks) = origins[job];
+        let suggestions = check_origin(docu, origin, chunks, &dictionaries, settings);
+        if results.send(Ok((job, suggestions))).is_err() {
+            return;
+        }
+    }
+}
+
+impl HunspellChecker {
+    /// Check `docu` with up to `jobs` worker threads.
+    ///
+    /// The origins are the unit of work, each one is checked by a single
+    /// worker. The suggestions are returned in the order of the origins
+    /// within `docu`, regardless of which worker finished first.
+    pub fn check_with_jobs<'a, 's>(
+        docu: &'a Documentation,
+        config: &crate::config::Config,
+        jobs: usize,
+    ) -> Result<SuggestionSet<'s>>
+    where
+        'a: 's,
+    {
+        let tokenizer = config.tokenizer();
+        let config = config
+            .hunspell
+            .as_ref()
+            .ok_or_else(|| anyhow!("Hunspell is not configured"))?;
+
+        let auto_detect_language = config.auto_detect_language();
+        let mut langs = config.langs();
+        if auto_detect_language {
+            if langs.len() < 2 {
                 bail!(
-                    "Failed to convert extra dictionary path to str {}",
-                    extra_dic.display()
+                    "Language auto detection requires at least two languages in `lang`, found {}",
+                    langs.len()
                 )
             }
+        } else {
+            // only the primary language is ever used
+            langs.truncate(1);
         }
+        let settings = Settings {
+            tokenizer,
+            quirks: config.quirks(),
+            min_word_length: config.min_word_length(),
+            accept_acronyms: config.accept_acronyms(),
+            ranking: config.ranking(),
+            auto_detect_language,
+            langs,
+        };
 
-        let suggestions = docu.iter().try_fold::<SuggestionSet, _, Result<_>>(
-            SuggestionSet::new(),
-            |mut acc, (origin, chunks)| {
-                debug!("Processing {}", origin.as_path().display());
-                for chunk in chunks {
-                    let plain = chunk.erase_markdown();
-                    trace!("{:?}", &plain);
-                    let txt = plain.as_str();
-                    for range in tokenize(txt) {
-                        let word = sub_chars(txt, range.clone());
-                        if !hunspell.check(&word) {
-                            trace!("No match for word (plain range: {:?}): >{}<", &range, &word);
-                            // get rid of single character suggestions
-                            let replacements = hunspell
-                                .suggest(&word)
-                                .into_iter()
-                                .filter(|x| x.len() > 1) // single char suggestions tend to be useless
-                                .collect::<Vec<_>>();
-
-                            for (range, span) in plain.find_spans(range.clone()) {
-                                acc.add(
-                                    origin.clone(),
-                                    Suggestion {
-                                        detector: Detector::Hunspell,
-                                        range,
-                                        span,
-                                        origin: origin.clone(),
-                                        replacements: replacements.clone(),
-                                        chunk,
-                                        description: Some(
-                                            "Possible spelling mistake found.".to_owned(),
-                                        ),
-                                    },
-                                )
-                            }
-                        } else {
-                            trace!(
-                                "Found a match for word (plain range: {:?}): >{}<",
-                                &range,
-                                word
-                            );
-                        }
-                    }
+        let origins = docu.iter().collect::<Vec<_>>();
+        // no point in loading dictionaries for idle workers
+        let workers = jobs.max(1).min(origins.len().max(1));
+        debug!(
+            "Checking {} origins with {} workers",
+            origins.len(),
+            workers
+        );
+
+        let mut per_origin: Vec<Option<Vec<Suggestion<'s>>>> = vec![None; origins.len()];
+        if workers == 1 {
+            let dictionaries = settings
+                .langs
+                .iter()
+                .map(|lang| Dictionary::load(config, lang))
+                .collect::<Result<Vec<_>>>()?;
+            for (idx, (origin, chunks)) in origins.iter().enumerate() {
+                per_origin[idx] =
+                    Some(check_origin(docu, origin, chunks, &dictionaries, &settings));
+            }
+        } else {
+            let (job_tx, job_rx) = mpsc::channel::<Job>();
+            let (result_tx, result_rx) = mpsc::channel::<JobResult<'s>>();
+            let job_rx = Mutex::new(job_rx);
+            for job in 0..origins.len() {
+                job_tx
+                    .send(job)
+                    .expect("The receiver is owned by this function. qed");
+            }
+            // workers quit once all jobs are taken
+            drop(job_tx);
+            std::thread::scope(|scope| -> Result<()> {
+                for _ in 0..workers {
+                    let result_tx = result_tx.clone();
+                    let (origins, settings, job_rx) = (&origins, &settings, &job_rx);
+                    scope.spawn(move || worker(origins, docu, config, settings, job_rx, result_tx));
+                }
+                // the collector finishes once all workers dropped their sender
+                drop(result_tx);
+                for result in result_rx {
+                    let (job, suggestions) = result?;
+                    per_origin[job] = Some(suggestions);
                 }
-                Ok(acc)
-            },
-        )?;
+                Ok(())
+            })?;
+        }
+
+        let mut acc = SuggestionSet::new();
+        for ((origin, _chunks), suggestions) in origins.into_iter().zip(per_origin) {
+            let suggestions = suggestions
+                .expect("Every origin is checked, unless a worker failed, which was returned. qed");
+            if !suggestions.is_empty() {
+                acc.extend(origin.clone(), suggestions);
+            }
+        }
+        Ok(acc)
+    }
+}
+
+impl Checker for HunspellChecker {
+    /// Requires the tokenization settings besides the `HunspellConfig`.
+    type Config = crate::config::Config;
+    fn check<'a, 's>(docu: &'a Documentation, config: &Self::Config) -> Result<SuggestionSet<'s>>
+    where
+        'a: 's,
+    {
+        Self::check_with_jobs(docu, config, 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_dictionary_lists_attempts() {
+        let message = missing_dictionary(
+            "en_US",
+            &[
+                (PathBuf::from("/nope"), "not a directory"),
+                (PathBuf::from("/usr/share/hunspell"), "no .aff file"),
+            ],
+        );
+        assert!(message.starts_with("Failed to find en_US.dic and en_US.aff, searched:"));
+        assert!(message.contains("\n  /nope (not a directory)"));
+        assert!(message.contains("\n  /usr/share/hunspell (no .aff file)"));
+        assert!(message.contains("cargo spellcheck config --user"));
+        assert!(missing_dictionary("de_DE", &[]).contains("no search dirs are configured"));
+    }
+
+    /// A temporary directory named after `name` holding the `xx_XX`
+    /// dictionary with the content `dic`, which is the only one configured,
+    /// also as user dictionary.
+    fn xx_dictionary(name: &str, dic: &str) -> (PathBuf, crate::config::Config) {
+        let dir =
+            std::env::temp_dir().join(format!("cargo-spellcheck-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("xx_XX.aff"), "SET UTF-8\n").unwrap();
+        std::fs::write(dir.join("xx_XX.dic"), dic).unwrap();
+
+        let mut config = crate::config::Config::default();
+        {
+            let hunspell = config.hunspell.as_mut().unwrap();
+            hunspell.lang = Some(crate::config::Langs::Single("xx_XX".to_owned()));
+            hunspell.search_dirs = Some(vec![dir.clone()]);
+            hunspell.skip_default_search_dirs = Some(true);
+            hunspell.extra_dictonaries = None;
+            hunspell.user_dictionary = Some(dir.join("xx_XX.dic"));
+        }
+        (dir, config)
+    }
+
+    #[test]
+    fn workers() {
+        let (dir, mut config) = xx_dictionary("workers", "3\nA\nfine\nword\n");
+        std::fs::write(dir.join("user.dic"), "1\nA\n").unwrap();
+        config.hunspell.as_mut().unwrap().user_dictionary = Some(dir.join("user.dic"));
+        let mut docs = Documentation::new();
+        for idx in 0..5 {
+            docs.join(Documentation::from((
+                ContentOrigin::RustSourceFile(PathBuf::from(format!("/tmp/worker{}.rs", idx))),
+                "/// A fine wrod\n/// A fine word\nstruct A;\n",
+            )));
+        }
+        let summary = |suggestions: SuggestionSet| {
+            suggestions
+                .iter()
+                .flat_map(|(origin, suggestions)| {
+                    suggestions.iter().map(move |suggestion| {
+                        (origin.clone(), suggestion.span.start, suggestion.span.end)
+                    })
+                })
+                .collect::<Vec<_>>()
+        };
+        let sequential = HunspellChecker::check_with_jobs(&docs, &config, 1);
+        let parallel = HunspellChecker::check_with_jobs(&docs, &config, 3);
+        let _ = std::fs::remove_dir_all(&dir);
+        let sequential = summary(sequential.unwrap());
+        assert_eq!(sequential.len(), 5);
+        assert_eq!(sequential, summary(parallel.unwrap()));
+    }
+
+    #[test]
+    fn ignored_words() {
+        let (dir, config) = xx_dictionary("ignored", "3\nA\nfine\nword\n");
+        let mut docs = Documentation::new();
+        docs.join(Documentation::from((
+            ContentOrigin::RustSourceFile(PathBuf::from("/tmp/ignored.rs")),
+            "/// A fine Wrod\nstruct A;\n",
+        )));
+        docs.join(Documentation::from((
+            ContentOrigin::RustSourceFile(PathBuf::from("/tmp/other.rs")),
+            "/// A fine wrod, tokio\nstruct B;\n",
+        )));
+        let before = HunspellChecker::check_with_jobs(&docs, &config, 1)
+            .map(|suggestions| suggestions.total_count());
+        docs.accept_words_everywhere(&["wrod".to_owned()]);
+        let after = HunspellChecker::check_with_jobs(&docs, &config, 1);
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(before.unwrap(), 3);
+        let after = after.unwrap();
+        assert_eq!(after.total_count(), 1);
+        assert_eq!(
+            after.unknown_words().into_iter().collect::<Vec<_>>(),
+            vec!["tokio"]
+        );
+    }
+
+    #[test]
+    fn affixed_extra_dictionary() {
+        let (dir, config) = xx_dictionary("affixed", "1\nword\n");
+        std::fs::write(
+            dir.join("compounds.aff"),
+            "SET UTF-8\nSFX S Y 1\nSFX S 0 s .\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("compounds.dic"), "1\nwidget/S\n").unwrap();
+        std::fs::write(dir.join("plain.dic"), "1\nplain\n").unwrap();
+
+        let mut config = config.hunspell.unwrap();
+        config.user_dictionary = Some(dir.join("plain.dic"));
+        config.extra_dictonaries = Some(vec![ExtraDictionary::WithAffixes {
+            dic: dir.join("compounds.dic"),
+            aff: dir.join("compounds.aff"),
+        }]);
+        let dictionary = Dictionary::load(&config, "xx_XX");
+
+        config.extra_dictonaries = Some(vec![ExtraDictionary::WithAffixes {
+            dic: dir.join("compounds.dic"),
+            aff: dir.join("missing.aff"),
+        }]);
+        let missing = Dictionary::load(&config, "xx_XX");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let dictionary = dictionary.unwrap();
+        assert_eq!(dictionary.affixed.len(), 1);
+        assert_eq!(dictionary.paths.len(), 3);
+        assert!(dictionary.check("word"));
+        assert!(dictionary.check("plain"));
+        assert!(dictionary.check("widget"));
+        assert!(!dictionary.hunspell.check("widget"));
+        assert!(!dictionary.check("wdiget"));
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn fallback_suggestions() {
+        let (dir, config) = xx_dictionary(
+            "fallback",
+            "5\nword/S\nworld\tpo:noun\nwork\nand\\/or\nsword\n",
+        );
+        let mut config = config.hunspell.unwrap();
+        let disabled = Dictionary::load(&config, "xx_XX");
+        config.fallback_suggestions = Some(true);
+        let enabled = Dictionary::load(&config, "xx_XX");
+        let words = dictionary_words(dir.join("xx_XX.dic").to_str().unwrap());
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(
+            words.unwrap(),
+            vec!["word", "world", "work", "and/or", "sword"]
+        );
+        assert_eq!(
+            closest_words("Wrd", &enabled.as_ref().unwrap().words),
+            vec!["word", "world", "work", "sword"]
+        );
+        assert_eq!(
+            closest_words("xyzzy", &enabled.as_ref().unwrap().words),
+            Vec::<String>::new()
+        );
+
+        // hunspell has no suggestions, since no anagram is listed
+        assert!(disabled.unwrap().suggest_or_closest("wrk").is_empty());
+        assert_eq!(
+            enabled.unwrap().suggest_or_closest("wrk"),
+            vec!["work", "word"]
+        );
+    }
 
-        // TODO sort spans by file and line + column
-        Ok(suggestions)
+    #[test]
+    fn acronyms() {
+        assert!(is_acronym("HTTP"));
+        assert!(is_acronym("ÜML"));
+        assert!(!is_acronym("A"));
+        assert!(!is_acronym("Http"));
+        assert!(!is_acronym("UTF8"));
+        assert!(!is_acronym("API's"));
     }
 }
@@ -0,0 +1,165 @@
+//! Language detection
+//!
+//! A lightweight heuristic, which counts the most common words of each
+//! candidate language, to pick the dictionary for a file among the
+//! configured languages. Only the language part of a code such as
+//! `de_DE` is considered, regional variants are indistinguishable.
+
+use log::trace;
+
+/// The most common words per language, all lowercase.
+const STOPWORDS: &[(&str, &[&str])] = &[
+    (
+        "en",
+        &[
+            "the", "and", "of", "to", "is", "in", "that", "it", "for", "with", "as", "are", "this",
+            "be", "on", "by", "not", "or", "an", "which", "from", "can", "if", "all", "will",
+        ],
+    ),
+    (
+        "de",
+        &[
+            "der", "die", "das", "und", "ist", "nicht", "ein", "eine", "zu", "mit", "den", "dem",
+            "von", "sich", "auf", "für", "im", "auch", "es", "wird", "werden", "oder", "wenn",
+            "kann", "sind",
+        ],
+    ),
+    (
+        "fr",
+        &[
+            "le", "la", "les", "de", "et", "est", "des", "une", "un", "du", "pour", "dans", "que",
+            "qui", "pas", "sur", "avec", "au", "ce", "sont", "par", "il", "plus", "ou", "être",
+            "aux",
+        ],
+    ),
+    (
+        "es",
+        &[
+            "el", "la", "los", "las", "y", "es", "del", "que", "en", "un", "una", "por", "con",
+            "para", "se", "no", "su", "al", "lo", "como", "más", "pero", "sus", "está", "son",
+        ],
+    ),
+    (
+        "it",
+        &[
+            "il", "la", "di", "che", "è", "e", "un", "una", "per", "non", "sono", "gli", "del",
+            "della", "con", "si", "lo", "le", "da", "questo", "nel", "alla", "come", "anche",
+            "più",
+        ],
+    ),
+    (
+        "nl",
+        &[
+            "de", "het", "een", "en", "van", "is", "dat", "niet", "op", "te", "met", "voor",
+            "zijn", "er", "ook", "aan", "wordt", "als", "bij", "om", "dit", "maar", "naar", "kan",
+            "worden",
+        ],
+    ),
+    (
+        "pt",
+        &[
+            "o", "a", "os", "as", "de", "do", "da", "que", "não", "um", "uma", "para", "com", "é",
+            "em", "por", "se", "dos", "das", "mais", "como", "ao", "mas", "são", "está",
+        ],
+    ),
+];
+
+/// Number of common words the best candidate must contain at least.
+const MIN_HITS: usize = 3;
+
+/// The language part of a code such as `en_US` or `de-DE`, lowercased.
+fn language_of(lang: &str) -> String {
+    lang.split(|c| c == '_' || c == '-')
+        .next()
+        .unwrap_or(lang)
+        .to_lowercase()
+}
+
+/// Index of the language of `candidates` `text` is most likely written in.
+///
+/// Returns `None` if the detection is not confident, which is the case if
+/// there are too few common words, or a second language scores close to
+/// the best one.
+pub(crate) fn detect(text: &str, candidates: &[&str]) -> Option<usize> {
+    let words = text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>();
+    let mut scores = candidates
+        .iter()
+        .enumerate()
+        .map(|(idx, candidate)| {
+            let language = language_of(candidate);
+            let stopwords = STOPWORDS
+                .iter()
+                .find(|(stopword_language, _)| *stopword_language == language)
+                .map(|(_, stopwords)| *stopwords)
+                .unwrap_or(&[]);
+            let hits = words
+                .iter()
+                .filter(|word| stopwords.contains(&word.as_str()))
+                .count();
+            (idx, hits)
+        })
+        .collect::<Vec<_>>();
+    scores.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    trace!(
+        "Language detection scores {:?} of {:?}",
+        &scores,
+        candidates
+    );
+    let (best, best_hits) = *scores.first()?;
+    let runner_up_hits = scores.get(1).map(|(_, hits)| *hits).unwrap_or(0);
+    // the best must beat the runner-up by half of its score
+    if best_hits < MIN_HITS || best_hits * 2 < runner_up_hits * 3 {
+        None
+    } else {
+        Some(best)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CANDIDATES: &[&str] = &["en_US", "de_DE", "fr_FR"];
+
+    #[test]
+    fn detection() {
+        assert_eq!(
+            detect(
+                "This is the manual of the tool, which explains all of the options.",
+                CANDIDATES
+            ),
+            Some(0)
+        );
+        assert_eq!(
+            detect(
+                "Das ist die Anleitung, sie erklärt auch die Optionen, die es gibt.",
+                CANDIDATES
+            ),
+            Some(1)
+        );
+        assert_eq!(
+            detect(
+                "Le manuel de l'outil, qui explique les options et les commandes.",
+                CANDIDATES
+            ),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn not_confident() {
+        // too few common words
+        assert_eq!(detect("Installation", CANDIDATES), None);
+        // regional variants are indistinguishable
+        assert_eq!(
+            detect("This is the manual of the tool.", &["en_US", "en_GB"]),
+            None
+        );
+        // no common words known for the language
+        assert_eq!(detect("the and of to is", &["xx_XX"]), None);
+    }
+}
@@ -7,38 +7,142 @@
 use super::*;
 
 use crate::Range;
+use anyhow::{anyhow, bail};
 use languagetool_rs::{LanguageTool, Request};
-use log::trace;
+use log::{trace, warn};
+use std::fmt;
 
 pub struct LanguageToolChecker;
 
+/// None of the configured endpoints responded, which must not be
+/// mistaken for a check without any mistakes.
+#[derive(Debug)]
+pub struct Unreachable {
+    urls: Vec<url::Url>,
+    /// Failure of the last endpoint tried.
+    reason: String,
+}
+
+impl fmt::Display for Unreachable {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "LanguageTool server is unreachable at {}: {}",
+            self.urls
+                .iter()
+                .map(url::Url::as_str)
+                .collect::<Vec<_>>()
+                .join(", "),
+            self.reason
+        )
+    }
+}
+
+impl std::error::Error for Unreachable {}
+
+/// Whether `e` is caused by a connection which could not be established
+/// or timed out, as opposed to a request which failed for another reason.
+///
+/// The underlying `reqwest` reports connection failures as `hyper` errors,
+/// which carry no status.
+fn is_unreachable(e: &anyhow::Error) -> bool {
+    e.chain()
+        .filter_map(|cause| cause.downcast_ref::<reqwest::Error>())
+        .any(|e| e.is_timeout() || (e.is_http() && e.status().is_none()))
+}
+
+/// Clients for all configured endpoints, which are tried in order.
+///
+/// Sticks with the last endpoint which responded, so an unreachable
+/// one is only tried again once all others failed.
+struct Endpoints {
+    clients: Vec<(url::Url, LanguageTool)>,
+    current: usize,
+}
+
+impl Endpoints {
+    fn new(urls: &[url::Url]) -> Result<Self> {
+        if urls.is_empty() {
+            bail!("No LanguageTool endpoint configured")
+        }
+        let clients = urls
+            .iter()
+            .map(|url| {
+                LanguageTool::new(url.as_str())
+                    .map(|lt| (url.clone(), lt))
+                    .map_err(|e| anyhow!("Invalid LanguageTool endpoint {}", url).context(e))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            clients,
+            current: 0,
+        })
+    }
+
+    /// Run `request` against the endpoints, starting with the current one,
+    /// until one of them succeeds, failing with [`Unreachable`] if none of
+    /// them could be reached. Any other failure is returned as it is.
+    fn request<T>(&mut self, mut request: impl FnMut(&LanguageTool) -> Result<T>) -> Result<T> {
+        let n = self.clients.len();
+        let mut reason = String::new();
+        for attempt in 0..n {
+            let idx = (self.current + attempt) % n;
+            let (url, lt) = &self.clients[idx];
+            match request(lt) {
+                Ok(t) => {
+                    log::debug!("LanguageTool endpoint {} served the request", url);
+                    self.current = idx;
+                    return Ok(t);
+                }
+                Err(e) if is_unreachable(&e) => {
+                    warn!("LanguageTool endpoint {} is unreachable: {}", url, e);
+                    reason = e.to_string();
+                }
+                Err(e) => {
+                    return Err(anyhow!("LanguageTool endpoint {} failed", url).context(e));
+                }
+            }
+        }
+        Err(Unreachable {
+            urls: self.clients.iter().map(|(url, _)| url.clone()).collect(),
+            reason,
+        }
+        .into())
+    }
+}
+
 impl Checker for LanguageToolChecker {
     type Config = crate::config::LanguageToolConfig;
     fn check<'a, 's>(docu: &'a Documentation, config: &Self::Config) -> Result<SuggestionSet<'s>>
     where
         'a: 's,
     {
-        let lt = LanguageTool::new(config.url.as_str())?;
+        let mut endpoints = Endpoints::new(config.urls())?;
         let suggestions = docu.iter().try_fold::<SuggestionSet, _, Result<_>>(
             SuggestionSet::new(),
             |mut acc, (origin, chunks)| {
                 for chunk in chunks {
-                    let plain = chunk.erase_markdown();
+                    let plain = docu.erase_markdown(chunk);
                     log::trace!("markdown erasure: {:?}", &plain);
-                    let req = Request::new(plain.to_string(), "en-US".to_owned());
-                    let resp = lt.check(req)?;
+                    let resp = endpoints.request(|lt| {
+                        let req = Request::new(plain.to_string(), "en-US".to_owned());
+                        Ok(lt.check(req)?)
+                    })?;
                     if let Some(software) = resp.software {
                         log::trace!("sw: {:?}", software);
                     }
                     if let Some(matches) = resp.matches {
                         for item in matches {
-                            if let Some(rule) = item.rule {
+                            let rule_id = if let Some(ref rule) = item.rule {
                                 if rule.id == "EN_QUOTES" {
                                     // really annoying and pointless in code related documentation
                                     continue;
                                 }
                                 trace!("item.rule: {:?}", rule);
-                            }
+                                Some(rule.id.clone())
+                            } else {
+                                None
+                            };
                             trace!("item.context: {:?}", item.context);
                             trace!("item.message: {:?}", item.message);
                             trace!("item.short_message: {:?}", item.short_message);
@@ -61,6 +165,8 @@ impl Checker for LanguageToolChecker {
                                             .collect(),
                                         chunk: chunk,
                                         description: Some(item.message.clone()),
+                                        score: None,
+                                        rule_id: rule_id.clone(),
                                     },
                                 );
                             }
@@ -74,3 +180,41 @@ impl Checker for LanguageToolChecker {
         Ok(suggestions)
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// An endpoint on a port nothing listens on.
+    pub(crate) fn unused_endpoint() -> url::Url {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+        url::Url::parse(&format!("http://127.0.0.1:{}", port)).unwrap()
+    }
+
+    #[test]
+    fn unreachable() {
+        let url = unused_endpoint();
+        let mut endpoints = Endpoints::new(&[url.clone()]).unwrap();
+        let e = endpoints
+            .request(|lt| {
+                let req = Request::new("Hello world".to_owned(), "en-US".to_owned());
+                Ok(lt.check(req)?)
+            })
+            .unwrap_err();
+        let unreachable = e
+            .downcast_ref::<Unreachable>()
+            .expect("An unused port must be reported as unreachable");
+        assert_eq!(unreachable.urls, vec![url]);
+    }
+
+    #[test]
+    fn failing_request() {
+        let mut endpoints = Endpoints::new(&[unused_endpoint()]).unwrap();
+        let e = endpoints
+            .request::<()>(|_lt| Err(anyhow!("Malformed response")))
+            .unwrap_err();
+        assert!(!e.is::<Unreachable>());
+    }
+}
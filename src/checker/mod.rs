@@ -4,17 +4,29 @@
 //! Contains also helpers to avoid re-implementing generic
 //! algorithms again and again, i.e. tokenization.
 
-use crate::{Config, Detector, Documentation, Suggestion, SuggestionSet};
+use crate::{CheckableChunk, Config, Detector, Documentation, Suggestion, SuggestionSet, Timings};
 
 use anyhow::Result;
 
 use crate::Range;
-use log::debug;
+use log::{debug, trace};
+use std::collections::HashSet;
+use unicode_segmentation::UnicodeSegmentation;
 
+mod quirks;
+pub use quirks::*;
+mod ranking;
+pub use ranking::*;
+
+mod external;
 #[cfg(feature = "hunspell")]
 mod hunspell;
+#[cfg(feature = "hunspell")]
+mod language;
 #[cfg(feature = "languagetool")]
 mod languagetool;
+mod reflow;
+mod repeated;
 
 /// Implementation for a checker
 pub(crate) trait Checker {
@@ -24,59 +36,328 @@ pub(crate) trait Checker {
         'a: 's;
 }
 
-/// Returns absolute offsets and the data with the token in question.
-///
-/// Does not handle hyphenation yet or partial words at boundaries.
-/// Returns the a vector of ranges for the input str.
-fn tokenize(s: &str) -> Vec<Range> {
-    let mut started = false;
-    let mut linear_start = 0usize;
-    let mut linear_end;
-    let mut bananasplit = Vec::with_capacity(32);
-    let _fin_char_idx = 0usize;
-
-    let blacklist = "\";:,.?!#(){}[]-\n\r/`".to_owned();
-    let is_ignore_char = |c: char| c.is_whitespace() || blacklist.contains(c);
-
-    for (c_idx, c) in s.char_indices() {
-        if is_ignore_char(c) {
-            linear_end = c_idx;
-            if started {
-                bananasplit.push(linear_start..linear_end);
-            }
-            started = false;
-        // @todo handle hyphenation
-        // if c == '\n' {
-        //     column = 0;
-        //     line += 1;
-        // }
+/// Language specific rules of the [`Tokenizer`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenizationPolicy {
+    /// Elided words, without their apostrophe, which are split off the
+    /// following word, i.e. `l` of the French `l'eau`.
+    pub elisions: &'static [&'static str],
+    /// Hyphens between two letters join the parts of a compound, so the
+    /// German `E-Mail-Adresse` is checked as a whole.
+    pub hyphenated_compounds: bool,
+}
+
+impl TokenizationPolicy {
+    /// Policies by language code, languages not listed use the default
+    /// policy, which suits English.
+    const LANGUAGES: &'static [(&'static str, TokenizationPolicy)] = &[
+        (
+            "fr",
+            TokenizationPolicy {
+                elisions: &[
+                    "c", "d", "j", "l", "m", "n", "s", "t", "qu", "jusqu", "lorsqu", "puisqu",
+                    "quoiqu",
+                ],
+                hyphenated_compounds: false,
+            },
+        ),
+        (
+            "it",
+            TokenizationPolicy {
+                elisions: &[
+                    "l", "un", "d", "c", "v", "dell", "nell", "all", "dall", "sull", "coll",
+                    "quest", "quell",
+                ],
+                hyphenated_compounds: false,
+            },
+        ),
+        (
+            "ca",
+            TokenizationPolicy {
+                elisions: &["l", "d", "m", "t", "s", "n"],
+                hyphenated_compounds: false,
+            },
+        ),
+        (
+            "de",
+            TokenizationPolicy {
+                elisions: &[],
+                hyphenated_compounds: true,
+            },
+        ),
+    ];
+
+    /// The policy of the hunspell language `lang`, i.e. `fr_FR`, which is
+    /// looked up by its language code.
+    pub fn of(lang: &str) -> Self {
+        let code = lang.split(|c| c == '_' || c == '-').next().unwrap_or(lang);
+        Self::LANGUAGES
+            .iter()
+            .find(|(language, _policy)| language.eq_ignore_ascii_case(code))
+            .map(|(_language, policy)| *policy)
+            .unwrap_or_default()
+    }
+
+    /// Number of characters of the elided word `word` starts with, if it
+    /// is followed by an apostrophe and further characters.
+    fn elision(&self, word: &[char], apostrophes: &[char]) -> Option<usize> {
+        let idx = word.iter().position(|c| apostrophes.contains(c))?;
+        if idx == 0 || idx + 1 >= word.len() {
+            return None;
+        }
+        let elided = word[..idx].iter().collect::<String>().to_lowercase();
+        if self.elisions.contains(&elided.as_str()) {
+            Some(idx)
         } else {
-            if !started {
-                linear_start = c_idx;
-                started = true;
+            None
+        }
+    }
+}
+
+/// Splits text into tokens, where abbreviations such as `e.g.` are
+/// accepted as they are and never yielded as tokens.
+///
+/// Apostrophes join words, so contractions such as `don't` are checked
+/// as a whole, see [`Tokenizer::with_apostrophes`]. Elisions and compounds
+/// are split according to the [`TokenizationPolicy`] of the language, see
+/// [`Tokenizer::with_language`].
+#[derive(Debug, Clone)]
+pub struct Tokenizer {
+    /// Longest first, so the longest matching abbreviation wins.
+    abbreviations: Vec<String>,
+    /// Characters which are part of a word if adjacent to it.
+    apostrophes: Vec<char>,
+    policy: TokenizationPolicy,
+}
+
+impl Default for Tokenizer {
+    fn default() -> Self {
+        Self::new(&[])
+    }
+}
+
+impl Tokenizer {
+    /// Abbreviations which are always recognized.
+    pub const ABBREVIATIONS: &'static [&'static str] = &["e.g.", "i.e.", "etc.", "vs.", "cf."];
+
+    /// Apostrophes which join words, unless configured otherwise.
+    pub const APOSTROPHES: &'static [char] = &['\'', '’'];
+
+    /// Characters commonly used as apostrophe, those which are not
+    /// configured as apostrophes separate words.
+    const APOSTROPHE_LIKE: &'static [char] = &['\'', '’', '‘', 'ʼ', '´'];
+
+    /// Recognize `extra_abbreviations` in addition to the built-in ones.
+    pub fn new(extra_abbreviations: &[String]) -> Self {
+        let mut abbreviations = Self::ABBREVIATIONS
+            .iter()
+            .map(|abbreviation| (*abbreviation).to_owned())
+            .chain(extra_abbreviations.iter().cloned())
+            .filter(|abbreviation| !abbreviation.is_empty())
+            .collect::<Vec<_>>();
+        // longest first, duplicates adjacent to be removed
+        abbreviations.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        abbreviations.dedup();
+        Self {
+            abbreviations,
+            apostrophes: Self::APOSTROPHES.to_vec(),
+            policy: TokenizationPolicy::default(),
+        }
+    }
+
+    /// Apply the [`TokenizationPolicy`] of the hunspell language `lang`.
+    pub fn with_language(mut self, lang: &str) -> Self {
+        self.policy = TokenizationPolicy::of(lang);
+        self
+    }
+
+    /// Replace the characters treated as apostrophes.
+    ///
+    /// An apostrophe within a word, as in `don't` or `rock'n'roll`, is part
+    /// of the word. A leading or trailing one, as in `'tis` or `'quoted'`,
+    /// is retained as well, so quirks can deal with it. An apostrophe which
+    /// is not adjacent to a word separates words, just like any other
+    /// apostrophe-like character which is not listed.
+    pub fn with_apostrophes(mut self, apostrophes: &[char]) -> Self {
+        self.apostrophes = apostrophes.to_vec();
+        self
+    }
+
+    /// Check if the apostrophe-like `c` at byte offset `idx` of `s` separates words.
+    fn is_separating_apostrophe(&self, s: &str, idx: usize, c: char) -> bool {
+        if !self.apostrophes.contains(&c) {
+            return true;
+        }
+        let adjacent = |c: Option<char>| c.map(|c| c.is_alphanumeric()).unwrap_or(false);
+        !adjacent(s[..idx].chars().next_back()) && !adjacent(s[idx + c.len_utf8()..].chars().next())
+    }
+
+    /// Check if the hyphen `c` at byte offset `idx` of `s` joins the parts
+    /// of a compound, which requires a letter on both sides.
+    fn is_joining_hyphen(&self, s: &str, idx: usize, c: char) -> bool {
+        let letter = |c: Option<char>| c.map(|c| c.is_alphabetic()).unwrap_or(false);
+        self.policy.hyphenated_compounds
+            && c == '-'
+            && letter(s[..idx].chars().next_back())
+            && letter(s[idx + c.len_utf8()..].chars().next())
+    }
+
+    /// Split elided words off the following word, according to the policy.
+    fn split_elisions(&self, s: &str, tokens: Vec<Range>) -> Vec<Range> {
+        if self.policy.elisions.is_empty() {
+            return tokens;
+        }
+        let chars = s.chars().collect::<Vec<_>>();
+        let mut acc = Vec::with_capacity(tokens.len());
+        for mut token in tokens {
+            while let Some(len) = self
+                .policy
+                .elision(&chars[token.clone()], self.apostrophes.as_slice())
+            {
+                acc.push(token.start..token.start + len);
+                // the apostrophe belongs to neither word
+                token.start += len + 1;
             }
+            acc.push(token);
         }
+        acc
     }
-    // at the end of string, assume word complete
-    // @todo for hypenation, check if line ends with a dash
-    if started {
-        if let Some((idx, _)) = s.char_indices().next_back() {
-            // increase by one, since the range's end goes one beyond, end bounds is _exclusive_ for ranges
-            let linear_end = idx + 1;
-            bananasplit.push(linear_start..linear_end)
-        } else {
-            log::warn!("Most liekly lost a word when tokenizing! BUG");
+
+    /// Byte length of the abbreviation `s` starts with, ignoring ascii case.
+    ///
+    /// The abbreviation must not be followed by further alphanumeric characters.
+    fn abbreviation_at(&self, s: &str) -> Option<usize> {
+        self.abbreviations.iter().find_map(|abbreviation| {
+            let candidate = s.get(..abbreviation.len())?;
+            if !candidate.eq_ignore_ascii_case(abbreviation) {
+                return None;
+            }
+            match s[abbreviation.len()..].chars().next() {
+                Some(c) if c.is_alphanumeric() => None,
+                _ => Some(abbreviation.len()),
+            }
+        })
+    }
+
+    /// Returns the character ranges of all tokens within `s`.
+    ///
+    /// Words are found along Unicode word boundaries, so letters composed
+    /// of multiple code points stay within their word, and punctuation or
+    /// symbols of any script separate words, including hyphens, slashes and
+    /// backticks. Digits, underscores and apostrophes within a word are
+    /// part of it.
+    ///
+    /// Does not handle hyphenation yet or partial words at boundaries.
+    pub fn tokenize(&self, s: &str) -> Vec<Range> {
+        let mut started = false;
+        let mut linear_start = 0usize;
+        let mut bananasplit = Vec::with_capacity(32);
+        // abbreviations are skipped entirely, by byte offset
+        let mut skip_until = 0usize;
+        // ranges are in characters, just like those of a chunk
+        let mut char_idx = 0usize;
+
+        let blacklist = "\";:,.?!#(){}[]-\n\r/`".to_owned();
+        let is_ignore_char = |c_idx: usize, c: char, within_word: bool| {
+            if Self::APOSTROPHE_LIKE.contains(&c) {
+                self.is_separating_apostrophe(s, c_idx, c)
+            } else if self.is_joining_hyphen(s, c_idx, c) {
+                false
+            } else {
+                !within_word || c.is_whitespace() || blacklist.contains(c)
+            }
+        };
+
+        for (segment_idx, segment) in s.split_word_bound_indices() {
+            // segments without any letter or digit are punctuation, symbols or whitespace
+            let within_word = segment.chars().any(char::is_alphanumeric);
+            for (c_idx, c) in segment.char_indices() {
+                let c_idx = segment_idx + c_idx;
+                let idx = char_idx;
+                char_idx += 1;
+                if c_idx < skip_until {
+                    continue;
+                }
+                let ignore = is_ignore_char(c_idx, c, within_word);
+                if !started && !ignore {
+                    if let Some(len) = self.abbreviation_at(&s[c_idx..]) {
+                        trace!("Skipping abbreviation >{}<", &s[c_idx..(c_idx + len)]);
+                        skip_until = c_idx + len;
+                        continue;
+                    }
+                }
+                if ignore {
+                    if started {
+                        bananasplit.push(linear_start..idx);
+                    }
+                    started = false;
+                } else if !started {
+                    linear_start = idx;
+                    started = true;
+                }
+            }
         }
+        // at the end of string, assume word complete
+        // @todo for hypenation, check if line ends with a dash
+        if started {
+            bananasplit.push(linear_start..char_idx)
+        }
+        self.split_elisions(s, bananasplit)
     }
-    bananasplit
+}
+
+/// The name of the phase of `detector` within the timings.
+fn phase(detector: Detector) -> String {
+    format!("checker {}", detector)
 }
 
 /// Check a full document for violations using the tools we have.
-pub fn check<'a, 's>(documentation: &'a Documentation, config: &Config) -> Result<SuggestionSet<'s>>
+///
+/// Hunspell checks run on up to `jobs` threads, all others on the current one.
+/// The duration of each checker is recorded in `timings`.
+pub fn check<'a, 's>(
+    documentation: &'a Documentation,
+    config: &Config,
+    jobs: usize,
+    timings: &mut Timings,
+) -> Result<SuggestionSet<'s>>
 where
     'a: 's,
 {
     let mut collective = SuggestionSet::<'s>::new();
+    #[cfg(not(feature = "hunspell"))]
+    let _ = jobs;
+
+    // chunks without any prose are not worth reporting on and markers which
+    // likely mislead the markdown erasure are warned about, so each chunk
+    // which might be affected is erased once up front
+    let mut code_only = HashSet::<*const CheckableChunk>::new();
+    for (origin, chunks) in documentation.iter() {
+        for chunk in chunks {
+            let code = documentation.may_be_code_only(chunk);
+            if !code && !chunk.as_str().contains(|c| c == '*' || c == '`') {
+                continue;
+            }
+            let plain = documentation.erase_markdown(chunk);
+            if code && plain.as_str().trim().is_empty() {
+                code_only.insert(chunk);
+            }
+            if let Some((marker, span)) = plain.unbalanced() {
+                log::warn!(
+                    "Unbalanced '{}' in {}:{}:{}, {}",
+                    marker,
+                    origin.as_path().display(),
+                    span.start.line,
+                    span.start.column + 1,
+                    if documentation.raw_fallback() {
+                        "checking the raw text instead"
+                    } else {
+                        "the markdown is likely not reduced to prose as intended"
+                    }
+                );
+            }
+        }
+    }
 
     #[cfg(feature = "languagetool")]
     {
@@ -86,10 +367,17 @@ where
                 .languagetool
                 .as_ref()
                 .expect("Must be Some(LanguageToolConfig) if is_enabled returns true");
-            if let Ok(mut suggestions) =
+            match timings.measure(phase(Detector::LanguageTool), || {
                 self::languagetool::LanguageToolChecker::check(documentation, config)
-            {
-                collective.join(suggestions);
+            }) {
+                Ok(suggestions) => collective.join(suggestions),
+                Err(e) if e.is::<self::languagetool::Unreachable>() => {
+                    if config.fail_on_unreachable() {
+                        return Err(e);
+                    }
+                    log::error!("{}, continuing without LanguageTool suggestions", e);
+                }
+                Err(e) => log::warn!("LanguageTool checks failed: {}", e),
             }
         }
     }
@@ -98,16 +386,57 @@ where
     {
         if config.is_enabled(Detector::Hunspell) {
             debug!("Running Hunspell checks");
-            let config = config
-                .hunspell
-                .as_ref()
-                .expect("Must be Some(HunspellConfig) if is_enabled returns true");
-            if let Ok(suggestions) = self::hunspell::HunspellChecker::check(documentation, config) {
-                collective.join(suggestions);
-            }
+            // a broken or missing dictionary must not pass as a clean check
+            let suggestions = timings.measure(phase(Detector::Hunspell), || {
+                self::hunspell::HunspellChecker::check_with_jobs(documentation, config, jobs)
+            })?;
+            collective.join(suggestions);
         }
     }
 
+    if config.is_enabled(Detector::Reflow) {
+        debug!("Running Reflow checks");
+        let config = config
+            .reflow
+            .as_ref()
+            .expect("Must be Some(ReflowConfig) if is_enabled returns true");
+        let suggestions = timings.measure(phase(Detector::Reflow), || {
+            self::reflow::ReflowChecker::check(documentation, config)
+        })?;
+        collective.join(suggestions);
+    }
+
+    if config.is_enabled(Detector::RepeatedWord) {
+        debug!("Running RepeatedWord checks");
+        let suggestions = timings.measure(phase(Detector::RepeatedWord), || {
+            self::repeated::RepeatedWordChecker::check(documentation, config)
+        })?;
+        collective.join(suggestions);
+    }
+
+    if config.is_enabled(Detector::External) {
+        debug!("Running External checks");
+        let config = config
+            .external
+            .as_ref()
+            .expect("Must be Some(ExternalConfig) if is_enabled returns true");
+        let suggestions = timings.measure(phase(Detector::External), || {
+            self::external::ExternalChecker::check(documentation, config)
+        })?;
+        collective.join(suggestions);
+    }
+
+    if !code_only.is_empty() {
+        debug!(
+            "Not reporting on {} chunks which are nothing but code",
+            code_only.len()
+        );
+        collective.retain(|_origin, suggestion| {
+            !code_only.contains(&(suggestion.chunk as *const CheckableChunk))
+        });
+    }
+    collective.sort();
+
     Ok(collective)
 }
 
@@ -134,11 +463,258 @@ pub mod tests {
         ];
     }
 
+    #[test]
+    fn abbreviations() {
+        const TEXT: &str = "Use a tool, e.g. a hammer, i.e. not etc. vs. E.g. cf.";
+        let words = |tokenizer: &Tokenizer, text: &str| {
+            tokenizer
+                .tokenize(text)
+                .into_iter()
+                .map(|range| crate::util::sub_chars(text, range))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            words(&Tokenizer::default(), TEXT),
+            vec!["Use", "a", "tool", "a", "hammer", "not"]
+        );
+        // must be followed by a boundary
+        assert_eq!(
+            words(&Tokenizer::default(), "vs.x versus"),
+            vec!["vs", "x", "versus"]
+        );
+        assert_eq!(
+            words(&Tokenizer::new(&["approx.".to_owned()]), "approx. five"),
+            vec!["five"]
+        );
+        let extra = ["e.g.", "approx.", "vs.", "approx."]
+            .iter()
+            .map(|abbreviation| (*abbreviation).to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            Tokenizer::new(&extra).abbreviations,
+            vec!["approx.", "e.g.", "etc.", "i.e.", "cf.", "vs."]
+        );
+    }
+
+    #[test]
+    fn apostrophes() {
+        let words = |tokenizer: &Tokenizer, text: &str| {
+            tokenizer
+                .tokenize(text)
+                .into_iter()
+                .map(|range| crate::util::sub_chars(text, range))
+                .collect::<Vec<_>>()
+        };
+        let tokenizer = Tokenizer::default();
+        assert_eq!(
+            words(&tokenizer, "I don't know"),
+            vec!["I", "don't", "know"]
+        );
+        assert_eq!(
+            words(&tokenizer, "'tis the season"),
+            vec!["'tis", "the", "season"]
+        );
+        assert_eq!(words(&tokenizer, "rock'n'roll"), vec!["rock'n'roll"]);
+        assert_eq!(words(&tokenizer, "can’t stop"), vec!["can’t", "stop"]);
+        // quotes are left to the quirks
+        assert_eq!(
+            words(&tokenizer, "a 'quoted' word"),
+            vec!["a", "'quoted'", "word"]
+        );
+        assert_eq!(
+            words(&tokenizer, "the users' homes"),
+            vec!["the", "users'", "homes"]
+        );
+        assert_eq!(words(&tokenizer, "a ' b ''"), vec!["a", "b"]);
+        assert_eq!(words(&tokenizer, "‘quoted’ text"), vec!["quoted’", "text"]);
+
+        let tokenizer = Tokenizer::default().with_apostrophes(&['\'']);
+        assert_eq!(words(&tokenizer, "can’t don't"), vec!["can", "t", "don't"]);
+    }
+
+    #[test]
+    fn languages() {
+        const FRENCH: &str = "L'eau qu’il boit, jusqu'à aujourd'hui, n'est pas d'ici.";
+        const GERMAN: &str = "Die E-Mail-Adresse im Ein- und Ausgang, Donaudampfschiff.";
+        let words = |lang: &str, text: &str| {
+            let tokenizer = Tokenizer::default().with_language(lang);
+            tokenizer
+                .tokenize(text)
+                .into_iter()
+                .map(|range| crate::util::sub_chars(text, range))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            words("fr_FR", FRENCH),
+            vec![
+                "L",
+                "eau",
+                "qu",
+                "il",
+                "boit",
+                "jusqu",
+                "à",
+                "aujourd'hui",
+                "n",
+                "est",
+                "pas",
+                "d",
+                "ici"
+            ]
+        );
+        assert_eq!(words("en_US", FRENCH)[..3], ["L'eau", "qu’il", "boit"]);
+        assert_eq!(
+            words("de_DE", GERMAN),
+            vec![
+                "Die",
+                "E-Mail-Adresse",
+                "im",
+                "Ein",
+                "und",
+                "Ausgang",
+                "Donaudampfschiff"
+            ]
+        );
+        assert_eq!(words("en_US", GERMAN)[..4], ["Die", "E", "Mail", "Adresse"]);
+        // contractions stay whole, even if they look like an elision
+        assert_eq!(words("en_US", "don't l'm"), vec!["don't", "l'm"]);
+        assert_eq!(words("fr", "l'"), vec!["l'"]);
+        assert_eq!(
+            TokenizationPolicy::of("fr-CA"),
+            TokenizationPolicy::of("fr_FR")
+        );
+        assert_eq!(
+            TokenizationPolicy::of("en_GB"),
+            TokenizationPolicy::default()
+        );
+    }
+
     #[test]
     fn tokens() {
-        let ranges: Vec<Range> = tokenize(TEXT);
+        let ranges: Vec<Range> = Tokenizer::default().tokenize(TEXT);
         for (range, expect) in ranges.into_iter().zip(TOKENS.iter()) {
-            assert_eq!(&&TEXT[range], expect);
+            assert_eq!(&crate::util::sub_chars(TEXT, range), expect);
+        }
+    }
+
+    mod tokenization {
+        use super::super::Tokenizer;
+        use crate::suggestion::single_line_chunk;
+        use crate::util::sub_chars;
+        use crate::{ContentOrigin, Documentation};
+
+        fn words(text: &str) -> Vec<String> {
+            Tokenizer::default()
+                .tokenize(text)
+                .into_iter()
+                .map(|range| sub_chars(text, range))
+                .collect()
+        }
+
+        #[test]
+        fn unicode() {
+            assert_eq!(
+                words("Naïve café in Zürich"),
+                vec!["Naïve", "café", "in", "Zürich"]
+            );
+            assert_eq!(words("Привет, мир!"), vec!["Привет", "мир"]);
+            // scripts without spaces form a single token
+            assert_eq!(words("日本語です。"), vec!["日本語です"]);
+            // combining characters are part of the letter
+            assert_eq!(
+                words("nai\u{308}ve Gru\u{308}ße"),
+                vec!["nai\u{308}ve", "Gru\u{308}ße"]
+            );
+            // punctuation of any script separates words
+            assert_eq!(
+                words("«Grüße» ¿Qué? „Wort“ dash—dash…"),
+                vec!["Grüße", "Qué", "Wort", "dash", "dash"]
+            );
+            // non-breaking spaces separate words, character ranges are returned
+            let text = "a\u{a0}bé c";
+            assert_eq!(Tokenizer::default().tokenize(text), vec![0..1, 2..4, 5..6]);
+        }
+
+        #[test]
+        fn spans() {
+            const TEXT: &str = "Naïve Grüße, привет мир";
+            let chunk = single_line_chunk(TEXT, 1, 4);
+            // as done by the checkers, through the plain overlay
+            let plain = chunk.erase_markdown();
+            let ranges = Tokenizer::default().tokenize(plain.as_str());
+            assert_eq!(ranges, vec![0..5, 6..11, 13..19, 20..23]);
+            let columns = ranges
+                .into_iter()
+                .map(|range| {
+                    let spans = plain.find_spans(range);
+                    assert_eq!(spans.len(), 1);
+                    let span = spans.values().next().unwrap();
+                    (span.start.column, span.end.column)
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(columns, vec![(4, 8), (10, 14), (17, 22), (24, 26)]);
+
+            // non-ascii characters before markup must not shift the spans
+            const SOURCE: &str = "/// *Grüße* wrold and über *naïve* `cöde` tset.\nstruct A;";
+            let origin = ContentOrigin::RustSourceFile(std::path::PathBuf::from("src/lib.rs"));
+            let docs = Documentation::from((origin, SOURCE));
+            let (_origin, chunks) = docs.iter().next().unwrap();
+            let chunk = chunks.first().unwrap();
+            let plain = chunk.erase_markdown();
+            let spans = Tokenizer::default()
+                .tokenize(plain.as_str())
+                .into_iter()
+                .map(|range| {
+                    let word = sub_chars(plain.as_str(), range.clone());
+                    let span = *plain.find_spans(range).values().next().unwrap();
+                    (word, span.start.column, span.end.column)
+                })
+                .collect::<Vec<_>>();
+            let column = |word: &str| SOURCE.split(word).next().unwrap().chars().count();
+            assert_eq!(spans.len(), 6);
+            for (word, start, end) in spans {
+                assert_eq!(start, column(&word));
+                assert_eq!(end, column(&word) + word.chars().count() - 1);
+            }
+        }
+
+        #[test]
+        fn digits() {
+            assert_eq!(
+                words("Use utf8 or UTF-16, the 3rd of 12 options"),
+                vec!["Use", "utf8", "or", "UTF", "16", "the", "3rd", "of", "12", "options"]
+            );
+            assert_eq!(words("version 1.2.3"), vec!["version", "1", "2", "3"]);
+        }
+
+        #[test]
+        fn hyphenation() {
+            assert_eq!(
+                words("A well-known, state-of-the-art tool"),
+                vec!["A", "well", "known", "state", "of", "the", "art", "tool"]
+            );
+            // line breaks are boundaries, words split across lines are not joined
+            assert_eq!(words("valid-\nated"), vec!["valid", "ated"]);
+            assert_eq!(words("-- dashes --"), vec!["dashes"]);
+        }
+
+        #[test]
+        fn punctuation() {
+            assert_eq!(
+                words("Hello, world! Really? (Yes) [no] {maybe}; fine: \"quoted\""),
+                vec!["Hello", "world", "Really", "Yes", "no", "maybe", "fine", "quoted"]
+            );
+            assert_eq!(
+                words("a/b `code` #tag path::to"),
+                vec!["a", "b", "code", "tag", "path", "to"]
+            );
+            // underscores join words, other symbols separate them
+            assert_eq!(
+                words("snake_case *bold* 50% x+y"),
+                vec!["snake_case", "bold", "50", "x", "y"]
+            );
+            assert_eq!(words(""), Vec::<String>::new());
+            assert_eq!(words(" ,.!? "), Vec::<String>::new());
         }
     }
 
@@ -240,4 +816,26 @@ pub mod tests {
         ];
         extraction_test_body(SIMPLE, EXPECTED_SPANS);
     }
+
+    #[cfg(feature = "languagetool")]
+    #[test]
+    fn fail_on_unreachable() {
+        let docs = Documentation::from((ContentOrigin::TestEntity, "/// Hello world\nstruct A;"));
+        let mut config = Config {
+            hunspell: None,
+            languagetool: Some(crate::LanguageToolConfig {
+                enabled: Some(true),
+                url: crate::config::Urls::Single(languagetool::tests::unused_endpoint()),
+                fail_on_unreachable: Some(false),
+            }),
+            ..Default::default()
+        };
+        let suggestions = check(&docs, &config, 1, &mut Timings::new())
+            .expect("Unreachable LanguageTool must not fail by default");
+        assert_eq!(suggestions.total_count(), 0);
+
+        config.languagetool.as_mut().unwrap().fail_on_unreachable = Some(true);
+        let e = check(&docs, &config, 1, &mut Timings::new()).unwrap_err();
+        assert!(e.is::<languagetool::Unreachable>());
+    }
 }
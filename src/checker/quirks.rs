@@ -0,0 +1,383 @@
+//! Quirks
+//!
+//! Tokens which are not found in the dictionary as they are, but
+//! commonly are valid words nonetheless, i.e. because they are
+//! enclosed in single quotes or carry a possessive suffix.
+//!
+//! Every quirk transforms a token into a simpler one, which is then
+//! checked again, until either the check passes or no quirk applies.
+
+use anyhow::{anyhow, Error, Result};
+use log::{trace, warn};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+/// Apostrophes, both typewriter and typographic.
+const APOSTROPHES: &[char] = &['\'', '’'];
+
+/// Typographic characters, as inserted by word processors, and their ASCII
+/// equivalents.
+const TYPOGRAPHIC: &[(char, &str)] = &[
+    ('‘', "'"),
+    ('’', "'"),
+    ('‚', "'"),
+    ('‹', "'"),
+    ('›', "'"),
+    ('ʼ', "'"),
+    ('“', "\""),
+    ('”', "\""),
+    ('„', "\""),
+    ('«', "\""),
+    ('»', "\""),
+    ('–', "-"),
+    ('—', "-"),
+    ('…', "..."),
+];
+
+/// A transformation of a token, that is worth checking in case the
+/// token itself is not accepted.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Quirk {
+    /// `'word'` is checked as `word`.
+    SingleQuoted,
+    /// `word's` and `words'` are checked as `word` and `words`.
+    ///
+    /// Does not apply to tokens starting with an apostrophe, those
+    /// are the domain of [`Quirk::SingleQuoted`].
+    Possessive,
+    /// Typographic apostrophes, quotes and dashes within the token are
+    /// replaced by their ASCII equivalents, i.e. `don’t` is checked as
+    /// `don't`.
+    ///
+    /// Only configured apostrophes end up within a token, all other such
+    /// characters separate words. Best listed first, so the other quirks see
+    /// the normalized token.
+    NormalizeTypographic,
+}
+
+impl Quirk {
+    /// Quirks enabled if none are configured.
+    pub const DEFAULT: &'static [Quirk] = &[Quirk::SingleQuoted, Quirk::Possessive];
+
+    /// All supported quirks.
+    pub const ALL: &'static [Quirk] = &[
+        Quirk::SingleQuoted,
+        Quirk::Possessive,
+        Quirk::NormalizeTypographic,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SingleQuoted => "single-quoted",
+            Self::Possessive => "possessive",
+            Self::NormalizeTypographic => "normalize-typographic",
+        }
+    }
+
+    /// What the quirk does, in a single line.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::SingleQuoted => "A token enclosed in single quotes is checked without them.",
+            Self::Possessive => "A possessive `'s` or trailing `'` is stripped before checking.",
+            Self::NormalizeTypographic => {
+                "Typographic quotes, dashes and ellipses are checked as their ASCII equivalents."
+            }
+        }
+    }
+
+    /// A token the quirk applies to and what it is checked as.
+    pub fn example(&self) -> (&'static str, &'static str) {
+        match self {
+            Self::SingleQuoted => ("'word'", "word"),
+            Self::Possessive => ("word's", "word"),
+            Self::NormalizeTypographic => ("don’t", "don't"),
+        }
+    }
+
+    /// Transform `word`, returns `None` if the quirk does not apply.
+    pub fn call(&self, word: &str) -> Option<String> {
+        match self {
+            Self::SingleQuoted => {
+                let inner = word.strip_prefix(APOSTROPHES)?.strip_suffix(APOSTROPHES)?;
+                if inner.is_empty() {
+                    None
+                } else {
+                    Some(inner.to_owned())
+                }
+            }
+            Self::Possessive => {
+                if word.starts_with(APOSTROPHES) {
+                    return None;
+                }
+                let base = word
+                    .strip_suffix('s')
+                    .and_then(|stripped| stripped.strip_suffix(APOSTROPHES))
+                    .or_else(|| word.strip_suffix(APOSTROPHES))?;
+                if base.is_empty() {
+                    None
+                } else {
+                    Some(base.to_owned())
+                }
+            }
+            Self::NormalizeTypographic => {
+                if !word
+                    .chars()
+                    .any(|c| TYPOGRAPHIC.iter().any(|(t, _)| *t == c))
+                {
+                    return None;
+                }
+                let normalized =
+                    word.chars()
+                        .fold(String::with_capacity(word.len()), |mut acc, c| {
+                            match TYPOGRAPHIC.iter().find(|(t, _)| *t == c) {
+                                Some((_, ascii)) => acc.push_str(ascii),
+                                None => acc.push(c),
+                            }
+                            acc
+                        });
+                Some(normalized)
+            }
+        }
+    }
+}
+
+impl FromStr for Quirk {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .iter()
+            .find(|quirk| quirk.as_str() == s)
+            .copied()
+            .ok_or_else(|| {
+                anyhow!(
+                    "Unknown quirk {}, expected one of {}",
+                    s,
+                    Self::ALL
+                        .iter()
+                        .map(|quirk| quirk.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })
+    }
+}
+
+impl TryFrom<String> for Quirk {
+    type Error = Error;
+    fn try_from(s: String) -> Result<Self> {
+        Self::from_str(s.as_str())
+    }
+}
+
+impl From<Quirk> for String {
+    fn from(quirk: Quirk) -> Self {
+        quirk.as_str().to_owned()
+    }
+}
+
+impl fmt::Display for Quirk {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+/// The set of quirks to apply to a token.
+#[derive(Debug, Clone, Copy)]
+pub struct Quirks<'q> {
+    quirks: &'q [Quirk],
+    max_iterations: usize,
+}
+
+impl<'q> Quirks<'q> {
+    /// Number of quirks applied to a single token at most, unless configured.
+    pub const DEFAULT_MAX_ITERATIONS: usize = 16;
+
+    pub fn new(quirks: &'q [Quirk]) -> Self {
+        Self {
+            quirks,
+            max_iterations: Self::DEFAULT_MAX_ITERATIONS,
+        }
+    }
+
+    /// Limit the number of quirks applied to a single token.
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    /// Keep applying the first quirk which applies to `word`, and pass
+    /// the result to `visit` until it returns `true`.
+    ///
+    /// Stops if no quirk applies anymore, a quirk did not change the
+    /// word or the iteration limit is reached.
+    fn apply(&self, word: &str, mut visit: impl FnMut(Quirk, &str) -> bool) -> bool {
+        let mut word = word.to_owned();
+        let mut iterations = 0usize;
+        while let Some((quirk, transformed)) = self
+            .quirks
+            .iter()
+            .find_map(|quirk| quirk.call(&word).map(|transformed| (*quirk, transformed)))
+        {
+            if transformed == word {
+                warn!("Quirk {} did not change >{}<, stopping", quirk, word);
+                break;
+            }
+            if iterations >= self.max_iterations {
+                warn!(
+                    "Reached the limit of {} quirk iterations for >{}<, stopping",
+                    self.max_iterations, word
+                );
+                break;
+            }
+            iterations += 1;
+            trace!("Applied quirk {}: >{}< -> >{}<", quirk, word, transformed);
+            if visit(quirk, &transformed) {
+                return true;
+            }
+            word = transformed;
+        }
+        false
+    }
+
+    /// The chain of quirks applied to `word` and the resulting
+    /// transformations, until no more quirk applies.
+    pub fn transformations(&self, word: &str) -> Vec<(Quirk, String)> {
+        let mut chain = Vec::new();
+        self.apply(word, |quirk, transformed| {
+            chain.push((quirk, transformed.to_owned()));
+            false
+        });
+        chain
+    }
+
+    /// Check `word` with `check`, and if that fails, keep applying the
+    /// first quirk which applies until `check` passes.
+    pub fn check_quirk(&self, word: &str, mut check: impl FnMut(&str) -> bool) -> bool {
+        check(word) || self.apply(word, |_quirk, transformed| check(transformed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dictionary(word: &str) -> bool {
+        ["Rust", "API", "word", "words"].contains(&word)
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            Quirk::from_str("single-quoted").unwrap(),
+            Quirk::SingleQuoted
+        );
+        assert_eq!(Quirk::from_str("possessive").unwrap(), Quirk::Possessive);
+        assert!(Quirk::from_str("possesive").is_err());
+    }
+
+    #[test]
+    fn possessive() {
+        let quirks = Quirks::new(&[Quirk::Possessive]);
+        assert!(quirks.check_quirk("Rust's", dictionary));
+        assert!(quirks.check_quirk("API’s", dictionary));
+        assert!(quirks.check_quirk("words'", dictionary));
+        assert!(!quirks.check_quirk("Rusts's", dictionary));
+        assert!(!quirks.check_quirk("'s", dictionary));
+        // enclosed in quotes, which is not a possessive
+        assert!(!quirks.check_quirk("'word'", dictionary));
+    }
+
+    #[test]
+    fn single_quoted() {
+        let quirks = Quirks::new(Quirk::DEFAULT);
+        assert!(quirks.check_quirk("'word'", dictionary));
+        assert!(quirks.check_quirk("'Rust's'", dictionary));
+        assert!(!quirks.check_quirk("'wrod'", dictionary));
+        assert!(!quirks.check_quirk("''", dictionary));
+        assert!(!Quirks::new(&[]).check_quirk("'word'", dictionary));
+    }
+
+    #[test]
+    fn normalize_typographic() {
+        const TEXT: &str = "„Don’t“ call the ‘students’ work well–known… «wrod»";
+        let dictionary = |word: &str| {
+            ["Don't", "call", "the", "students", "work", "well", "known"].contains(&word)
+        };
+        let quirks = Quirks::new(&[
+            Quirk::NormalizeTypographic,
+            Quirk::SingleQuoted,
+            Quirk::Possessive,
+        ]);
+        // as the tokens are yielded by the tokenizer
+        let tokens = crate::checker::Tokenizer::default()
+            .tokenize(TEXT)
+            .into_iter()
+            .map(|range| crate::util::sub_chars(TEXT, range))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            vec![
+                "Don’t",
+                "call",
+                "the",
+                "students’",
+                "work",
+                "well",
+                "known",
+                "wrod"
+            ]
+        );
+        let accepted = |quirks: &Quirks| {
+            tokens
+                .iter()
+                .filter(|token| quirks.check_quirk(token, dictionary))
+                .count()
+        };
+        assert_eq!(accepted(&quirks), 7);
+        // not enabled by default
+        assert_eq!(accepted(&Quirks::new(Quirk::DEFAULT)), 6);
+        assert_eq!(Quirk::NormalizeTypographic.call("word"), None);
+    }
+
+    #[test]
+    fn transformations() {
+        let quirks = Quirks::new(Quirk::DEFAULT);
+        assert_eq!(
+            quirks.transformations("'Rust's'"),
+            vec![
+                (Quirk::SingleQuoted, "Rust's".to_owned()),
+                (Quirk::Possessive, "Rust".to_owned()),
+            ]
+        );
+        assert!(quirks.transformations("Rust").is_empty());
+    }
+
+    #[test]
+    fn listing() {
+        for quirk in Quirk::ALL {
+            assert_eq!(quirk.as_str().parse::<Quirk>().unwrap(), *quirk);
+            let (token, checked_as) = quirk.example();
+            assert_eq!(quirk.call(token).as_deref(), Some(checked_as));
+        }
+        assert!(Quirk::DEFAULT
+            .iter()
+            .all(|quirk| Quirk::ALL.contains(quirk)));
+        assert!("single_quoted".parse::<Quirk>().is_err());
+    }
+
+    #[test]
+    fn iteration_limit() {
+        let quirks = Quirks::new(Quirk::DEFAULT).with_max_iterations(1);
+        assert_eq!(
+            quirks.transformations("'Rust's'"),
+            vec![(Quirk::SingleQuoted, "Rust's".to_owned())]
+        );
+        assert!(!quirks.check_quirk("'Rust's'", dictionary));
+        assert!(!Quirks::new(Quirk::DEFAULT)
+            .with_max_iterations(0)
+            .check_quirk("'word'", dictionary));
+    }
+}
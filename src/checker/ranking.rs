@@ -0,0 +1,168 @@
+//! Ranking
+//!
+//! Order the replacements provided by a checker, so the most useful
+//! ones are presented first.
+
+use anyhow::{bail, Error, Result};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+/// Number of single character insertions, deletions or substitutions
+/// required to transform `a` into `b`.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    let mut current = vec![0usize; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + if ca == *cb { 0 } else { 1 };
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// Capitalization of a word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Casing {
+    Lower,
+    Upper,
+    /// First letter uppercase, all others lowercase.
+    Capitalized,
+    Mixed,
+}
+
+impl Casing {
+    fn of(word: &str) -> Self {
+        let mut letters = word.chars().filter(|c| c.is_alphabetic());
+        let first = if let Some(first) = letters.next() {
+            first
+        } else {
+            return Self::Lower;
+        };
+        let rest = letters.collect::<Vec<_>>();
+        if first.is_lowercase() && rest.iter().all(|c| c.is_lowercase()) {
+            Self::Lower
+        } else if first.is_uppercase() && rest.iter().all(|c| c.is_lowercase()) {
+            Self::Capitalized
+        } else if first.is_uppercase() && rest.iter().all(|c| c.is_uppercase()) {
+            Self::Upper
+        } else {
+            Self::Mixed
+        }
+    }
+}
+
+/// Strategy to order the replacements of a mistake.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Ranking {
+    /// Keep the order as provided by the checker.
+    Checker,
+    /// Replacements with the same capitalization as the mistake first,
+    /// then by ascending edit distance to the mistake.
+    Similarity,
+}
+
+impl Default for Ranking {
+    fn default() -> Self {
+        Self::Checker
+    }
+}
+
+impl Ranking {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Checker => "checker",
+            Self::Similarity => "similarity",
+        }
+    }
+
+    /// Order the `replacements` for the mistake `word`, the relative
+    /// order of equally ranked replacements is retained.
+    pub fn rank(&self, word: &str, mut replacements: Vec<String>) -> Vec<String> {
+        match self {
+            Self::Checker => {}
+            Self::Similarity => {
+                let casing = Casing::of(word);
+                replacements.sort_by_cached_key(|replacement| {
+                    (
+                        Casing::of(replacement) != casing,
+                        edit_distance(word, replacement),
+                    )
+                });
+            }
+        }
+        replacements
+    }
+}
+
+impl FromStr for Ranking {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "checker" => Self::Checker,
+            "similarity" => Self::Similarity,
+            other => bail!("Unknown ranking {}", other),
+        })
+    }
+}
+
+impl TryFrom<String> for Ranking {
+    type Error = Error;
+    fn try_from(s: String) -> Result<Self> {
+        Self::from_str(s.as_str())
+    }
+}
+
+impl From<Ranking> for String {
+    fn from(ranking: Ranking) -> Self {
+        ranking.as_str().to_owned()
+    }
+}
+
+impl fmt::Display for Ranking {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("wrold", "world"), 2);
+        assert_eq!(edit_distance("Grüße", "Grüsse"), 2);
+        assert_eq!(edit_distance("abc", ""), 3);
+    }
+
+    #[test]
+    fn similarity() {
+        let replacements = vec![
+            "wold".to_owned(),
+            "World".to_owned(),
+            "would".to_owned(),
+            "world".to_owned(),
+        ];
+        assert_eq!(
+            Ranking::Similarity.rank("Wrold", replacements.clone()),
+            vec!["World", "wold", "would", "world"]
+        );
+        assert_eq!(
+            Ranking::Similarity.rank("wrold", replacements.clone()),
+            vec!["wold", "would", "world", "World"]
+        );
+        assert_eq!(
+            Ranking::Checker.rank("wrold", replacements.clone()),
+            replacements
+        );
+    }
+}
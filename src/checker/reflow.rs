@@ -0,0 +1,365 @@
+//! Reflow
+//!
+//! Re-wrap doc comment paragraphs which contain lines exceeding the
+//! configured width, retaining the indentation and the comment prefix.
+//!
+//! Only plain paragraphs of `///` and `//!` comments are considered,
+//! code blocks, tables, headings, lists and block quotes are left as
+//! they are.
+
+use super::Checker;
+use crate::config::ReflowConfig;
+use crate::util::sub_chars;
+use crate::{ContentOrigin, Detector, Documentation, Range, Span, Suggestion, SuggestionSet};
+
+use anyhow::Result;
+use log::{debug, trace};
+
+pub(crate) struct ReflowChecker;
+
+/// A single line of a doc comment.
+#[derive(Debug, Clone)]
+struct Line {
+    /// Range of the content within the chunk.
+    range: Range,
+    /// Location of the content within the file.
+    span: Span,
+    /// Indentation and comment prefix in front of the content, i.e. `    ///`.
+    prefix: String,
+    /// Content of the line, including the space following the prefix.
+    content: String,
+}
+
+impl Line {
+    fn width(&self) -> usize {
+        self.prefix.chars().count() + self.content.chars().count()
+    }
+
+    /// A hard line break, the line must remain the last one of its paragraph.
+    fn ends_hard(&self) -> bool {
+        self.content.ends_with("  ") || self.content.ends_with('\\')
+    }
+}
+
+/// Check if the content of a doc comment line is prose, which may be
+/// joined with its neighbours.
+fn is_prose(content: &str) -> bool {
+    // a single space commonly follows the comment prefix, more indicate
+    // an indented code block or the continuation of a list item
+    let text = content.strip_prefix(' ').unwrap_or(content);
+    let first = if let Some(first) = text.split_whitespace().next() {
+        first
+    } else {
+        return false;
+    };
+    if text.starts_with(char::is_whitespace) {
+        return false;
+    }
+    // headings, tables, block quotes, html, lists and emphasis, which is
+    // indistinguishable from an unordered list at this point
+    if first.starts_with(&['#', '|', '>', '<', '-', '*', '+', '='][..]) {
+        return false;
+    }
+    // ordered lists
+    let digits = first.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 && first[digits..].starts_with(&['.', ')'][..]) {
+        return false;
+    }
+    // link reference definitions
+    if first.starts_with('[') && text.contains("]:") {
+        return false;
+    }
+    true
+}
+
+/// A code fence, either with backticks or tildes.
+fn is_fence(content: &str) -> bool {
+    let text = content.trim_start();
+    text.starts_with("```") || text.starts_with("~~~")
+}
+
+/// Greedily fill lines of at most `width` characters with `words`,
+/// a word which exceeds `width` by itself is put on a line of its own.
+fn wrap<'w>(words: impl IntoIterator<Item = &'w str>, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut current_width = 0usize;
+    for word in words {
+        let word_width = word.chars().count();
+        if current_width > 0 && current_width + 1 + word_width > width {
+            lines.push(std::mem::take(&mut current));
+            current_width = 0;
+        }
+        if current_width > 0 {
+            current.push(' ');
+            current_width += 1;
+        }
+        current.push_str(word);
+        current_width += word_width;
+    }
+    if current_width > 0 {
+        lines.push(current);
+    }
+    lines
+}
+
+/// Re-wrap the `paragraph` to `max_width`, returns `None` if no line
+/// exceeds `max_width` or wrapping does not change anything.
+fn reflow(paragraph: &[Line], max_width: usize) -> Option<String> {
+    if paragraph.iter().all(|line| line.width() <= max_width) {
+        return None;
+    }
+    let prefix = paragraph.first()?.prefix.as_str();
+    let separator = format!("\n{}", prefix);
+    // the space following the prefix
+    let width = max_width.saturating_sub(prefix.chars().count() + 1).max(1);
+    let wrapped = wrap(
+        paragraph
+            .iter()
+            .flat_map(|line| line.content.split_whitespace()),
+        width,
+    )
+    .into_iter()
+    .map(|line| format!(" {}", line))
+    .collect::<Vec<_>>()
+    .join(separator.as_str());
+    let original = paragraph
+        .iter()
+        .map(|line| line.content.as_str())
+        .collect::<Vec<_>>()
+        .join(separator.as_str());
+    if wrapped == original {
+        None
+    } else {
+        Some(wrapped)
+    }
+}
+
+/// Split the lines of a chunk into paragraphs of prose.
+///
+/// `source` are the lines of the file the chunk originates from, used to
+/// obtain the comment prefix of each line.
+fn paragraphs<'a>(
+    fragments: impl IntoIterator<Item = (&'a Range, &'a Span)>,
+    chunk: &str,
+    source: &[&str],
+) -> Vec<Vec<Line>> {
+    let mut paragraphs = Vec::new();
+    let mut current: Vec<Line> = Vec::new();
+    let mut in_fence = false;
+    for (range, span) in fragments {
+        let content = sub_chars(chunk, range.clone());
+        let line = if span.start.line == span.end.line && !range.is_empty() {
+            source
+                .get(span.start.line.saturating_sub(1))
+                // the file might have changed since
+                .filter(|text| sub_chars(text, span.start.column..span.end.column + 1) == content)
+                .map(|text| Line {
+                    range: range.clone(),
+                    span: *span,
+                    prefix: text.chars().take(span.start.column).collect::<String>(),
+                    content: content.clone(),
+                })
+        } else {
+            None
+        };
+        if is_fence(&content) {
+            in_fence = !in_fence;
+        }
+        let line = line.filter(|line| {
+            let prefix = line.prefix.trim_start();
+            !in_fence
+                && !is_fence(&line.content)
+                && (prefix == "///" || prefix == "//!")
+                && is_prose(&line.content)
+        });
+        match line {
+            Some(line) => {
+                let continues = current.last().map(|last| {
+                    last.span.end.line + 1 == line.span.start.line
+                        && last.prefix == line.prefix
+                        && !last.ends_hard()
+                });
+                if continues == Some(false) {
+                    paragraphs.push(std::mem::take(&mut current));
+                }
+                current.push(line);
+            }
+            None if !current.is_empty() => {
+                paragraphs.push(std::mem::take(&mut current));
+            }
+            None => {}
+        }
+    }
+    if !current.is_empty() {
+        paragraphs.push(current);
+    }
+    paragraphs
+}
+
+impl Checker for ReflowChecker {
+    type Config = ReflowConfig;
+
+    fn check<'a, 's>(docu: &'a Documentation, config: &Self::Config) -> Result<SuggestionSet<'s>>
+    where
+        'a: 's,
+    {
+        let max_width = config.max_width();
+        let mut acc = SuggestionSet::new();
+        for (origin, chunks) in docu.iter() {
+            let path = match origin {
+                ContentOrigin::RustSourceFile(path) => path,
+                _ => continue,
+            };
+            let source = match std::fs::read_to_string(path) {
+                Ok(source) => source,
+                Err(e) => {
+                    debug!("Not reflowing {}, failed to read: {}", path.display(), e);
+                    continue;
+                }
+            };
+            let source = source.lines().collect::<Vec<_>>();
+            for chunk in chunks {
+                for paragraph in paragraphs(chunk.iter(), chunk.as_str(), &source) {
+                    let replacement = if let Some(replacement) = reflow(&paragraph, max_width) {
+                        replacement
+                    } else {
+                        continue;
+                    };
+                    let (first, last) = (&paragraph[0], &paragraph[paragraph.len() - 1]);
+                    trace!(
+                        "Reflowing lines {}..={} of {}",
+                        first.span.start.line,
+                        last.span.end.line,
+                        path.display()
+                    );
+                    acc.add(
+                        origin.clone(),
+                        Suggestion {
+                            detector: Detector::Reflow,
+                            origin: origin.clone(),
+                            chunk,
+                            span: Span {
+                                start: first.span.start,
+                                end: last.span.end,
+                            },
+                            range: first.range.start..last.range.end,
+                            replacements: vec![replacement],
+                            description: Some(format!(
+                                "Paragraph exceeds the maximum line width of {}.",
+                                max_width
+                            )),
+                            score: None,
+                            rule_id: None,
+                        },
+                    );
+                }
+            }
+        }
+        Ok(acc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"/// A short line.
+/// This line of the paragraph is considerably longer than the configured maximum width.
+/// It continues.
+///
+/// ```rust
+/// let code_is_never_wrapped = "regardless of its length, which is also far beyond the limit";
+/// ```
+///
+/// | a table is never wrapped either | regardless of its length, which exceeds the limit |
+///
+/// Short.
+struct A;
+"#;
+
+    fn reflowed(source: &str, max_width: usize) -> Vec<(usize, usize, String)> {
+        let path = std::env::temp_dir().join(format!(
+            "cargo-spellcheck-reflow-{}-{}.rs",
+            std::process::id(),
+            max_width
+        ));
+        std::fs::write(&path, source).unwrap();
+        let docs = Documentation::from((ContentOrigin::RustSourceFile(path.clone()), source));
+        let config = ReflowConfig {
+            enabled: None,
+            max_width: Some(max_width),
+        };
+        let suggestions = ReflowChecker::check(&docs, &config).unwrap();
+        let _ = std::fs::remove_file(&path);
+        suggestions
+            .iter()
+            .flat_map(|(_origin, suggestions)| suggestions.iter())
+            .map(|suggestion| {
+                (
+                    suggestion.span.start.line,
+                    suggestion.span.end.line,
+                    suggestion.replacements[0].clone(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn wrap_words() {
+        assert_eq!(
+            wrap("a bb ccc dddd".split_whitespace(), 6),
+            vec!["a bb", "ccc", "dddd"]
+        );
+        assert_eq!(
+            wrap("overlong a".split_whitespace(), 4),
+            vec!["overlong", "a"]
+        );
+        assert!(wrap(Vec::<&str>::new(), 4).is_empty());
+    }
+
+    #[test]
+    fn prose() {
+        assert!(is_prose(" Some text."));
+        assert!(is_prose(" 2021 was a year."));
+        assert!(!is_prose(""));
+        assert!(!is_prose(" "));
+        assert!(!is_prose("     indented code"));
+        assert!(!is_prose(" - item"));
+        assert!(!is_prose(" 1. item"));
+        assert!(!is_prose(" # Heading"));
+        assert!(!is_prose(" | table |"));
+        assert!(!is_prose(" [link]: https://example.com"));
+    }
+
+    #[test]
+    fn paragraph() {
+        assert_eq!(
+            reflowed(SOURCE, 40),
+            vec![(
+                1,
+                3,
+                " A short line. This line of the\n/// paragraph is considerably longer\n/// than the configured maximum width.\n/// It continues."
+                    .to_owned()
+            )]
+        );
+        assert!(reflowed(SOURCE, 100).is_empty());
+    }
+
+    #[test]
+    fn indented() {
+        const INDENTED: &str = r#"mod a {
+    //! Inner docs with a line which is just a little bit too long.
+    struct B;
+}
+"#;
+        assert_eq!(
+            reflowed(INDENTED, 50),
+            vec![(
+                2,
+                2,
+                " Inner docs with a line which is just a\n    //! little bit too long.".to_owned()
+            )]
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! Repeated words
+//!
+//! Flag a word which directly follows an identical one, ignoring case,
+//! i.e. `the the`, even if the two are split by a line break.
+//! Words separated by anything but whitespace, such as `that. That`,
+//! are not considered repeated.
+
+use super::{Checker, Tokenizer};
+use crate::config::Config;
+use crate::util::sub_chars;
+use crate::{Detector, Documentation, Range, Suggestion, SuggestionSet};
+
+use anyhow::{anyhow, Result};
+use log::{debug, trace};
+
+pub(crate) struct RepeatedWordChecker;
+
+/// Ranges of `text` to delete, one per word which repeats its predecessor.
+///
+/// The whitespace following the repeated word is deleted with it, or the
+/// whitespace preceding it if it ends the line, but never a line break.
+fn repeated(text: &str, tokenizer: &Tokenizer, allowed: &[String]) -> Vec<(Range, String)> {
+    let chars = text.chars().collect::<Vec<char>>();
+    let is_blank = |c: char| c == ' ' || c == '\t';
+    let mut acc = Vec::new();
+    let mut previous: Option<(Range, String)> = None;
+    // deletions must not overlap
+    let mut deleted_until = 0usize;
+    for range in tokenizer.tokenize(text) {
+        let word = sub_chars(text, range.clone());
+        let current = (range.clone(), word.to_lowercase());
+        let repeats = match previous {
+            Some((ref previous_range, ref previous_word)) => {
+                previous_word == &current.1
+                    && chars[previous_range.end..range.start]
+                        .iter()
+                        .all(|c| c.is_whitespace())
+            }
+            None => false,
+        };
+        if repeats
+            && word.chars().any(|c| c.is_alphabetic())
+            && !allowed
+                .iter()
+                .any(|allowed| allowed.to_lowercase() == current.1)
+        {
+            let previous_end = previous
+                .as_ref()
+                .map(|(range, _)| range.end)
+                .unwrap_or(0)
+                .max(deleted_until);
+            let mut start = range.start;
+            let mut end = range.end;
+            while end < chars.len() && is_blank(chars[end]) {
+                end += 1;
+            }
+            if end == range.end {
+                while start > previous_end && is_blank(chars[start - 1]) {
+                    start -= 1;
+                }
+            }
+            deleted_until = end;
+            acc.push((start..end, word));
+        }
+        previous = Some(current);
+    }
+    acc
+}
+
+impl Checker for RepeatedWordChecker {
+    type Config = Config;
+
+    fn check<'a, 's>(docu: &'a Documentation, config: &Self::Config) -> Result<SuggestionSet<'s>>
+    where
+        'a: 's,
+    {
+        let tokenizer = config.tokenizer();
+        let allowed = config
+            .repeated_words
+            .as_ref()
+            .ok_or_else(|| anyhow!("RepeatedWord is not configured"))?
+            .allowed();
+        let mut acc = SuggestionSet::new();
+        for (origin, chunks) in docu.iter() {
+            debug!("Processing {}", origin.as_path().display());
+            for chunk in chunks {
+                let plain = docu.erase_markdown(chunk);
+                for (range, word) in repeated(plain.as_str(), &tokenizer, allowed) {
+                    trace!("Repeated word (plain range: {:?}): >{}<", &range, &word);
+                    if let Some((range, span)) = plain.find_span(range) {
+                        acc.add(
+                            origin.clone(),
+                            Suggestion {
+                                detector: Detector::RepeatedWord,
+                                range,
+                                span,
+                                origin: origin.clone(),
+                                replacements: vec![String::new()],
+                                chunk,
+                                description: Some(format!("Word `{}` is repeated.", word)),
+                                score: None,
+                                rule_id: None,
+                            },
+                        )
+                    }
+                }
+            }
+        }
+        Ok(acc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ContentOrigin;
+    use std::path::PathBuf;
+
+    fn deleted(text: &str, allowed: &[String]) -> Vec<String> {
+        repeated(text, &Tokenizer::default(), allowed)
+            .into_iter()
+            .map(|(range, _word)| sub_chars(text, range))
+            .collect()
+    }
+
+    #[test]
+    fn words() {
+        assert_eq!(deleted("Pass the the salt.", &[]), vec!["the "]);
+        assert_eq!(deleted("Pass the The salt.", &[]), vec!["The "]);
+        assert_eq!(deleted("Pass the salt salt", &[]), vec![" salt"]);
+        assert_eq!(deleted("Across the\nthe line", &[]), vec!["the "]);
+        assert_eq!(deleted("a b a b", &[]), Vec::<String>::new());
+        assert_eq!(deleted("Stop. Stop, please.", &[]), Vec::<String>::new());
+        assert_eq!(deleted("Since 1999 1999", &[]), Vec::<String>::new());
+        assert_eq!(deleted("the the the", &[]), vec!["the ", "the"]);
+        assert_eq!(
+            deleted("He had had enough.", &["Had".to_owned()]),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn spans() {
+        let docs = Documentation::from((
+            ContentOrigin::RustSourceFile(PathBuf::from("/tmp/repeated.rs")),
+            "/// Pass the\n/// the salt.\nstruct A;\n",
+        ));
+        let mut config = Config::default();
+        config.repeated_words = Some(Default::default());
+        let suggestions = RepeatedWordChecker::check(&docs, &config).unwrap();
+        assert_eq!(suggestions.total_count(), 1);
+        let (_, suggestions) = suggestions.iter().next().unwrap();
+        assert_eq!(suggestions[0].detector, Detector::RepeatedWord);
+        assert_eq!(suggestions[0].replacements, vec![String::new()]);
+        assert_eq!(suggestions[0].span.start.line, 2);
+        assert_eq!(suggestions[0].span.start.column, 4);
+        assert_eq!(suggestions[0].span.end.column, 7);
+    }
+}
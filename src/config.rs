@@ -5,6 +5,7 @@
 //! A default configuration will be generated in the default
 //! location by default. Default. Default default default.
 
+use crate::checker::{Quirk, Quirks, Ranking, Tokenizer};
 use crate::suggestion::Detector;
 use anyhow::{anyhow, bail, Error, Result};
 use log::trace;
@@ -18,38 +19,123 @@ use std::path::{Path, PathBuf};
 pub struct Config {
     pub hunspell: Option<HunspellConfig>,
     pub languagetool: Option<LanguageToolConfig>,
+    pub extra: Option<ExtraConfig>,
+    pub tokenization: Option<TokenizationConfig>,
+    pub reflow: Option<ReflowConfig>,
+    pub manifest: Option<ManifestConfig>,
+    pub external: Option<ExternalConfig>,
+    pub repeated_words: Option<RepeatedWordsConfig>,
+    pub rust: Option<RustConfig>,
+    pub markdown: Option<MarkdownConfig>,
+    pub plain_text: Option<PlainTextConfig>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct HunspellConfig {
-    pub lang: Option<String>, // TODO impl a custom xx_YY code deserializer based on iso crates
+    /// Allows to disable the checker, while retaining its configuration.
+    pub enabled: Option<bool>,
+    /// A single language or a list of languages, only the first one is
+    /// used unless `auto_detect_language` is set.
+    pub lang: Option<Langs>, // TODO impl a custom xx_YY code deserializer based on iso crates
+    /// Pick the language of each markdown file among all of `lang`.
+    pub auto_detect_language: Option<bool>,
     // must be option so it can be omitted in the config
     pub search_dirs: Option<Vec<PathBuf>>,
-    pub extra_dictonaries: Option<Vec<PathBuf>>,
+    /// Only consult the configured `search_dirs`, none of the OS specific defaults.
+    pub skip_default_search_dirs: Option<bool>,
+    pub extra_dictonaries: Option<Vec<ExtraDictionary>>,
+    /// Personal dictionary used across all projects, defaults to
+    /// `user.dic` next to the default configuration file.
+    pub user_dictionary: Option<PathBuf>,
+    pub quirks: Option<Vec<Quirk>>,
+    /// Number of quirks applied to a single token at most.
+    pub quirks_max_iterations: Option<usize>,
+    /// Tokens with fewer characters are not checked.
+    pub min_word_length: Option<usize>,
+    /// Accept tokens consisting of two or more uppercase letters, i.e. `HTTP`,
+    /// without consulting the dictionary.
+    pub accept_acronyms: Option<bool>,
+    /// Order of the replacements offered for a mistake.
+    pub ranking: Option<Ranking>,
+    /// Accept the package name and dependency names of the nearest manifest.
+    pub allow_dependency_names: Option<bool>,
+    /// Offer the closest dictionary words by edit distance, if hunspell
+    /// has no suggestions for a mistake.
+    pub fallback_suggestions: Option<bool>,
 }
 
 impl HunspellConfig {
+    pub fn enabled(&self) -> bool {
+        self.enabled.unwrap_or(true)
+    }
+
+    /// The primary language.
     pub fn lang(&self) -> &str {
-        if let Some(ref lang) = self.lang {
-            lang.as_str()
+        self.langs()[0]
+    }
+
+    /// All configured languages, the primary one first, never empty.
+    pub fn langs(&self) -> Vec<&str> {
+        let langs = self
+            .lang
+            .as_ref()
+            .map(|lang| lang.as_slice().iter().map(|lang| lang.as_str()).collect())
+            .unwrap_or_else(Vec::new);
+        if langs.is_empty() {
+            vec!["en_US"]
         } else {
-            "en_US"
+            langs
         }
     }
 
-    pub fn search_dirs(&self) -> &[PathBuf] {
-        if let Some(ref search_dirs) = &self.search_dirs {
-            search_dirs.as_slice()
+    pub fn auto_detect_language(&self) -> bool {
+        self.auto_detect_language.unwrap_or(false)
+    }
+
+    /// OS specific directories commonly containing dictionaries.
+    pub fn default_search_dirs() -> Vec<PathBuf> {
+        let mut search_dirs = if cfg!(target_os = "macos") {
+            directories::BaseDirs::new()
+                .map(|base| vec![base.home_dir().to_owned().join("/Library/Spelling/")])
+                .unwrap_or_else(|| Vec::with_capacity(2))
         } else {
-            lazy_static::lazy_static! {
-                static ref LOOKUP_DIRS: Vec<PathBuf> = vec![PathBuf::from("/usr/share/myspell")];
-            };
+            Vec::with_capacity(2)
+        };
+
+        #[cfg(target_os = "macos")]
+        search_dirs.push(PathBuf::from("/Library/Spelling/"));
 
-            LOOKUP_DIRS.as_slice()
+        #[cfg(target_os = "linux")]
+        search_dirs.extend(vec![
+            // Fedora
+            PathBuf::from("/usr/share/myspell/"),
+            // Arch Linux
+            PathBuf::from("/usr/share/hunspell/"),
+            PathBuf::from("/usr/share/myspell/dicts/"),
+        ]);
+
+        search_dirs
+    }
+
+    pub fn skip_default_search_dirs(&self) -> bool {
+        self.skip_default_search_dirs.unwrap_or(false)
+    }
+
+    /// The configured search dirs, which replace the OS specific defaults.
+    /// Without any configured, the defaults are used unless those are skipped.
+    pub fn search_dirs(&self) -> &[PathBuf] {
+        lazy_static::lazy_static! {
+            static ref DEFAULT_SEARCH_DIRS: Vec<PathBuf> = HunspellConfig::default_search_dirs();
+        };
+
+        match self.search_dirs {
+            Some(ref search_dirs) => search_dirs.as_slice(),
+            None if self.skip_default_search_dirs() => &[],
+            None => DEFAULT_SEARCH_DIRS.as_slice(),
         }
     }
 
-    pub fn extra_dictonaries(&self) -> &[PathBuf] {
+    pub fn extra_dictonaries(&self) -> &[ExtraDictionary] {
         if let Some(ref extra_dictonaries) = self.extra_dictonaries {
             extra_dictonaries.as_slice()
         } else {
@@ -57,6 +143,50 @@ impl HunspellConfig {
         }
     }
 
+    /// The user dictionary, if one is configured or present in the
+    /// default location.
+    pub fn user_dictionary(&self) -> Option<PathBuf> {
+        if let Some(ref user_dictionary) = self.user_dictionary {
+            Some(user_dictionary.clone())
+        } else {
+            Config::default_user_dictionary_path()
+                .ok()
+                .filter(|path| path.is_file())
+        }
+    }
+
+    pub fn quirks(&self) -> Quirks<'_> {
+        let quirks = if let Some(ref quirks) = self.quirks {
+            Quirks::new(quirks.as_slice())
+        } else {
+            Quirks::new(Quirk::DEFAULT)
+        };
+        quirks.with_max_iterations(
+            self.quirks_max_iterations
+                .unwrap_or(Quirks::DEFAULT_MAX_ITERATIONS),
+        )
+    }
+
+    pub fn min_word_length(&self) -> usize {
+        self.min_word_length.unwrap_or(1)
+    }
+
+    pub fn accept_acronyms(&self) -> bool {
+        self.accept_acronyms.unwrap_or(false)
+    }
+
+    pub fn ranking(&self) -> Ranking {
+        self.ranking.unwrap_or_default()
+    }
+
+    pub fn allow_dependency_names(&self) -> bool {
+        self.allow_dependency_names.unwrap_or(false)
+    }
+
+    pub fn fallback_suggestions(&self) -> bool {
+        self.fallback_suggestions.unwrap_or(false)
+    }
+
     pub fn sanitize_paths(&mut self, base: &Path) -> Result<()> {
         if let Some(ref mut search_dirs) = &mut self.search_dirs {
             for path in search_dirs.iter_mut() {
@@ -75,18 +205,492 @@ impl HunspellConfig {
                 *path = abspath;
             }
         }
+        if let Some(ref mut user_dictionary) = &mut self.user_dictionary {
+            if !user_dictionary.is_absolute() {
+                *user_dictionary = base.join(user_dictionary.clone());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How text is split into tokens.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct TokenizationConfig {
+    /// Abbreviations accepted in addition to the built-in ones, i.e. `approx.`.
+    pub extra_abbreviations: Option<Vec<String>>,
+    /// Characters which join words, i.e. in `don't`.
+    pub apostrophes: Option<Vec<char>>,
+}
+
+impl TokenizationConfig {
+    pub fn extra_abbreviations(&self) -> &[String] {
+        if let Some(ref extra_abbreviations) = self.extra_abbreviations {
+            extra_abbreviations.as_slice()
+        } else {
+            &[]
+        }
+    }
+
+    pub fn apostrophes(&self) -> &[char] {
+        if let Some(ref apostrophes) = self.apostrophes {
+            apostrophes.as_slice()
+        } else {
+            Tokenizer::APOSTROPHES
+        }
+    }
+}
+
+/// An attribute, besides `doc`, whose string literal is prose shown to
+/// users, i.e. the message of `#[error("..")]`.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct DocAttribute {
+    /// Path of the attribute, i.e. `error` or `clap`.
+    pub path: String,
+    /// Name of the argument holding the prose, as in `#[clap(about = "..")]`.
+    /// If omitted, the first argument as in `#[error("..")]` or the value
+    /// as in `#[error = ".."]` is used.
+    pub argument: Option<String>,
+}
+
+impl DocAttribute {
+    /// Attributes of the language whose notes are shown to users, checked
+    /// unless `check_attribute_notes` is disabled.
+    pub const BUILTIN: &'static [(&'static str, Option<&'static str>)] = &[
+        ("deprecated", None),
+        ("deprecated", Some("note")),
+        ("must_use", None),
+    ];
+}
+
+/// How rust sources are processed.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct RustConfig {
+    pub extra_doc_attributes: Option<Vec<DocAttribute>>,
+    /// Check the notes of `#[deprecated]` and `#[must_use]`.
+    pub check_attribute_notes: Option<bool>,
+    /// Check the format strings of `println!`, `eprintln!` and the `log` macros.
+    pub check_format_strings: Option<bool>,
+    /// Only check the docs of public items, and the module docs of public
+    /// modules.
+    pub only_public: Option<bool>,
+    /// Consider `pub(crate)` items public, if `only_public` is set.
+    pub include_pub_crate: Option<bool>,
+    /// Join a word split across two doc comment lines by a trailing hyphen.
+    pub join_hyphenated_lines: Option<bool>,
+}
+
+impl RustConfig {
+    pub fn check_format_strings(&self) -> bool {
+        self.check_format_strings.unwrap_or(false)
+    }
+
+    pub fn only_public(&self) -> bool {
+        self.only_public.unwrap_or(false)
+    }
+
+    pub fn include_pub_crate(&self) -> bool {
+        self.include_pub_crate.unwrap_or(false)
+    }
+
+    pub fn join_hyphenated_lines(&self) -> bool {
+        self.join_hyphenated_lines.unwrap_or(false)
+    }
+
+    pub fn extra_doc_attributes(&self) -> &[DocAttribute] {
+        if let Some(ref extra_doc_attributes) = self.extra_doc_attributes {
+            extra_doc_attributes.as_slice()
+        } else {
+            &[]
+        }
+    }
+
+    pub fn check_attribute_notes(&self) -> bool {
+        self.check_attribute_notes.unwrap_or(true)
+    }
+
+    /// All attributes whose string literal is checked, the builtin ones
+    /// followed by the `extra_doc_attributes`.
+    pub fn doc_attributes(&self) -> Vec<DocAttribute> {
+        let builtin = if self.check_attribute_notes() {
+            DocAttribute::BUILTIN
+        } else {
+            &[]
+        };
+        builtin
+            .iter()
+            .map(|(path, argument)| DocAttribute {
+                path: (*path).to_owned(),
+                argument: argument.map(ToOwned::to_owned),
+            })
+            .chain(self.extra_doc_attributes().iter().cloned())
+            .collect()
+    }
+}
+
+/// How markdown, in files and doc comments alike, is reduced to prose.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct MarkdownConfig {
+    /// Check the text of blockquotes, including nested ones.
+    pub check_blockquotes: Option<bool>,
+    /// Check the raw text of a chunk with unbalanced emphasis or code
+    /// markers, rather than the mis-parsed prose.
+    pub raw_fallback: Option<bool>,
+    /// Do not report on doc comments and files without any prose, i.e. a
+    /// doc comment consisting of a fenced code block only.
+    pub skip_code_only: Option<bool>,
+}
+
+impl MarkdownConfig {
+    pub fn check_blockquotes(&self) -> bool {
+        self.check_blockquotes.unwrap_or(true)
+    }
+
+    pub fn raw_fallback(&self) -> bool {
+        self.raw_fallback.unwrap_or(false)
+    }
+
+    pub fn skip_code_only(&self) -> bool {
+        self.skip_code_only.unwrap_or(false)
+    }
+}
+
+/// Plain text files, which are prose as they are, without any markup.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct PlainTextConfig {
+    /// Check `.txt` files, which are skipped unless enabled.
+    pub enabled: Option<bool>,
+}
+
+impl PlainTextConfig {
+    pub fn enabled(&self) -> bool {
+        self.enabled.unwrap_or(false)
+    }
+}
+
+/// Re-wrap doc comment paragraphs with overly long lines.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct ReflowConfig {
+    /// Allows to disable the checker, while retaining its configuration.
+    pub enabled: Option<bool>,
+    /// Maximum number of characters per line, including the indentation
+    /// and the comment prefix.
+    pub max_width: Option<usize>,
+}
+
+impl ReflowConfig {
+    pub const DEFAULT_MAX_WIDTH: usize = 80;
+
+    pub fn enabled(&self) -> bool {
+        self.enabled.unwrap_or(true)
+    }
+
+    pub fn max_width(&self) -> usize {
+        self.max_width.unwrap_or(Self::DEFAULT_MAX_WIDTH)
+    }
+}
+
+/// Flag words which directly follow an identical word, i.e. `the the`.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct RepeatedWordsConfig {
+    /// Allows to disable the checker, while retaining its configuration.
+    pub enabled: Option<bool>,
+    /// Words which are intentionally repeated at times, i.e. `had`.
+    pub allowed: Option<Vec<String>>,
+}
+
+impl RepeatedWordsConfig {
+    pub fn enabled(&self) -> bool {
+        self.enabled.unwrap_or(true)
+    }
+
+    pub fn allowed(&self) -> &[String] {
+        if let Some(ref allowed) = self.allowed {
+            allowed.as_slice()
+        } else {
+            &[]
+        }
+    }
+}
+
+/// A command which is fed the text to check via stdin and reports
+/// mistakes via stdout, see `checker::external` for the protocol.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ExternalConfig {
+    /// Allows to disable the checker, while retaining its configuration.
+    pub enabled: Option<bool>,
+    /// The executable, looked up in `PATH` unless it is a path.
+    pub command: String,
+    pub args: Option<Vec<String>>,
+}
+
+impl ExternalConfig {
+    pub fn enabled(&self) -> bool {
+        self.enabled.unwrap_or(true)
+    }
+
+    pub fn args(&self) -> &[String] {
+        if let Some(ref args) = self.args {
+            args.as_slice()
+        } else {
+            &[]
+        }
+    }
+}
+
+/// Prose of the `Cargo.toml` manifests, as displayed on crates.io.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct ManifestConfig {
+    /// Check the `description` of the `[package]` table.
+    pub check_description: Option<bool>,
+    /// Check the `keywords` of the `[package]` table.
+    pub check_keywords: Option<bool>,
+}
+
+impl ManifestConfig {
+    pub fn check_description(&self) -> bool {
+        self.check_description.unwrap_or(false)
+    }
+
+    pub fn check_keywords(&self) -> bool {
+        self.check_keywords.unwrap_or(false)
+    }
+}
+
+/// Content to check in addition to what is found by traversal.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct ExtraConfig {
+    /// Files or directories, i.e. generated documentation, which are
+    /// always checked, without following any module declarations.
+    pub include_paths: Option<Vec<PathBuf>>,
+}
+
+impl ExtraConfig {
+    pub fn include_paths(&self) -> &[PathBuf] {
+        if let Some(ref include_paths) = self.include_paths {
+            include_paths.as_slice()
+        } else {
+            &[]
+        }
+    }
+
+    pub fn sanitize_paths(&mut self, base: &Path) -> Result<()> {
+        if let Some(ref mut include_paths) = &mut self.include_paths {
+            for path in include_paths.iter_mut() {
+                if !path.is_absolute() {
+                    *path = base.join(path.clone());
+                }
+            }
+        }
         Ok(())
     }
 }
 
+/// An extra dictionary, either a plain word list sharing the affixes of
+/// the language dictionary, or a `.dic` with an `.aff` of its own.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum ExtraDictionary {
+    Plain(PathBuf),
+    WithAffixes { dic: PathBuf, aff: PathBuf },
+}
+
+impl ExtraDictionary {
+    pub fn dic(&self) -> &Path {
+        match self {
+            Self::Plain(dic) => dic.as_path(),
+            Self::WithAffixes { dic, .. } => dic.as_path(),
+        }
+    }
+
+    /// The affixes, if the dictionary does not share the ones of the
+    /// language dictionary.
+    pub fn aff(&self) -> Option<&Path> {
+        match self {
+            Self::Plain(_) => None,
+            Self::WithAffixes { aff, .. } => Some(aff.as_path()),
+        }
+    }
+}
+
+/// Either a single language or a list of languages.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum Langs {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+impl Langs {
+    pub fn as_slice(&self) -> &[String] {
+        match self {
+            Self::Single(lang) => std::slice::from_ref(lang),
+            Self::Multiple(langs) => langs.as_slice(),
+        }
+    }
+}
+
+/// Either a single url or a list of urls.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum Urls {
+    Single(url::Url),
+    Multiple(Vec<url::Url>),
+}
+
+impl Urls {
+    pub fn as_slice(&self) -> &[url::Url] {
+        match self {
+            Self::Single(url) => std::slice::from_ref(url),
+            Self::Multiple(urls) => urls.as_slice(),
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct LanguageToolConfig {
-    pub url: url::Url,
+    /// Allows to disable the checker, while retaining its configuration.
+    pub enabled: Option<bool>,
+    /// Endpoints are tried in order, until one of them responds.
+    pub url: Urls,
+    /// Abort the run if none of the endpoints can be reached, instead of
+    /// continuing without LanguageTool suggestions.
+    pub fail_on_unreachable: Option<bool>,
 }
 
 impl LanguageToolConfig {
-    pub fn url(&self) -> &url::Url {
-        &self.url
+    pub fn enabled(&self) -> bool {
+        self.enabled.unwrap_or(true)
+    }
+
+    pub fn urls(&self) -> &[url::Url] {
+        self.url.as_slice()
+    }
+
+    pub fn fail_on_unreachable(&self) -> bool {
+        self.fail_on_unreachable.unwrap_or(false)
+    }
+}
+
+/// Captures the names of the fields of a struct, as its derived
+/// `Deserialize` implementation passes them on, without deserializing
+/// anything.
+struct FieldNames<'f>(&'f mut &'static [&'static str]);
+
+impl<'de, 'f> serde::Deserializer<'de> for FieldNames<'f> {
+    type Error = serde::de::value::Error;
+
+    fn deserialize_any<V: serde::de::Visitor<'de>>(
+        self,
+        _visitor: V,
+    ) -> std::result::Result<V::Value, Self::Error> {
+        Err(serde::de::Error::custom("Not a struct"))
+    }
+
+    fn deserialize_struct<V: serde::de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> std::result::Result<V::Value, Self::Error> {
+        *self.0 = fields;
+        Err(serde::de::Error::custom("Captured the field names"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map enum
+        identifier ignored_any
+    }
+}
+
+/// The names of the fields of the struct `T`.
+fn field_names<T: serde::de::DeserializeOwned>() -> &'static [&'static str] {
+    let mut fields: &'static [&'static str] = &[];
+    let _ = T::deserialize(FieldNames(&mut fields));
+    fields
+}
+
+/// All keys which are recognized in the table named `name`, where the top
+/// level table has an empty name.
+fn known_keys(name: &str) -> &'static [&'static str] {
+    match name {
+        "" => field_names::<Config>(),
+        "hunspell" => field_names::<HunspellConfig>(),
+        "languagetool" => field_names::<LanguageToolConfig>(),
+        "extra" => field_names::<ExtraConfig>(),
+        "tokenization" => field_names::<TokenizationConfig>(),
+        "reflow" => field_names::<ReflowConfig>(),
+        "manifest" => field_names::<ManifestConfig>(),
+        "external" => field_names::<ExternalConfig>(),
+        "repeated_words" => field_names::<RepeatedWordsConfig>(),
+        "rust" => field_names::<RustConfig>(),
+        "markdown" => field_names::<MarkdownConfig>(),
+        "plain_text" => field_names::<PlainTextConfig>(),
+        _ => &[],
+    }
+}
+
+/// Keys of `table` which are not known for the table named `name`,
+/// as dotted paths.
+fn unknown_keys(name: &str, table: &toml::value::Table) -> Vec<String> {
+    let known = known_keys(name);
+    let mut unknown = Vec::new();
+    for (key, value) in table.iter() {
+        let path = if name.is_empty() {
+            key.to_owned()
+        } else {
+            format!("{}.{}", name, key)
+        };
+        if !known.contains(&key.as_str()) {
+            unknown.push(path);
+        } else if let toml::Value::Table(ref inner) = value {
+            // only the top level table contains tables
+            if name.is_empty() {
+                unknown.extend(unknown_keys(key, inner));
+            }
+        }
+    }
+    unknown
+}
+
+/// The format of a configuration file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// The format implied by the extension of `path`, toml unless it is
+    /// `.yaml` or `.yml`.
+    pub fn of(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("yaml") | Some("yml") => Self::Yaml,
+            _ => Self::Toml,
+        }
+    }
+}
+
+/// The tables of a yaml `value` as toml, to look for unknown keys.
+///
+/// Only the keys are of interest, so all other values are replaced, which
+/// also covers `null`, which toml lacks.
+fn yaml_tables(value: &serde_yaml::Value) -> toml::Value {
+    match value {
+        serde_yaml::Value::Mapping(mapping) => toml::Value::Table(
+            mapping
+                .iter()
+                .map(|(key, value)| {
+                    let key = key
+                        .as_str()
+                        .map(|key| key.to_owned())
+                        .unwrap_or_else(|| format!("{:?}", key));
+                    (key, yaml_tables(value))
+                })
+                .collect(),
+        ),
+        _ => toml::Value::Boolean(false),
     }
 }
 
@@ -101,15 +705,69 @@ impl Config {
         if let Some(ref mut hunspell) = self.hunspell {
             hunspell.sanitize_paths(base)?;
         }
+        if let Some(ref mut extra) = self.extra {
+            extra.sanitize_paths(base)?;
+        }
         Ok(())
     }
 
+    /// Fail if `s` contains keys which are not recognized, and would be
+    /// silently ignored otherwise, i.e. due to a typo.
+    pub fn check_keys<S: AsRef<str>>(s: S) -> Result<()> {
+        Self::check_keys_as(s, ConfigFormat::Toml)
+    }
+
+    /// Like `check_keys`, for a configuration in the given `format`.
+    pub fn check_keys_as<S: AsRef<str>>(s: S, format: ConfigFormat) -> Result<()> {
+        let value: toml::Value = match format {
+            ConfigFormat::Toml => {
+                toml::from_str(s.as_ref()).map_err(|e| anyhow!("Failed parse toml").context(e))?
+            }
+            ConfigFormat::Yaml => {
+                let value: serde_yaml::Value = serde_yaml::from_str(s.as_ref())
+                    .map_err(|e| anyhow!("Failed parse yaml").context(e))?;
+                yaml_tables(&value)
+            }
+        };
+        let unknown = if let toml::Value::Table(ref table) = value {
+            unknown_keys("", table)
+        } else {
+            Vec::new()
+        };
+        if !unknown.is_empty() {
+            bail!("Unknown configuration keys: {}", unknown.join(", "))
+        }
+        Ok(())
+    }
+
+    /// Like `load_from`, but fails on unknown keys, see `check_keys`.
+    pub fn load_from_strict<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let contents = std::fs::read_to_string(path.as_ref()).map_err(|e| {
+            anyhow!("Failed to read from file {}", path.as_ref().display()).context(e)
+        })?;
+        Self::check_keys_as(&contents, ConfigFormat::of(path.as_ref()))
+            .map_err(|e| e.context(anyhow!("Invalid config {}", path.as_ref().display())))?;
+        Self::load_from(path)
+    }
+
     pub fn parse<S: AsRef<str>>(s: S) -> Result<Self> {
-        let cfg =
-            toml::from_str(s.as_ref()).map_err(|e| anyhow!("Failed parse toml").context(e))?;
+        Self::parse_as(s, ConfigFormat::Toml)
+    }
+
+    /// Parse a configuration in the given `format`.
+    pub fn parse_as<S: AsRef<str>>(s: S, format: ConfigFormat) -> Result<Self> {
+        let cfg = match format {
+            ConfigFormat::Toml => {
+                toml::from_str(s.as_ref()).map_err(|e| anyhow!("Failed parse toml").context(e))?
+            }
+            ConfigFormat::Yaml => serde_yaml::from_str(s.as_ref())
+                .map_err(|e| anyhow!("Failed parse yaml").context(e))?,
+        };
         Ok(cfg)
     }
 
+    /// Load the configuration file at `path`, which is parsed as yaml if
+    /// its extension is `.yaml` or `.yml` and as toml otherwise.
     pub fn load_from<P: AsRef<Path>>(path: P) -> Result<Self> {
         let mut file = File::open(path.as_ref().to_str().unwrap())
             .map_err(|e| anyhow!("Failed to open file {}", path.as_ref().display()).context(e))?;
@@ -117,7 +775,7 @@ impl Config {
         file.read_to_string(&mut contents).map_err(|e| {
             anyhow!("Failed to read from file {}", path.as_ref().display()).context(e)
         })?;
-        Self::parse(&contents).and_then(|mut cfg| {
+        Self::parse_as(&contents, ConfigFormat::of(path.as_ref())).and_then(|mut cfg| {
             if let Some(base) = path.as_ref().parent() {
                 cfg.sanitize_paths(base)?;
             }
@@ -141,9 +799,23 @@ impl Config {
         toml::to_string(self).map_err(|e| anyhow!("Failed to convert to toml").context(e))
     }
 
+    pub fn to_yaml(&self) -> Result<String> {
+        serde_yaml::to_string(self).map_err(|e| anyhow!("Failed to convert to yaml").context(e))
+    }
+
+    /// Render the configuration in the given `format`.
+    pub fn to_format(&self, format: ConfigFormat) -> Result<String> {
+        match format {
+            ConfigFormat::Toml => self.to_toml(),
+            ConfigFormat::Yaml => self.to_yaml(),
+        }
+    }
+
+    /// Write the configuration to `path`, in the format its extension
+    /// implies, see `load_from`.
     pub fn write_values_to_path<P: AsRef<Path>>(&self, path: P) -> Result<Self> {
-        let s = self.to_toml()?;
         let path = path.as_ref();
+        let s = self.to_format(ConfigFormat::of(path))?;
 
         if let Some(path) = path.parent() {
             std::fs::create_dir_all(path).map_err(|e| {
@@ -187,6 +859,15 @@ impl Config {
         }
     }
 
+    /// Location of the user dictionary, next to the default config file.
+    pub fn default_user_dictionary_path() -> Result<PathBuf> {
+        let path = Self::default_path()?;
+        Ok(path
+            .parent()
+            .map(|dir| dir.join("user.dic"))
+            .unwrap_or_else(|| PathBuf::from("user.dic")))
+    }
+
     pub fn write_default_values() -> Result<Self> {
         let d = Self::default_path()?;
         Self::write_default_values_to(d.join("config.toml"))
@@ -194,16 +875,77 @@ impl Config {
 
     pub fn is_enabled(&self, detector: Detector) -> bool {
         match detector {
-            Detector::Hunspell => self.hunspell.is_some(),
-            Detector::LanguageTool => self.languagetool.is_some(),
+            Detector::Hunspell => self
+                .hunspell
+                .as_ref()
+                .map(|hunspell| hunspell.enabled())
+                .unwrap_or(false),
+            Detector::LanguageTool => self
+                .languagetool
+                .as_ref()
+                .map(|languagetool| languagetool.enabled())
+                .unwrap_or(false),
+            Detector::Reflow => self
+                .reflow
+                .as_ref()
+                .map(|reflow| reflow.enabled())
+                .unwrap_or(false),
+            Detector::External => self
+                .external
+                .as_ref()
+                .map(|external| external.enabled())
+                .unwrap_or(false),
+            Detector::RepeatedWord => self
+                .repeated_words
+                .as_ref()
+                .map(|repeated_words| repeated_words.enabled())
+                .unwrap_or(false),
             #[cfg(test)]
             Detector::Dummy => true,
         }
     }
 
+    /// How rust sources are processed, the defaults if not configured.
+    pub fn rust(&self) -> RustConfig {
+        self.rust.clone().unwrap_or_default()
+    }
+
+    /// How markdown is reduced to prose, the defaults if not configured.
+    pub fn markdown(&self) -> MarkdownConfig {
+        self.markdown.clone().unwrap_or_default()
+    }
+
+    /// Whether plain text files are checked, which is off unless enabled.
+    pub fn check_plain_text(&self) -> bool {
+        self.plain_text
+            .as_ref()
+            .map(|plain_text| plain_text.enabled())
+            .unwrap_or(false)
+    }
+
+    /// The tokenizer as configured, following the rules of the primary
+    /// hunspell language.
+    pub fn tokenizer(&self) -> Tokenizer {
+        let tokenizer = if let Some(ref tokenization) = self.tokenization {
+            Tokenizer::new(tokenization.extra_abbreviations())
+                .with_apostrophes(tokenization.apostrophes())
+        } else {
+            Tokenizer::default()
+        };
+        if let Some(ref hunspell) = self.hunspell {
+            tokenizer.with_language(hunspell.lang())
+        } else {
+            tokenizer
+        }
+    }
+
     pub fn full() -> Self {
         let languagetool = LanguageToolConfig {
-            url: url::Url::parse("http://127.0.0.1:8010").expect("Default ip must be ok"),
+            enabled: Some(true),
+            url: Urls::Single(
+                url::Url::parse("http://127.0.0.1:8010").expect("Default ip must be ok"),
+            ),
+            fail_on_unreachable: Some(false),
         };
         Self {
             languagetool: Some(languagetool),
@@ -214,33 +956,35 @@ impl Config {
 
 impl Default for Config {
     fn default() -> Self {
-        let mut search_dirs = if cfg!(target_os = "macos") {
-            directories::BaseDirs::new()
-                .map(|base| vec![base.home_dir().to_owned().join("/Library/Spelling/")])
-                .unwrap_or_else(|| Vec::with_capacity(2))
-        } else {
-            Vec::with_capacity(2)
-        };
-
-        #[cfg(target_os = "macos")]
-        search_dirs.push(PathBuf::from("/Library/Spelling/"));
-
-        #[cfg(target_os = "linux")]
-        search_dirs.extend(vec![
-            // Fedora
-            PathBuf::from("/usr/share/myspell/"),
-            // Arch Linux
-            PathBuf::from("/usr/share/hunspell/"),
-            PathBuf::from("/usr/share/myspell/dicts/"),
-        ]);
+        let search_dirs = HunspellConfig::default_search_dirs();
 
         Self {
             hunspell: Some(HunspellConfig {
-                lang: Some("en_US".to_owned()),
+                enabled: Some(true),
+                lang: Some(Langs::Single("en_US".to_owned())),
+                auto_detect_language: Some(false),
                 search_dirs: Some(search_dirs),
+                skip_default_search_dirs: Some(false),
                 extra_dictonaries: Some(Vec::new()),
+                user_dictionary: None,
+                quirks: Some(Quirk::DEFAULT.to_vec()),
+                quirks_max_iterations: Some(Quirks::DEFAULT_MAX_ITERATIONS),
+                min_word_length: Some(1),
+                accept_acronyms: Some(false),
+                ranking: Some(Ranking::Checker),
+                allow_dependency_names: Some(false),
+                fallback_suggestions: Some(false),
             }),
             languagetool: None,
+            extra: None,
+            tokenization: None,
+            reflow: None,
+            manifest: None,
+            external: None,
+            repeated_words: None,
+            rust: None,
+            markdown: None,
+            plain_text: None,
         }
     }
 }
@@ -276,6 +1020,498 @@ extra_dictonaries = ["/home/bernhard/test.dic"]
         .unwrap();
     }
 
+    #[test]
+    fn enabled() {
+        let cfg = Config::parse(
+            r#"
+[hunspell]
+enabled = false
+lang = "en_US"
+
+[languagetool]
+url = "http://127.0.0.1:8010/"
+			"#,
+        )
+        .unwrap();
+        assert!(!cfg.is_enabled(Detector::Hunspell));
+        assert!(cfg.is_enabled(Detector::LanguageTool));
+        assert!(cfg.hunspell.is_some());
+        assert!(!cfg.languagetool.as_ref().unwrap().fail_on_unreachable());
+    }
+
+    #[test]
+    fn user_dictionary() {
+        let mut cfg = Config::parse(
+            r#"
+[hunspell]
+user_dictionary = "personal.dic"
+			"#,
+        )
+        .unwrap();
+        cfg.sanitize_paths(Path::new("/home/bernhard/.config"))
+            .unwrap();
+        assert_eq!(
+            cfg.hunspell.unwrap().user_dictionary(),
+            Some(PathBuf::from("/home/bernhard/.config/personal.dic"))
+        );
+        assert!(Config::default_user_dictionary_path()
+            .unwrap()
+            .ends_with("user.dic"));
+    }
+
+    #[test]
+    fn extra_dictionaries() {
+        let cfg = Config::parse(
+            r#"
+[hunspell]
+extra_dictonaries = ["/opt/words.dic", { dic = "/opt/compounds.dic", aff = "/opt/compounds.aff" }]
+			"#,
+        )
+        .unwrap();
+        let hunspell = cfg.hunspell.unwrap();
+        let extra = hunspell.extra_dictonaries();
+        assert_eq!(extra.len(), 2);
+        assert_eq!(extra[0].dic(), Path::new("/opt/words.dic"));
+        assert_eq!(extra[0].aff(), None);
+        assert_eq!(extra[1].dic(), Path::new("/opt/compounds.dic"));
+        assert_eq!(extra[1].aff(), Some(Path::new("/opt/compounds.aff")));
+    }
+
+    #[test]
+    fn skip_default_search_dirs() {
+        let cfg = Config::parse(
+            r#"
+[hunspell]
+search_dirs = ["/opt/dictionaries"]
+			"#,
+        )
+        .unwrap();
+        let hunspell = cfg.hunspell.unwrap();
+        // configured search dirs replace the defaults
+        assert_eq!(
+            hunspell.search_dirs(),
+            &[PathBuf::from("/opt/dictionaries")]
+        );
+
+        let hunspell = HunspellConfig {
+            search_dirs: None,
+            ..hunspell
+        };
+        assert_eq!(
+            hunspell.search_dirs(),
+            HunspellConfig::default_search_dirs().as_slice()
+        );
+        let hunspell = HunspellConfig {
+            skip_default_search_dirs: Some(true),
+            ..hunspell
+        };
+        assert!(hunspell.search_dirs().is_empty());
+    }
+
+    #[test]
+    fn include_paths() {
+        let mut cfg = Config::parse(
+            r#"
+[extra]
+include_paths = ["target/generated", "/tmp/docs.md"]
+			"#,
+        )
+        .unwrap();
+        cfg.sanitize_paths(Path::new("/home/bernhard/project"))
+            .unwrap();
+        assert_eq!(
+            cfg.extra.unwrap().include_paths(),
+            &[
+                PathBuf::from("/home/bernhard/project/target/generated"),
+                PathBuf::from("/tmp/docs.md")
+            ]
+        );
+    }
+
+    #[test]
+    fn langs() {
+        let single = Config::parse("[hunspell]\nlang = \"de_DE\"\n").unwrap();
+        let single = single.hunspell.unwrap();
+        assert_eq!(single.langs(), vec!["de_DE"]);
+        assert!(!single.auto_detect_language());
+
+        let multiple = Config::parse(
+            r#"
+[hunspell]
+lang = ["en_US", "de_DE"]
+auto_detect_language = true
+			"#,
+        )
+        .unwrap();
+        let multiple = multiple.hunspell.unwrap();
+        assert_eq!(multiple.lang(), "en_US");
+        assert_eq!(multiple.langs(), vec!["en_US", "de_DE"]);
+        assert!(multiple.auto_detect_language());
+
+        let empty = Config::parse("[hunspell]\nlang = []\n").unwrap();
+        assert_eq!(empty.hunspell.unwrap().lang(), "en_US");
+    }
+
+    #[test]
+    fn apostrophes() {
+        let cfg = Config::parse("[tokenization]\napostrophes = [\"'\"]\n").unwrap();
+        let text = "can’t";
+        let tokens = cfg.tokenizer().tokenize(text);
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(crate::util::sub_chars(text, tokens[0].clone()), "can");
+
+        let cfg = Config::parse("[tokenization]\n").unwrap();
+        assert_eq!(
+            cfg.tokenization.unwrap().apostrophes(),
+            Tokenizer::APOSTROPHES
+        );
+
+        // elisions follow the primary language
+        let cfg = Config::parse("[hunspell]\nlang = [\"fr_FR\", \"en_US\"]\n").unwrap();
+        assert_eq!(cfg.tokenizer().tokenize("l'eau"), vec![0..1, 2..5]);
+    }
+
+    #[test]
+    fn markdown() {
+        assert!(Config::default().markdown().check_blockquotes());
+        let cfg = Config::parse(
+            r#"
+[markdown]
+check_blockquotes = false
+			"#,
+        )
+        .unwrap();
+        assert!(!cfg.markdown().check_blockquotes());
+        assert!(!cfg.markdown().skip_code_only());
+    }
+
+    #[test]
+    fn plain_text() {
+        assert!(!Config::default().check_plain_text());
+        let cfg = Config::parse("[plain_text]\nenabled = true\n").unwrap();
+        assert!(cfg.check_plain_text());
+    }
+
+    #[test]
+    fn doc_attributes() {
+        let cfg = Config::parse(
+            r#"
+[rust]
+extra_doc_attributes = [{ path = "error" }, { path = "clap", argument = "about" }]
+check_format_strings = true
+only_public = true
+			"#,
+        )
+        .unwrap();
+        assert!(cfg.rust().check_format_strings());
+        assert!(cfg.rust().only_public());
+        assert!(!cfg.rust().include_pub_crate());
+        assert_eq!(
+            cfg.rust().extra_doc_attributes(),
+            &[
+                DocAttribute {
+                    path: "error".to_owned(),
+                    argument: None,
+                },
+                DocAttribute {
+                    path: "clap".to_owned(),
+                    argument: Some("about".to_owned()),
+                },
+            ][..]
+        );
+        assert!(Config::default().rust().extra_doc_attributes().is_empty());
+        assert!(!Config::default().rust().check_format_strings());
+        assert!(!Config::default().rust().only_public());
+    }
+
+    #[test]
+    fn languagetool_urls() {
+        let single = Config::parse(
+            r#"
+[languagetool]
+url = "http://127.0.0.1:8010/"
+			"#,
+        )
+        .unwrap();
+        let single = single.languagetool.unwrap();
+        assert_eq!(single.urls().len(), 1);
+
+        let multiple = Config::parse(
+            r#"
+[languagetool]
+url = ["http://127.0.0.1:8010/", "http://10.0.0.1:8081/"]
+			"#,
+        )
+        .unwrap();
+        let languagetool = multiple.languagetool.as_ref().unwrap();
+        assert_eq!(
+            languagetool
+                .urls()
+                .iter()
+                .map(|url| url.as_str())
+                .collect::<Vec<_>>(),
+            vec!["http://127.0.0.1:8010/", "http://10.0.0.1:8081/"]
+        );
+
+        // both forms survive a roundtrip
+        let serialized = multiple.to_toml().unwrap();
+        assert!(serialized.contains("url = [\"http://127.0.0.1:8010/\""));
+        assert_eq!(
+            Config::parse(serialized).unwrap().languagetool.unwrap().url,
+            languagetool.url
+        );
+        let serialized = Config::full().to_toml().unwrap();
+        assert!(serialized.contains("url = \"http://127.0.0.1:8010/\""));
+    }
+
+    #[test]
+    fn quirks() {
+        let cfg = Config::parse(
+            r#"
+[hunspell]
+quirks = ["possessive"]
+			"#,
+        )
+        .unwrap();
+        assert_eq!(cfg.hunspell.unwrap().quirks, Some(vec![Quirk::Possessive]));
+
+        assert!(Config::parse(
+            r#"
+[hunspell]
+quirks = ["unheard-of"]
+			"#,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn min_word_length() {
+        let cfg = Config::parse(
+            r#"
+[hunspell]
+min_word_length = 3
+			"#,
+        )
+        .unwrap();
+        assert_eq!(cfg.hunspell.unwrap().min_word_length(), 3);
+
+        let cfg = Config::parse(
+            r#"
+[hunspell]
+lang = "en_US"
+			"#,
+        )
+        .unwrap();
+        assert_eq!(cfg.hunspell.unwrap().min_word_length(), 1);
+    }
+
+    #[test]
+    fn yaml() {
+        const TOML: &str = r#"
+[hunspell]
+lang = ["en_US", "de_DE"]
+search_dirs = ["/opt/dictionaries"]
+extra_dictonaries = ["/opt/project.dic", { dic = "/opt/terms.dic", aff = "/opt/terms.aff" }]
+quirks = ["single-quoted"]
+min_word_length = 3
+ranking = "similarity"
+fallback_suggestions = true
+
+[languagetool]
+url = ["http://127.0.0.1:8010", "https://languagetool.example.com"]
+
+[tokenization]
+extra_abbreviations = ["approx."]
+apostrophes = ["'"]
+
+[reflow]
+max_width = 100
+
+[repeated_words]
+allowed = ["had"]
+
+[external]
+command = "my-spell-tool"
+args = ["--json"]
+
+[manifest]
+check_description = true
+
+[rust]
+extra_doc_attributes = [{ path = "error" }, { path = "clap", argument = "about" }]
+only_public = true
+
+[markdown]
+check_blockquotes = false
+
+[extra]
+include_paths = ["/opt/docs.md"]
+"#;
+        // compared as json, since toml fails to serialize affixed extra dictionaries
+        let json = |cfg: &Config| serde_json::to_string(cfg).unwrap();
+        let cfg = Config::parse(TOML).unwrap();
+        let yaml = cfg.to_yaml().unwrap();
+        let from_yaml = Config::parse_as(&yaml, ConfigFormat::Yaml).unwrap();
+        assert_eq!(json(&from_yaml), json(&cfg));
+        assert_eq!(from_yaml.to_yaml().unwrap(), yaml);
+        Config::check_keys_as(&yaml, ConfigFormat::Yaml).unwrap();
+
+        let full = Config::full();
+        let yaml = full.to_format(ConfigFormat::Yaml).unwrap();
+        let from_yaml = Config::parse_as(&yaml, ConfigFormat::Yaml).unwrap();
+        assert_eq!(json(&from_yaml), json(&full));
+        Config::check_keys_as(&yaml, ConfigFormat::Yaml).unwrap();
+
+        let e =
+            Config::check_keys_as("hunspell:\n  lnag: en_US\n", ConfigFormat::Yaml).unwrap_err();
+        assert!(e.to_string().contains("hunspell.lnag"));
+
+        assert_eq!(
+            ConfigFormat::of(Path::new("spellcheck.yml")),
+            ConfigFormat::Yaml
+        );
+        assert_eq!(
+            ConfigFormat::of(Path::new("spellcheck.yaml")),
+            ConfigFormat::Yaml
+        );
+        assert_eq!(
+            ConfigFormat::of(Path::new("spellcheck.toml")),
+            ConfigFormat::Toml
+        );
+        assert_eq!(
+            ConfigFormat::of(Path::new("spellcheck")),
+            ConfigFormat::Toml
+        );
+
+        let path =
+            std::env::temp_dir().join(format!("cargo-spellcheck-{}.yaml", std::process::id()));
+        // search dirs must exist when loaded
+        let mut cfg = cfg;
+        cfg.hunspell.as_mut().unwrap().search_dirs = None;
+        cfg.write_values_to_path(&path).unwrap();
+        let written = std::fs::read_to_string(&path);
+        let loaded = Config::load_from_strict(&path);
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(written.unwrap(), cfg.to_yaml().unwrap());
+        assert_eq!(json(&loaded.unwrap()), json(&cfg));
+    }
+
+    #[test]
+    fn accept_acronyms() {
+        let cfg = Config::parse(
+            r#"
+[hunspell]
+accept_acronyms = true
+			"#,
+        )
+        .unwrap();
+        assert!(cfg.hunspell.unwrap().accept_acronyms());
+
+        // misspelled acronyms are reported unless asked otherwise
+        assert!(!Config::default().hunspell.unwrap().accept_acronyms());
+    }
+
+    #[test]
+    fn ranking() {
+        let cfg = Config::parse(
+            r#"
+[hunspell]
+ranking = "similarity"
+			"#,
+        )
+        .unwrap();
+        assert_eq!(cfg.hunspell.unwrap().ranking(), Ranking::Similarity);
+
+        assert!(Config::parse(
+            r#"
+[hunspell]
+ranking = "alphabetical"
+			"#,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn reflow() {
+        assert!(!Config::default().is_enabled(Detector::Reflow));
+
+        let cfg = Config::parse(
+            r#"
+[reflow]
+max_width = 100
+			"#,
+        )
+        .unwrap();
+        assert!(cfg.is_enabled(Detector::Reflow));
+        assert_eq!(cfg.reflow.unwrap().max_width(), 100);
+    }
+
+    #[test]
+    fn strict() {
+        assert!(Config::check_keys(
+            r#"
+[hunspell]
+lang = "en_US"
+quirks = ["possessive"]
+
+[languagetool]
+url = ["http://127.0.0.1:8010"]
+			"#,
+        )
+        .is_ok());
+
+        let e = Config::check_keys(
+            r#"
+colour = true
+
+[hunspell]
+lnag = "en_US"
+
+[Hunspell]
+lang = "en_US"
+			"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "Unknown configuration keys: Hunspell, colour, hunspell.lnag"
+        );
+
+        // the default configuration is always valid
+        Config::check_keys(Config::full().to_toml().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn known_keys_of_all_tables() {
+        assert!(field_names::<Config>().contains(&"hunspell"));
+        for table in field_names::<Config>() {
+            assert!(
+                !known_keys(table).is_empty(),
+                "Keys of table `{}` are not known",
+                table
+            );
+        }
+        assert_eq!(
+            known_keys("markdown"),
+            &["check_blockquotes", "raw_fallback", "skip_code_only"]
+        );
+    }
+
+    #[test]
+    fn repeated_words() {
+        assert!(!Config::default().is_enabled(Detector::RepeatedWord));
+
+        let cfg = Config::parse(
+            r#"
+[repeated_words]
+allowed = ["had"]
+			"#,
+        )
+        .unwrap();
+        assert!(cfg.is_enabled(Detector::RepeatedWord));
+        assert_eq!(cfg.repeated_words.unwrap().allowed(), &["had".to_owned()]);
+    }
+
     #[test]
     fn empty() {
         let _ = Config::parse(
@@ -359,4 +1595,19 @@ lang = "en_US"
         )
         .unwrap();
     }
+
+    #[test]
+    fn manifest() {
+        let cfg = Config::parse(
+            r#"
+[manifest]
+check_description = true
+			"#,
+        )
+        .unwrap();
+        let manifest = cfg.manifest.expect("Must contain manifest config");
+        assert!(manifest.check_description());
+        assert!(!manifest.check_keywords());
+        assert!(Config::default().manifest.is_none());
+    }
 }
@@ -0,0 +1,287 @@
+//! Erase AsciiDoc syntax
+//!
+//! Only the prose of an AsciiDoc document is retained, every fragment
+//! of it is mapped back to the `Span` it originates from, so suggestions
+//! can be applied to the file.
+//!
+//! Source, listing, literal, passthrough and comment blocks are skipped
+//! entirely, as are attribute entries and block macros.
+
+use super::*;
+
+use fancy_regex::Regex;
+
+use crate::documentation::chunk::{inline_prose, match_end, ChunkBuilder};
+
+lazy_static::lazy_static! {
+    static ref HEADING: Regex = Regex::new(r"^(?:=|#){1,6}\s+").expect("HEADING regex compiles");
+    static ref LIST_ITEM: Regex = Regex::new(r"^\s*(?:\*+|-|\.+|\d+\.|[a-zA-Z]\.|<\d+>)\s+(?:\[[ x*]\]\s+)?").expect("LIST_ITEM regex compiles");
+    static ref ADMONITION: Regex = Regex::new(r"^(?:NOTE|TIP|IMPORTANT|WARNING|CAUTION):\s+").expect("ADMONITION regex compiles");
+    static ref BLOCK_TITLE: Regex = Regex::new(r"^\.(?=[^\s.])").expect("BLOCK_TITLE regex compiles");
+    static ref ATTRIBUTE_ENTRY: Regex = Regex::new(r"^:!?[^:\s]+!?:(?:\s|$)").expect("ATTRIBUTE_ENTRY regex compiles");
+    static ref BLOCK_ATTRIBUTES: Regex = Regex::new(r"^\[.*\]\s*$").expect("BLOCK_ATTRIBUTES regex compiles");
+    static ref VERBATIM_ATTRIBUTES: Regex = Regex::new(r"^\[(?:source|listing|literal|pass|stem|latexmath|asciimath)(?:[,#.%].*)?\]\s*$").expect("VERBATIM_ATTRIBUTES regex compiles");
+    static ref BLOCK_MACRO: Regex = Regex::new(r"^[a-z]+::\S*\[.*\]\s*$").expect("BLOCK_MACRO regex compiles");
+
+    /// Inline elements, paired with the capture group which is prose and must be retained.
+    static ref INLINE: Vec<(Regex, Option<usize>)> = inline();
+    /// Inline elements within tables, where cell separators are not prose either.
+    static ref INLINE_TABLE: Vec<(Regex, Option<usize>)> = {
+        let mut inline = inline();
+        inline.push((Regex::new(r"\|").expect("CELL regex compiles"), None));
+        inline
+    };
+}
+
+fn inline() -> Vec<(Regex, Option<usize>)> {
+    vec![
+        // monospace and passthrough
+        (Regex::new(r"`[^`]+`").expect("MONOSPACE regex compiles"), None),
+        (Regex::new(r"\+\+?[^+\s][^+]*\+\+?").expect("PASSTHROUGH regex compiles"), None),
+        // attribute references
+        (Regex::new(r"\{[\w-]+\}").expect("ATTRIBUTE_REFERENCE regex compiles"), None),
+        // anchors and roles
+        (Regex::new(r"\[\[[^\]]*\]\]").expect("ANCHOR regex compiles"), None),
+        (Regex::new(r"\[[#.][^\]]*\](?=[#*_`])").expect("ROLE regex compiles"), None),
+        // cross references with optional text
+        (Regex::new(r"<<[^,>]+(?:,\s*([^>]+))?>>").expect("XREF regex compiles"), Some(1)),
+        // macros without prose
+        (Regex::new(r"\b(?:kbd|btn|menu|pass|stem|latexmath|asciimath|indexterm|image|icon):\S*?\[[^\]]*\]").expect("CODE_MACRO regex compiles"), None),
+        // macros with prose
+        (Regex::new(r"\b(?:link|xref|mailto|footnote|footnoteref|anchor):\S*?\[([^\]]*)\]").expect("TEXT_MACRO regex compiles"), Some(1)),
+        // urls with optional text
+        (Regex::new(r"\b(?:https?|ftp|irc|file)://[^\s\[]+(?:\[([^\]]*)\])?").expect("URL regex compiles"), Some(1)),
+        // formatting marks
+        (Regex::new(r"[*#^~]+").expect("MARKS regex compiles"), None),
+        (Regex::new(r"(?<!\w)_+|_+(?!\w)").expect("UNDERSCORE regex compiles"), None),
+    ]
+}
+
+/// Verbatim block delimiters, the content in between is not prose.
+const VERBATIM_DELIMITERS: &[char] = &['-', '.', '+', '/'];
+/// Compound block delimiters, the content in between is prose.
+const COMPOUND_DELIMITERS: &[char] = &['=', '*', '_'];
+
+/// Check if `line` consists of at least four repetitions of one of `delimiters`
+fn delimiter(line: &str, delimiters: &[char]) -> Option<char> {
+    let line = line.trim_end();
+    let mut chars = line.chars();
+    let first = chars.next()?;
+    if delimiters.contains(&first) && line.chars().count() >= 4 && chars.all(|c| c == first) {
+        Some(first)
+    } else {
+        None
+    }
+}
+
+impl CheckableChunk {
+    /// Extract the prose from an AsciiDoc document.
+    pub fn from_asciidoc(content: &str) -> Self {
+        let mut builder = ChunkBuilder::new();
+
+        // newlines to insert before the next fragment
+        let mut pending = 0usize;
+        // the closing delimiter of the verbatim block we are in
+        let mut verbatim: Option<String> = None;
+        // the next block is a verbatim block, per its block attributes
+        let mut verbatim_next = false;
+        // skipping a verbatim paragraph, which ends with an empty line
+        let mut verbatim_paragraph = false;
+        // within a table
+        let mut table = false;
+        // at the beginning of a paragraph
+        let mut paragraph_start = true;
+
+        for (lineno, line) in content.lines().enumerate() {
+            let lineno = lineno + 1;
+            let trimmed = line.trim_end();
+
+            if let Some(ref closing) = verbatim {
+                if trimmed == closing {
+                    verbatim = None;
+                }
+                continue;
+            }
+
+            if trimmed.is_empty() {
+                verbatim_paragraph = false;
+                paragraph_start = true;
+                pending = pending.max(2);
+                continue;
+            }
+
+            if verbatim_paragraph {
+                continue;
+            }
+
+            if delimiter(trimmed, VERBATIM_DELIMITERS).is_some() || trimmed.starts_with("```") {
+                verbatim = Some(if trimmed.starts_with("```") {
+                    "```".to_owned()
+                } else {
+                    trimmed.to_owned()
+                });
+                verbatim_next = false;
+                pending = pending.max(2);
+                continue;
+            }
+            if delimiter(trimmed, COMPOUND_DELIMITERS).is_some() || trimmed == "--" {
+                pending = pending.max(2);
+                paragraph_start = true;
+                continue;
+            }
+            if trimmed == "|===" {
+                table = !table;
+                pending = pending.max(2);
+                paragraph_start = true;
+                continue;
+            }
+
+            if trimmed.starts_with("//") {
+                continue;
+            }
+            if match_end(&ATTRIBUTE_ENTRY, trimmed).is_some()
+                || match_end(&BLOCK_MACRO, trimmed).is_some()
+                || trimmed == "+"
+            {
+                pending = pending.max(2);
+                paragraph_start = true;
+                continue;
+            }
+            if match_end(&BLOCK_ATTRIBUTES, trimmed).is_some() {
+                if match_end(&VERBATIM_ATTRIBUTES, trimmed).is_some() {
+                    verbatim_next = true;
+                }
+                pending = pending.max(2);
+                paragraph_start = true;
+                continue;
+            }
+
+            if paragraph_start {
+                paragraph_start = false;
+                if verbatim_next {
+                    // a verbatim paragraph without delimiters
+                    verbatim_next = false;
+                    verbatim_paragraph = true;
+                    continue;
+                }
+                if line.starts_with(char::is_whitespace) && match_end(&LIST_ITEM, line).is_none() {
+                    // indented literal paragraph
+                    verbatim_paragraph = true;
+                    continue;
+                }
+            }
+
+            // strip the block level markup
+            let (offset, standalone) = if let Some(end) = match_end(&HEADING, line) {
+                (end, true)
+            } else if let Some(end) = match_end(&LIST_ITEM, line) {
+                (end, true)
+            } else if let Some(end) = match_end(&BLOCK_TITLE, line) {
+                (end, true)
+            } else if let Some(end) = match_end(&ADMONITION, line) {
+                (end, false)
+            } else {
+                (0, false)
+            };
+            if standalone {
+                pending = pending.max(2);
+            }
+
+            let body = &line[offset..];
+            let column_offset = line[..offset].chars().count();
+            let ranges = inline_prose(body, if table { &INLINE_TABLE } else { &INLINE });
+            if ranges.is_empty() {
+                continue;
+            }
+
+            if !builder.is_empty() {
+                for _ in 0..pending.max(1) {
+                    builder.newline();
+                }
+            }
+            pending = if standalone { 2 } else { 0 };
+
+            builder.push_ranges(body, ranges, lineno, column_offset);
+        }
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::load_span_from;
+
+    const ASCIIDOC: &str = r#"= Document Titel
+:author: Jane Doe
+:toc:
+
+== Introdution
+
+This is *very* _importent_ text with `code_here` and a https://example.com[linkk].
+
+[source,rust]
+----
+let x = "mistkae";
+----
+
+* Firts item
+** Nested itme
+
+// a commment line
+
+NOTE: Admonitons are prose.
+
+....
+literal blok
+....
+"#;
+
+    const PLAIN: &str = r#"Document Titel
+
+Introdution
+
+This is very importent text with  and a linkk.
+
+Firts item
+
+Nested itme
+
+Admonitons are prose."#;
+
+    #[test]
+    fn asciidoc_reduction() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let chunk = CheckableChunk::from_asciidoc(ASCIIDOC);
+        assert_eq!(chunk.as_str(), PLAIN);
+
+        for (range, span) in chunk.iter() {
+            assert_eq!(
+                load_span_from(ASCIIDOC.as_bytes(), *span).expect("Span must be loadable"),
+                util::sub_chars(chunk.as_str(), range.clone())
+            );
+        }
+    }
+
+    #[test]
+    fn asciidoc_verbatim_paragraph() {
+        const SOURCE: &str = r#"[source]
+let skipped = true;
+
+Checked again.
+"#;
+        let chunk = CheckableChunk::from_asciidoc(SOURCE);
+        assert_eq!(chunk.as_str(), "Checked again.");
+        assert_eq!(
+            chunk.iter().next().map(|(_range, span)| *span),
+            Some(Span {
+                start: LineColumn { line: 4, column: 0 },
+                end: LineColumn {
+                    line: 4,
+                    column: 13
+                },
+            })
+        );
+    }
+}
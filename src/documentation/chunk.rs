@@ -13,6 +13,12 @@ use crate::{util::sub_chars, Range, Span};
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
 pub enum ContentOrigin {
     CommonMarkFile(PathBuf),
+    AsciiDocFile(PathBuf),
+    RestructuredTextFile(PathBuf),
+    /// A `.txt` file, which is prose without any markup.
+    PlainTextFile(PathBuf),
+    /// The `description` and `keywords` of a `Cargo.toml`.
+    CargoManifest(PathBuf),
     RustDocTest(PathBuf, Span), // span is just there to disambiguiate
     RustSourceFile(PathBuf),
     #[cfg(test)]
@@ -23,6 +29,10 @@ impl ContentOrigin {
     pub fn as_path(&self) -> &Path {
         match self {
             Self::CommonMarkFile(path) => path.as_path(),
+            Self::AsciiDocFile(path) => path.as_path(),
+            Self::RestructuredTextFile(path) => path.as_path(),
+            Self::PlainTextFile(path) => path.as_path(),
+            Self::CargoManifest(path) => path.as_path(),
             Self::RustDocTest(path, _) => path.as_path(),
             Self::RustSourceFile(path) => path.as_path(),
             #[cfg(test)]
@@ -34,6 +44,21 @@ impl ContentOrigin {
             }
         }
     }
+
+    /// Short name of the type of content.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::CommonMarkFile(_) => "markdown",
+            Self::AsciiDocFile(_) => "asciidoc",
+            Self::RestructuredTextFile(_) => "restructuredtext",
+            Self::PlainTextFile(_) => "plain-text",
+            Self::CargoManifest(_) => "manifest",
+            Self::RustDocTest(_, _) => "doc-test",
+            Self::RustSourceFile(_) => "rust-source",
+            #[cfg(test)]
+            Self::TestEntity => "test-entity",
+        }
+    }
 }
 
 impl fmt::Display for ContentOrigin {
@@ -51,7 +76,16 @@ pub struct CheckableChunk {
     /// Mapping from range within `content` and
     /// `Span` referencing the location within the source file.
     /// For a markdown file i.e. this would become a single entry spanning from start to end.
+    /// See `source_mapping()` for the invariants.
     source_mapping: IndexMap<Range, Span>,
+    /// Character offsets of all newlines within `content`, to locate a
+    /// character without scanning the content.
+    newlines: Vec<usize>,
+    /// Character ranges of the occurrences of words accepted by a trailing
+    /// `<!-- spellcheck: ok word -->`.
+    accepted: Vec<Range>,
+    /// The content is prose as it is, there is no markdown to erase.
+    plain_text: bool,
 }
 
 impl std::hash::Hash for CheckableChunk {
@@ -76,12 +110,44 @@ impl CheckableChunk {
     }
 
     pub fn from_string(content: String, source_mapping: IndexMap<Range, Span>) -> Self {
+        debug_assert!(
+            Self::is_valid_mapping(&content, &source_mapping),
+            "Source mapping {:?} violates its invariants for {:?}",
+            source_mapping,
+            content
+        );
+        let newlines = content
+            .chars()
+            .enumerate()
+            .filter(|(_idx, c)| *c == '\n')
+            .map(|(idx, _c)| idx)
+            .collect();
+        let accepted = if content.contains("<!--") {
+            super::toggle::accepted_occurrences(&content)
+        } else {
+            Vec::new()
+        };
         Self {
             content,
             source_mapping,
+            newlines,
+            accepted,
+            plain_text: false,
         }
     }
 
+    /// Check the invariants documented with `source_mapping()`.
+    fn is_valid_mapping(content: &str, source_mapping: &IndexMap<Range, Span>) -> bool {
+        let len = content.chars().count();
+        source_mapping
+            .keys()
+            .all(|range| range.start <= range.end && range.end <= len)
+            && source_mapping
+                .keys()
+                .zip(source_mapping.keys().skip(1))
+                .all(|(range, next)| range.end <= next.start)
+    }
+
     /// Find which part of the range maps to which span.
     /// Note that Range can very well be split into multiple fragments
     /// where each of them can be mapped to a potentially non-continuous
@@ -96,7 +162,7 @@ impl CheckableChunk {
     ///           (13,17) => (4,0)->(4,3),
     /// ]
     /// ```
-    pub(super) fn find_spans(&self, range: Range) -> IndexMap<Range, Span> {
+    pub(crate) fn find_spans(&self, range: Range) -> IndexMap<Range, Span> {
         trace!(
             "############################################ chunk find_span {:?}",
             &range
@@ -105,8 +171,8 @@ impl CheckableChunk {
         let Range { start, end } = range;
         self.source_mapping
             .iter()
-            .skip_while(|(fragment_range, _span)| fragment_range.end <= start)
-            .take_while(|(fragment_range, _span)| end <= fragment_range.end)
+            .skip(self.first_fragment_ending_after(start))
+            .take_while(|(fragment_range, _span)| fragment_range.start < end)
             .inspect(|x| {
                 trace!(">>> item {:?} ∈ {:?}", &range, x.0);
             })
@@ -145,33 +211,8 @@ impl CheckableChunk {
                 if let Some(span_len) = fragment_span.one_line_len() {
                     debug_assert_eq!(span_len, fragment_range.len());
                 }
-                // take the full fragment string, we need to count newlines before and after
-                let s = sub_chars(self.as_str(), fragment_range.clone());
-                // relative to the range given / offset
-                let shift = sub_fragment_range.start - fragment_range.start;
-                let mut sub_fragment_span = fragment_span.clone();
-                let state: LineColumn = fragment_span.start;
-                for (idx, c, cursor) in s.chars().enumerate().scan(state, |state, (idx, c)| {
-                    let x: (usize, char, LineColumn) = (idx, c, state.clone());
-                    match c {
-                        '\n' => {
-                            state.line += 1;
-                            state.column = 0;
-                        }
-                        _ => state.column += 1,
-                    }
-                    Some(x)
-                }) {
-                    trace!("char[{}]: {}", idx, c);
-                    if idx == shift {
-                        sub_fragment_span.start = cursor;
-                    }
-                    sub_fragment_span.end = cursor; // always set, even if we never reach the end of fragment
-                    if idx >= (sub_fragment_range.len() + shift - 1) {
-                        break;
-                    }
-                }
-
+                let sub_fragment_span =
+                    self.sub_fragment_span(fragment_range, fragment_span, &sub_fragment_range);
                 if let Some(sub_fragment_span_len) = sub_fragment_span.one_line_len() {
                     debug_assert_eq!(sub_fragment_span_len, sub_fragment_range.len());
                 }
@@ -187,6 +228,96 @@ impl CheckableChunk {
             .collect::<IndexMap<_, _>>()
     }
 
+    /// Index of the first fragment which ends after `offset`, found by
+    /// binary search since the fragments are sorted.
+    fn first_fragment_ending_after(&self, offset: usize) -> usize {
+        let (mut low, mut high) = (0, self.source_mapping.len());
+        while low < high {
+            let mid = (low + high) / 2;
+            let (fragment_range, _span) = self
+                .source_mapping
+                .get_index(mid)
+                .expect("Index is less than the length. qed");
+            if fragment_range.end <= offset {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    }
+
+    /// The span of `sub_fragment_range`, which is part of the fragment
+    /// `fragment_range` located at `fragment_span`.
+    fn sub_fragment_span(
+        &self,
+        fragment_range: &Range,
+        fragment_span: &Span,
+        sub_fragment_range: &Range,
+    ) -> Span {
+        let last = sub_fragment_range
+            .end
+            .saturating_sub(1)
+            .max(sub_fragment_range.start);
+        Span {
+            start: self.locate(fragment_range, fragment_span, sub_fragment_range.start),
+            end: self.locate(fragment_range, fragment_span, last),
+        }
+    }
+
+    /// The location of the character at `offset` within the chunk, which is
+    /// part of the fragment `fragment_range` located at `fragment_span`.
+    ///
+    /// A newline is located at the end of its line, the character following
+    /// it at the start of the next line.
+    fn locate(&self, fragment_range: &Range, fragment_span: &Span, offset: usize) -> LineColumn {
+        let first = self
+            .newlines
+            .partition_point(|newline| *newline < fragment_range.start);
+        let before = self.newlines.partition_point(|newline| *newline < offset);
+        if before == first {
+            LineColumn {
+                line: fragment_span.start.line,
+                column: fragment_span.start.column + offset - fragment_range.start,
+            }
+        } else {
+            LineColumn {
+                line: fragment_span.start.line + before - first,
+                column: offset - self.newlines[before - 1] - 1,
+            }
+        }
+    }
+
+    /// Extract the part `range` of the chunk as a chunk of its own, i.e. a
+    /// single sentence.
+    ///
+    /// The source mapping only retains the fragments covered by `range`,
+    /// trimmed to it just like `find_spans` does, with their ranges
+    /// relative to the start of `range`.
+    pub fn sub_chunk(&self, range: Range) -> CheckableChunk {
+        let content = sub_chars(self.as_str(), range.clone());
+        let source_mapping = self
+            .source_mapping
+            .iter()
+            .filter(|(fragment_range, _)| fragment_range.len() > 0)
+            .filter_map(|(fragment_range, fragment_span)| {
+                let sub_fragment_range = std::cmp::max(fragment_range.start, range.start)
+                    ..std::cmp::min(fragment_range.end, range.end);
+                if sub_fragment_range.start >= sub_fragment_range.end {
+                    return None;
+                }
+                let span =
+                    self.sub_fragment_span(fragment_range, fragment_span, &sub_fragment_range);
+                Some((
+                    (sub_fragment_range.start - range.start)
+                        ..(sub_fragment_range.end - range.start),
+                    span,
+                ))
+            })
+            .collect::<IndexMap<_, _>>();
+        Self::from_string(content, source_mapping)
+    }
+
     pub fn as_str(&self) -> &str {
         self.content.as_str()
     }
@@ -199,6 +330,17 @@ impl CheckableChunk {
         self.source_mapping.iter()
     }
 
+    /// The mapping of character ranges of the content to their spans
+    /// within the source file, one entry per fragment.
+    ///
+    /// The ranges are sorted, do not overlap and lie within the content,
+    /// but may leave gaps, i.e. for the newlines joining the lines of a
+    /// doc comment. The spans are sorted alike, but are not contiguous,
+    /// since comment prefixes and indentation are not part of the content.
+    pub fn source_mapping(&self) -> &IndexMap<Range, Span> {
+        &self.source_mapping
+    }
+
     pub fn fragment_count(&self) -> usize {
         self.source_mapping.len()
     }
@@ -208,20 +350,187 @@ impl CheckableChunk {
         PlainOverlay::erase_markdown(self)
     }
 
+    /// Obtain an accessor object, removing the markdown annotations as configured.
+    pub fn erase_markdown_with(&self, config: &crate::config::MarkdownConfig) -> PlainOverlay {
+        PlainOverlay::erase_markdown_with(self, config)
+    }
+
+    /// Check if the content is prose as it is, without any markdown.
+    pub fn is_plain_text(&self) -> bool {
+        self.plain_text
+    }
+
     /// Obtain the length in characters.
     pub fn len_in_chars(&self) -> usize {
         self.content.chars().count()
     }
+
+    /// Check if `range` lies within an occurrence of a word accepted by
+    /// a trailing `<!-- spellcheck: ok word -->`.
+    pub fn is_accepted(&self, range: &Range) -> bool {
+        self.accepted
+            .iter()
+            .any(|accepted| accepted.start <= range.start && range.end <= accepted.end)
+    }
+}
+
+/// Incrementally assemble a `CheckableChunk` from fragments of a non-rust file.
+///
+/// Used by the parsers of document formats other than markdown, which strip the
+/// markup themselves and only pass on the prose including its source location.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ChunkBuilder {
+    content: String,
+    /// Length of `content` in characters.
+    cursor: usize,
+    source_mapping: IndexMap<Range, Span>,
+}
+
+impl ChunkBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append `fragment`, which is located at `line` (1-indexed) starting
+    /// with `column` (0-indexed) in the source file.
+    ///
+    /// `fragment` must not contain any newlines.
+    pub fn push(&mut self, fragment: &str, line: usize, column: usize) {
+        let len = fragment.chars().count();
+        if len == 0 {
+            return;
+        }
+        debug_assert!(!fragment.contains('\n'));
+        let range = self.cursor..(self.cursor + len);
+        let span = Span {
+            start: LineColumn { line, column },
+            end: LineColumn {
+                line,
+                column: column + len - 1,
+            },
+        };
+        self.source_mapping.insert(range, span);
+        self.content.push_str(fragment);
+        self.cursor += len;
+    }
+
+    /// Append a newline, which is _not_ covered by any span.
+    pub fn newline(&mut self) {
+        self.content.push('\n');
+        self.cursor += 1;
+    }
+
+    /// Append the character `ranges` of `line`, which is located at `lineno`
+    /// (1-indexed), where `line` starts at `column_offset` in the source file.
+    ///
+    /// Leading whitespace of the line is not appended.
+    pub fn push_ranges(
+        &mut self,
+        line: &str,
+        ranges: impl IntoIterator<Item = Range>,
+        lineno: usize,
+        column_offset: usize,
+    ) {
+        let mut line_start = true;
+        for range in ranges {
+            let fragment = sub_chars(line, range.clone());
+            let leading = if line_start {
+                fragment.chars().take_while(|c| c.is_whitespace()).count()
+            } else {
+                0
+            };
+            let trimmed = &fragment[fragment
+                .char_indices()
+                .nth(leading)
+                .map(|(idx, _)| idx)
+                .unwrap_or(fragment.len())..];
+            if !trimmed.is_empty() {
+                line_start = false;
+            }
+            self.push(trimmed, lineno, column_offset + range.start + leading);
+        }
+    }
+
+    /// Check if no content was pushed yet.
+    pub fn is_empty(&self) -> bool {
+        self.content.is_empty()
+    }
+
+    pub fn build(self) -> CheckableChunk {
+        CheckableChunk::from_string(self.content, self.source_mapping)
+    }
+
+    /// Build a chunk whose content is taken as prose as it is, without
+    /// erasing any markdown.
+    pub fn build_plain_text(self) -> CheckableChunk {
+        let mut chunk = self.build();
+        chunk.plain_text = true;
+        chunk
+    }
+}
+
+/// Byte offset of the end of the first match of `regex` in `line`, if any.
+pub(crate) fn match_end(regex: &fancy_regex::Regex, line: &str) -> Option<usize> {
+    regex.find(line).ok().flatten().map(|m| m.end())
+}
+
+/// Determine the character ranges within `line` which are prose.
+///
+/// Every match of the `markup` expressions is erased, except for the
+/// capture group paired with the expression, which is retained.
+pub(crate) fn inline_prose(
+    line: &str,
+    markup: &[(fancy_regex::Regex, Option<usize>)],
+) -> Vec<Range> {
+    let mut keep = vec![true; line.len()];
+    for (regex, retain) in markup.iter() {
+        let mut pos = 0usize;
+        while let Some(captures) = regex.captures_from_pos(line, pos).ok().flatten() {
+            let full = captures.get(0).expect("Capture group 0 always exists. qed");
+            // all expressions consume at least one character
+            pos = full.end();
+            let retained = retain.and_then(|idx| captures.get(idx));
+            for idx in full.start()..full.end() {
+                if let Some(ref retained) = retained {
+                    if retained.start() <= idx && idx < retained.end() {
+                        continue;
+                    }
+                }
+                keep[idx] = false;
+            }
+        }
+    }
+
+    let mut ranges = Vec::with_capacity(8);
+    let mut start = None;
+    let mut count = 0usize;
+    for (char_idx, (byte_idx, _c)) in line.char_indices().enumerate() {
+        count = char_idx + 1;
+        match (keep[byte_idx], start) {
+            (true, None) => start = Some(char_idx),
+            (false, Some(s)) => {
+                ranges.push(s..char_idx);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        ranges.push(s..count);
+    }
+    ranges
 }
 
 /// Convert the clusters of one file into a source description as well
 /// as well as vector of checkable chunks.
 impl From<Clusters> for Vec<CheckableChunk> {
     fn from(clusters: Clusters) -> Vec<CheckableChunk> {
+        let join_hyphenated = clusters.join_hyphenated_lines;
         clusters
             .set
             .into_iter()
-            .map(|literal_set| CheckableChunk::from_literalset(literal_set))
+            .map(|literal_set| literal_set.into_joined_chunk(join_hyphenated))
+            .chain(clusters.format_strings)
             .collect::<Vec<_>>()
     }
 }
@@ -443,6 +752,126 @@ mod test {
         }
     }
 
+    #[test]
+    fn find_spans_across_fragments() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        const SOURCE: &'static str = fluff_up!(["xyz", "second", "third"]);
+        let set = gen_literal_set(SOURCE);
+        let chunk = CheckableChunk::from_literalset(set);
+        assert_eq!(chunk.as_str(), " xyz\n second\n third");
+
+        // every fragment overlapping the range contributes its part
+        let range2span = chunk.find_spans(2..9);
+        assert_eq!(
+            range2span.keys().cloned().collect::<Vec<_>>(),
+            vec![2..4, 5..9]
+        );
+        let extracted = range2span
+            .values()
+            .map(|span| {
+                load_span_from(SOURCE.as_bytes(), *span).expect("Span extraction must work")
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(extracted, vec!["yz", " sec"]);
+
+        // fragments adjacent to the range do not
+        let range2span = chunk.find_spans(5..12);
+        assert_eq!(range2span.keys().cloned().collect::<Vec<_>>(), vec![5..12]);
+        let range2span = chunk.find_spans(0..20);
+        assert_eq!(
+            range2span.keys().cloned().collect::<Vec<_>>(),
+            vec![0..4, 5..12, 13..19]
+        );
+    }
+
+    #[test]
+    fn sub_chunk_multiline() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        const SOURCE: &'static str = fluff_up!(["xyz", "second", "third"] @ "       ");
+        let set = gen_literal_set(SOURCE);
+        let chunk = dbg!(CheckableChunk::from_literalset(set));
+        assert_eq!(chunk.as_str(), " xyz\n second\n third");
+
+        // "second\n third"
+        let sub = dbg!(chunk.sub_chunk(6..19));
+        assert_eq!(sub.as_str(), "second\n third");
+        assert_eq!(sub.fragment_count(), 2);
+        for (range, span) in sub.iter() {
+            assert_eq!(
+                load_span_from(SOURCE.as_bytes(), *span).expect("Span extraction must work"),
+                sub_chars(sub.as_str(), range.clone())
+            );
+        }
+        // maps to the same location as the chunk it was taken from
+        assert_eq!(
+            sub.find_spans(0..6).values().collect::<Vec<_>>(),
+            chunk.find_spans(6..12).values().collect::<Vec<_>>()
+        );
+
+        // within a single fragment
+        let sub = chunk.sub_chunk(8..10);
+        assert_eq!(sub.as_str(), "co");
+        let (range, span) = sub.iter().next().unwrap();
+        assert_eq!(range, &(0..2));
+        assert_eq!(
+            span.start,
+            LineColumn {
+                line: 2,
+                column: 13
+            }
+        );
+        assert_eq!(
+            span.end,
+            LineColumn {
+                line: 2,
+                column: 14
+            }
+        );
+    }
+
+    #[test]
+    fn source_mapping() {
+        const SOURCE: &'static str = fluff_up!(["xyz", "second", "third"] @ "       ");
+        let set = gen_literal_set(SOURCE);
+        let chunk = CheckableChunk::from_literalset(set);
+        let mapping = chunk.source_mapping();
+        assert_eq!(mapping.len(), chunk.fragment_count());
+        assert_eq!(
+            mapping.keys().cloned().collect::<Vec<_>>(),
+            vec![0..4, 5..12, 13..19]
+        );
+        for (range, span) in mapping.iter() {
+            assert_eq!(
+                load_span_from(SOURCE.as_bytes(), *span).expect("Span extraction must work"),
+                sub_chars(chunk.as_str(), range.clone())
+            );
+        }
+
+        let span = *mapping.values().next().unwrap();
+        assert!(CheckableChunk::is_valid_mapping(
+            "abc",
+            &indexmap::indexmap! { 0..1 => span, 2..3 => span }
+        ));
+        assert!(CheckableChunk::is_valid_mapping("", &IndexMap::new()));
+        // overlapping
+        assert!(!CheckableChunk::is_valid_mapping(
+            "abc",
+            &indexmap::indexmap! { 0..2 => span, 1..3 => span }
+        ));
+        // unsorted
+        assert!(!CheckableChunk::is_valid_mapping(
+            "abc",
+            &indexmap::indexmap! { 2..3 => span, 0..1 => span }
+        ));
+        // exceeds the content
+        assert!(!CheckableChunk::is_valid_mapping(
+            "abc",
+            &indexmap::indexmap! { 0..4 => span }
+        ));
+    }
+
     #[test]
     fn find_spans_chyrp() {
         let _ = env_logger::builder().is_test(true).try_init();
@@ -492,4 +921,75 @@ Buchfink"#];
             assert_eq!(span, expected_span);
         }
     }
+
+    /// Resolve the span of every word of large chunks, a doc comment of
+    /// many lines with one fragment per line and a markdown file with a
+    /// single fragment spanning all of its lines.
+    ///
+    /// Run with `cargo test --release -- --ignored find_spans_bench --nocapture`.
+    #[test]
+    #[ignore]
+    fn find_spans_bench() {
+        const LINES: usize = 2000;
+        const LINE: &str = "Lorme ipsmu dolro sti amte, consectteur adipiscign elti.";
+        let len = LINE.chars().count();
+        let content = vec![LINE; LINES].join("\n");
+
+        let doc_comment = CheckableChunk::from_str(
+            &content,
+            (0..LINES)
+                .map(|line| {
+                    let start = line * (len + 1);
+                    let span = Span {
+                        start: LineColumn {
+                            line: line + 1,
+                            column: 3,
+                        },
+                        end: LineColumn {
+                            line: line + 1,
+                            column: 3 + len - 1,
+                        },
+                    };
+                    (start..(start + len), span)
+                })
+                .collect(),
+        );
+        let markdown = CheckableChunk::from_str(
+            &content,
+            indexmap::indexmap! { 0..content.chars().count() => Span {
+                    start: LineColumn { line: 1, column: 0 },
+                    end: LineColumn { line: LINES, column: len - 1 },
+                }
+            },
+        );
+
+        let words = crate::checker::Tokenizer::default().tokenize(&content);
+        assert_eq!(words.len(), LINES * 8);
+        for (name, chunk, column) in &[("doc comment", doc_comment, 3), ("markdown", markdown, 0)] {
+            let start = std::time::Instant::now();
+            for word in words.iter() {
+                let spans = chunk.find_spans(word.clone());
+                assert_eq!(spans.len(), 1);
+                let span = spans.values().next().unwrap();
+                assert_eq!(span.start.line, word.start / (len + 1) + 1);
+                assert_eq!(span.start.column, column + word.start % (len + 1));
+            }
+            println!(
+                "find_spans of {} words within a {}: {:?}",
+                words.len(),
+                name,
+                start.elapsed()
+            );
+        }
+    }
+
+    #[test]
+    fn accepted() {
+        const CONTENT: &str = "A tokio <!-- spellcheck: ok tokio -->\nand tokio";
+        let chunk = CheckableChunk::from_str(CONTENT, indexmap::indexmap! {});
+        assert!(chunk.is_accepted(&(2..7)));
+        assert!(chunk.is_accepted(&(3..5)));
+        assert!(!chunk.is_accepted(&(0..1)));
+        assert!(!chunk.is_accepted(&(42..47)));
+    }
 }
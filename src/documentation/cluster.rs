@@ -1,15 +1,274 @@
 //! Cluster `proc_macro2::Literal`s into `LiteralSets`
 
 use super::*;
+use crate::config::{DocAttribute, RustConfig};
+use crate::documentation::formatstring::{format_string_literal, FORMAT_MACROS};
 use crate::documentation::Range;
 use crate::Span;
 use anyhow::{anyhow, Error, Result};
+use proc_macro2::{token_stream, Delimiter, Group};
 use std::convert::TryFrom;
 
 /// Cluster literals for one file
 #[derive(Debug)]
 pub struct Clusters {
     pub(super) set: Vec<LiteralSet>,
+    /// Paths of files included as documentation with
+    /// `#[doc = include_str!("..")]`, as written in the source.
+    pub(super) includes: Vec<String>,
+    /// Prose of format strings, which is never joined with docs.
+    pub(super) format_strings: Vec<CheckableChunk>,
+    /// Attributes besides `doc` whose literals are extracted.
+    doc_attributes: Vec<DocAttribute>,
+    /// Extract the format strings of `println!` and friends.
+    check_format_strings: bool,
+    /// The last set stems from one of `doc_attributes`, and must not be
+    /// joined with adjacent docs.
+    last_is_attribute: bool,
+    /// Only extract the docs of public items.
+    only_public: bool,
+    /// Consider `pub(crate)` public.
+    include_pub_crate: bool,
+    /// Join words split across lines by a trailing hyphen.
+    pub(super) join_hyphenated_lines: bool,
+}
+
+/// Visibility of the items within a token stream, only tracked if solely
+/// the docs of public items are extracted.
+///
+/// An item is public if it is declared `pub` and all items it is nested
+/// in are public. Module docs `//!` follow the visibility of the module
+/// they document, the docs at the top of a file are always extracted,
+/// since the visibility of the `mod` declaration is not known.
+#[derive(Debug, Clone, Copy)]
+struct Scope {
+    /// The item owning the stream is public.
+    public: bool,
+    /// Items inherit the visibility of the owning item, as enum variants,
+    /// trait items and the items of trait impls do.
+    inherit: bool,
+    /// Items are separated by commas, as fields and variants are.
+    fields: bool,
+}
+
+impl Scope {
+    /// The top level of a file.
+    const FILE: Self = Self {
+        public: true,
+        inherit: false,
+        fields: false,
+    };
+}
+
+/// Keywords which determine how the visibility of an item applies to
+/// the items within its body.
+const ITEM_KEYWORDS: &[&str] = &["mod", "struct", "union", "enum", "trait", "impl", "fn"];
+
+/// Arguments of `#![doc(..)]` holding URLs rather than prose, which are
+/// never checked.
+const DOC_URL_ARGUMENTS: &[&str] = &[
+    "html_root_url",
+    "html_logo_url",
+    "html_favicon_url",
+    "html_playground_url",
+    "issue_tracker_base_url",
+];
+
+/// Obtain the content of a plain or raw string literal, without
+/// resolving any escape sequences but `\\`.
+fn unquote(literal: &str) -> Option<String> {
+    if let Some(raw) = literal.strip_prefix('r') {
+        let hashes = raw.chars().take_while(|c| *c == '#').count();
+        let inner = raw
+            .get(hashes..raw.len().checked_sub(hashes)?)?
+            .strip_prefix('"')?
+            .strip_suffix('"')?;
+        Some(inner.to_owned())
+    } else {
+        let inner = literal.strip_prefix('"')?.strip_suffix('"')?;
+        Some(inner.replace("\\\\", "\\"))
+    }
+}
+
+/// Collect the attribute groups of a single item, where the leading `#`
+/// was already consumed from `iter`.
+///
+/// Inner attributes `#![..]` and outer attributes `#[..]` are never
+/// mixed, so the crate attributes do not end up with the first item.
+fn attribute_run(iter: &mut token_stream::IntoIter) -> Vec<Group> {
+    let mut run = Vec::new();
+    let mut kind = None;
+    let mut lookahead = iter.clone();
+    loop {
+        let mut next = lookahead.next();
+        let inner = matches!(next, Some(TokenTree::Punct(ref punct)) if punct.as_char() == '!');
+        if inner {
+            next = lookahead.next();
+        }
+        match next {
+            Some(TokenTree::Group(group))
+                if group.delimiter() == Delimiter::Bracket
+                    && *kind.get_or_insert(inner) == inner =>
+            {
+                run.push(group);
+                *iter = lookahead.clone();
+            }
+            _ => break,
+        }
+        match lookahead.next() {
+            Some(TokenTree::Punct(ref punct)) if punct.as_char() == '#' => {}
+            _ => break,
+        }
+    }
+    run
+}
+
+/// Consume the restriction of a `pub` visibility, i.e. `(crate)`, and
+/// check if the visibility counts as public.
+fn is_public_restriction(iter: &mut token_stream::IntoIter, include_pub_crate: bool) -> bool {
+    let mut lookahead = iter.clone();
+    match lookahead.next() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => {
+            *iter = lookahead;
+            let trees = group.stream().into_iter().collect::<Vec<_>>();
+            include_pub_crate
+                && matches!(trees.as_slice(), [TokenTree::Ident(ident)] if *ident == "crate")
+        }
+        _ => true,
+    }
+}
+
+/// Check if the content of an attribute is `macro_export`, which makes a
+/// `macro_rules!` public.
+fn is_macro_export(stream: proc_macro2::TokenStream) -> bool {
+    let trees = stream.into_iter().collect::<Vec<_>>();
+    matches!(trees.as_slice(), [TokenTree::Ident(ident)] if *ident == "macro_export")
+}
+
+/// The plain or raw string literal `tree`, if it is one.
+fn str_literal(tree: &TokenTree) -> Option<proc_macro2::Literal> {
+    match tree {
+        TokenTree::Literal(literal) => {
+            let rendered = literal.to_string();
+            if rendered.starts_with('"')
+                || rendered.starts_with("r\"")
+                || rendered.starts_with("r#")
+            {
+                Some(literal.clone())
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Obtain the string literal holding the prose of an attribute listed in
+/// `doc_attributes`, given the content of the attribute.
+///
+/// The path, i.e. `error` or `serde::rename`, must match exactly. Without
+/// an `argument`, the first argument `#[error("..", ..)]` or the value
+/// `#[error = ".."]` must be a string literal. With an `argument`, the
+/// named argument `#[clap(about = "..", ..)]` must be present. The first
+/// of the attributes with a matching path which yields a literal is used.
+fn doc_attribute_literal(
+    stream: proc_macro2::TokenStream,
+    doc_attributes: &[DocAttribute],
+) -> Option<proc_macro2::Literal> {
+    if doc_attributes.is_empty() {
+        return None;
+    }
+    let trees = stream.into_iter().collect::<Vec<_>>();
+    let path_len = trees
+        .iter()
+        .take_while(|tree| match tree {
+            TokenTree::Ident(_) => true,
+            TokenTree::Punct(punct) => punct.as_char() == ':',
+            _ => false,
+        })
+        .count();
+    let path = trees[..path_len]
+        .iter()
+        .map(|tree| tree.to_string())
+        .collect::<String>();
+    doc_attributes
+        .iter()
+        .filter(|doc_attribute| doc_attribute.path == path)
+        .filter(|doc_attribute| {
+            path != "doc"
+                || !doc_attribute
+                    .argument
+                    .as_deref()
+                    .map(|argument| DOC_URL_ARGUMENTS.contains(&argument))
+                    .unwrap_or(false)
+        })
+        .find_map(|doc_attribute| {
+            attribute_argument_literal(&trees[path_len..], doc_attribute.argument.as_deref())
+        })
+}
+
+/// Obtain the string literal of the `argument`, given the tokens of an
+/// attribute following its path.
+fn attribute_argument_literal(
+    trees: &[TokenTree],
+    argument: Option<&str>,
+) -> Option<proc_macro2::Literal> {
+    match (trees, argument) {
+        ([TokenTree::Punct(eq), value], None) if eq.as_char() == '=' => str_literal(value),
+        ([TokenTree::Group(group)], argument) if group.delimiter() == Delimiter::Parenthesis => {
+            let trees = group.stream().into_iter().collect::<Vec<_>>();
+            let mut arguments = trees
+                .split(|tree| matches!(tree, TokenTree::Punct(punct) if punct.as_char() == ','));
+            match argument {
+                None => match arguments.next()? {
+                    [value] => str_literal(value),
+                    _ => None,
+                },
+                Some(argument) => arguments.find_map(|trees| match trees {
+                    [TokenTree::Ident(name), TokenTree::Punct(eq), value]
+                        if name == argument && eq.as_char() == '=' =>
+                    {
+                        str_literal(value)
+                    }
+                    _ => None,
+                }),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Check if the tokens spell the path `spellcheck::skip`.
+fn is_skip_path(trees: &[TokenTree]) -> bool {
+    match trees {
+        [TokenTree::Ident(tool), TokenTree::Punct(first), TokenTree::Punct(second), TokenTree::Ident(name)] => {
+            *tool == "spellcheck"
+                && first.as_char() == ':'
+                && second.as_char() == ':'
+                && *name == "skip"
+        }
+        _ => false,
+    }
+}
+
+/// Check if the content of an attribute is `spellcheck::skip`, or
+/// `cfg_attr(<predicate>, spellcheck::skip)`.
+///
+/// The latter is required for the code to compile, since `rustc` does not
+/// know about the `spellcheck` tool.
+fn is_skip_marker(stream: proc_macro2::TokenStream) -> bool {
+    let trees = stream.into_iter().collect::<Vec<_>>();
+    match trees.as_slice() {
+        [TokenTree::Ident(cfg_attr), TokenTree::Group(group)] if *cfg_attr == "cfg_attr" => {
+            let trees = group.stream().into_iter().collect::<Vec<_>>();
+            // the first segment is the predicate
+            trees
+                .split(|tree| matches!(tree, TokenTree::Punct(punct) if punct.as_char() == ','))
+                .skip(1)
+                .any(is_skip_path)
+        }
+        trees => is_skip_path(trees),
+    }
 }
 
 impl Clusters {
@@ -17,7 +276,9 @@ impl Clusters {
     /// requires a adjacency list.
     fn process_literal(&mut self, source: &str, literal: proc_macro2::Literal) -> Result<()> {
         let literal = TrimmedLiteral::try_from((source, literal))?;
-        if let Some(cls) = self.set.last_mut() {
+        if std::mem::replace(&mut self.last_is_attribute, false) {
+            self.set.push(LiteralSet::from(literal));
+        } else if let Some(cls) = self.set.last_mut() {
             if let Err(literal) = cls.add_adjacent(literal) {
                 trace!(target: "documentation",
                     "appending, but failed to append: {:?} to set {:?}",
@@ -34,12 +295,156 @@ impl Clusters {
         Ok(())
     }
 
+    /// A literal of one of the `doc_attributes`, which always forms a set
+    /// of its own.
+    fn process_attribute_literal(
+        &mut self,
+        source: &str,
+        literal: proc_macro2::Literal,
+    ) -> Result<()> {
+        let literal = TrimmedLiteral::try_from((source, literal))?;
+        self.set.push(LiteralSet::from(literal));
+        self.last_is_attribute = true;
+        Ok(())
+    }
+
+    /// The visibility of an item within `scope`, which is declared `pub`
+    /// if `declared` is set.
+    fn item_visibility(&self, scope: Scope, declared: bool) -> bool {
+        !self.only_public || (scope.public && (scope.inherit || declared))
+    }
+
+    /// The scope of the body of an item of kind `keyword`, nested in `scope`.
+    fn nested_scope(
+        &self,
+        scope: Scope,
+        keyword: Option<&str>,
+        public: bool,
+        trait_impl: bool,
+    ) -> Scope {
+        if !self.only_public {
+            return scope;
+        }
+        let (public, inherit, fields) = match keyword {
+            Some("mod") => (public, false, false),
+            Some("struct") | Some("union") => (public, false, true),
+            Some("enum") => (public, true, true),
+            Some("trait") => (public, true, false),
+            // impls have no visibility of their own
+            Some("impl") => (scope.public, trait_impl, false),
+            // items within function bodies are never public
+            Some("fn") => (false, false, false),
+            _ => return scope,
+        };
+        Scope {
+            public,
+            inherit,
+            fields,
+        }
+    }
+
     /// Helper function to parse a stream and associated the found literals
-    fn parse_token_tree(&mut self, source: &str, stream: proc_macro2::TokenStream) -> Result<()> {
+    fn parse_token_tree(
+        &mut self,
+        source: &str,
+        stream: proc_macro2::TokenStream,
+        scope: Scope,
+    ) -> Result<()> {
+        // the item currently processed
+        let mut public = self.item_visibility(scope, false);
+        let mut keyword = None;
+        let mut trait_impl = false;
         let mut iter = stream.into_iter();
         while let Some(tree) = iter.next() {
             match tree {
+                TokenTree::Punct(punct) if punct.as_char() == '#' => {
+                    let inner = matches!(iter.clone().next(), Some(TokenTree::Punct(ref punct)) if punct.as_char() == '!');
+                    let attributes = attribute_run(&mut iter);
+                    if attributes
+                        .iter()
+                        .any(|attribute| is_skip_marker(attribute.stream()))
+                    {
+                        trace!(target: "documentation", "Skipping the docs of an item marked with spellcheck::skip");
+                        continue;
+                    }
+                    if !inner {
+                        let mut lookahead = iter.clone();
+                        let declared = match lookahead.next() {
+                            Some(TokenTree::Ident(ident)) if ident == "pub" => {
+                                is_public_restriction(&mut lookahead, self.include_pub_crate)
+                            }
+                            _ => false,
+                        } || attributes
+                            .iter()
+                            .any(|attribute| is_macro_export(attribute.stream()));
+                        public = self.item_visibility(scope, declared);
+                    }
+                    // inner attributes belong to the owning item
+                    let collect = if inner { scope.public } else { public };
+                    if !collect {
+                        trace!(target: "documentation", "Skipping the docs of an item which is not public");
+                        continue;
+                    }
+                    for attribute in attributes {
+                        if let Some(literal) =
+                            doc_attribute_literal(attribute.stream(), &self.doc_attributes)
+                        {
+                            trace!(target: "documentation", "Found doc attribute literal {:?}", literal);
+                            if let Err(e) = self.process_attribute_literal(source, literal) {
+                                log::error!(
+                                    "BUG: Failed to guarantee literal content/span integrity: {}",
+                                    e
+                                );
+                            }
+                            continue;
+                        }
+                        self.parse_token_tree(source, attribute.stream(), scope)?;
+                    }
+                }
+                TokenTree::Punct(punct)
+                    if punct.as_char() == ';' || (scope.fields && punct.as_char() == ',') =>
+                {
+                    public = self.item_visibility(scope, false);
+                    keyword = None;
+                    trait_impl = false;
+                }
                 TokenTree::Ident(ident) => {
+                    let name = ident.to_string();
+                    if name == "pub" {
+                        let declared = is_public_restriction(&mut iter, self.include_pub_crate);
+                        public = self.item_visibility(scope, declared);
+                        continue;
+                    }
+                    if keyword.is_none() && ITEM_KEYWORDS.contains(&name.as_str()) {
+                        keyword = Some(name);
+                        continue;
+                    }
+                    if name == "for" && keyword.as_deref() == Some("impl") {
+                        trait_impl = true;
+                        continue;
+                    }
+                    if self.check_format_strings && FORMAT_MACROS.contains(&name.as_str()) {
+                        let mut lookahead = iter.clone();
+                        match (lookahead.next(), lookahead.next()) {
+                            (Some(TokenTree::Punct(bang)), Some(TokenTree::Group(group)))
+                                if bang.as_char() == '!' =>
+                            {
+                                iter = lookahead;
+                                if let Some(literal) = format_string_literal(&name, group.stream())
+                                {
+                                    let start = literal.span().start();
+                                    if let Some(chunk) =
+                                        CheckableChunk::from_format_string(source, start)
+                                    {
+                                        trace!(target: "documentation", "Found format string of {}! at {:?}", name, start);
+                                        self.format_strings.push(chunk);
+                                    }
+                                }
+                                continue;
+                            }
+                            _ => {}
+                        }
+                    }
                     // if we find an identifier
                     // which is doc
                     if ident != "doc" {
@@ -68,6 +473,36 @@ impl Clusters {
                         continue;
                     }
                     let comment = comment.unwrap();
+                    // `include_str!("path")`, the content of the file is checked separately
+                    if let TokenTree::Ident(ref ident) = comment {
+                        if ident != "include_str" {
+                            continue;
+                        }
+                        match (iter.next(), iter.next()) {
+                            (Some(TokenTree::Punct(punct)), Some(TokenTree::Group(group)))
+                                if punct.as_char() == '!' =>
+                            {
+                                let path = group.stream().into_iter().next().and_then(|tree| {
+                                    if let TokenTree::Literal(literal) = tree {
+                                        unquote(literal.to_string().as_str())
+                                    } else {
+                                        None
+                                    }
+                                });
+                                if let Some(path) = path {
+                                    trace!(target: "documentation", "Found doc include of {}", &path);
+                                    self.includes.push(path);
+                                } else {
+                                    log::warn!(
+                                        "Unsupported argument to include_str!: {}",
+                                        group.stream()
+                                    );
+                                }
+                            }
+                            _ => {}
+                        }
+                        continue;
+                    }
                     if let TokenTree::Literal(literal) = comment {
                         trace!(target: "documentation",
                             "Found doc literal at {:?}: {:?}",
@@ -86,7 +521,14 @@ impl Clusters {
                     }
                 }
                 TokenTree::Group(group) => {
-                    self.parse_token_tree(source, group.stream())?;
+                    let nested = self.nested_scope(scope, keyword.as_deref(), public, trait_impl);
+                    self.parse_token_tree(source, group.stream(), nested)?;
+                    // a body ends the item
+                    if group.delimiter() == Delimiter::Brace {
+                        public = self.item_visibility(scope, false);
+                        keyword = None;
+                        trait_impl = false;
+                    }
                 }
                 _ => {}
             };
@@ -95,15 +537,31 @@ impl Clusters {
     }
 }
 
-impl TryFrom<&str> for Clusters {
-    type Error = Error;
-    fn try_from(source: &str) -> Result<Self> {
+impl Clusters {
+    /// Cluster the docs of `source`, including the string literals
+    /// configured in `config`.
+    pub fn load_from_str(source: &str, config: &RustConfig) -> Result<Self> {
         let mut chunk = Self {
             set: Vec::with_capacity(64),
+            includes: Vec::new(),
+            format_strings: Vec::new(),
+            doc_attributes: config.doc_attributes(),
+            check_format_strings: config.check_format_strings(),
+            last_is_attribute: false,
+            only_public: config.only_public(),
+            include_pub_crate: config.include_pub_crate(),
+            join_hyphenated_lines: config.join_hyphenated_lines(),
         };
         let stream = syn::parse_str::<proc_macro2::TokenStream>(source)
             .map_err(|e| anyhow!("Failed to parse content to stream").context(e))?;
-        chunk.parse_token_tree(source, stream)?;
+        chunk.parse_token_tree(source, stream, Scope::FILE)?;
         Ok(chunk)
     }
 }
+
+impl TryFrom<&str> for Clusters {
+    type Error = Error;
+    fn try_from(source: &str) -> Result<Self> {
+        Self::load_from_str(source, &Default::default())
+    }
+}
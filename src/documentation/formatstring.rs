@@ -0,0 +1,258 @@
+//! Extract the prose of format strings
+//!
+//! The format string of `println!("..")` and friends is split at its
+//! placeholders, escape sequences and escaped braces, the remaining
+//! fragments are joined by newlines, so words on either side of a
+//! placeholder are never merged. Every fragment maps back to its exact
+//! location within the source file.
+
+use super::*;
+
+use crate::documentation::chunk::ChunkBuilder;
+
+/// Macros whose first argument is a format string.
+pub(crate) const FORMAT_MACROS: &[&str] = &[
+    "print", "println", "eprint", "eprintln", "error", "warn", "info", "debug", "trace",
+];
+
+/// Logging macros, which accept a `target: ".."` argument in front of the format string.
+const LOG_MACROS: &[&str] = &["error", "warn", "info", "debug", "trace"];
+
+/// Append `fragment` to `builder`, separated from the previous one by a newline.
+fn flush(builder: &mut ChunkBuilder, fragment: &mut String, lineno: usize, column: usize) {
+    if fragment.is_empty() {
+        return;
+    }
+    if !builder.is_empty() {
+        builder.newline();
+    }
+    builder.push(fragment.as_str(), lineno, column);
+    fragment.clear();
+}
+
+/// Check if `text` reads like prose, which requires at least two words
+/// consisting of letters only, ignoring surrounding punctuation.
+fn is_prose(text: &str) -> bool {
+    text.split_whitespace()
+        .map(|word| word.trim_matches(|c: char| c.is_ascii_punctuation()))
+        .filter(|word| word.chars().count() > 1 && word.chars().all(char::is_alphabetic))
+        .count()
+        >= 2
+}
+
+/// The string literal, among the arguments of the format macro `name`,
+/// which is the format string.
+pub(crate) fn format_string_literal(
+    name: &str,
+    arguments: proc_macro2::TokenStream,
+) -> Option<proc_macro2::Literal> {
+    let trees = arguments.into_iter().collect::<Vec<_>>();
+    let mut arguments =
+        trees.split(|tree| matches!(tree, TokenTree::Punct(punct) if punct.as_char() == ','));
+    let mut first = arguments.next()?;
+    if LOG_MACROS.contains(&name) {
+        if let [TokenTree::Ident(target), TokenTree::Punct(colon), ..] = first {
+            if target == "target" && colon.as_char() == ':' {
+                first = arguments.next()?;
+            }
+        }
+    }
+    match first {
+        [TokenTree::Literal(literal)] => {
+            let rendered = literal.to_string();
+            if rendered.starts_with('"') || rendered.starts_with('r') {
+                Some(literal.clone())
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+impl CheckableChunk {
+    /// Extract the prose of the format string literal starting at `start`
+    /// within `source`, returns `None` if it is not prose.
+    pub fn from_format_string(source: &str, start: LineColumn) -> Option<Self> {
+        let mut lines = source
+            .lines()
+            .skip(start.line.checked_sub(1)?)
+            .map(|line| line.chars().collect::<Vec<char>>());
+        let mut lineno = start.line;
+        let mut chars = lines.next()?;
+        let mut column = start.column;
+        let (hashes, escapes) = match chars.get(column)? {
+            '"' => (0, true),
+            'r' => (
+                chars[column + 1..]
+                    .iter()
+                    .take_while(|c| **c == '#')
+                    .count(),
+                false,
+            ),
+            _ => return None,
+        };
+        let closing = std::iter::once('"')
+            .chain(std::iter::repeat('#').take(hashes))
+            .collect::<Vec<char>>();
+        column += if escapes { 1 } else { hashes + 2 };
+
+        let mut builder = ChunkBuilder::new();
+        let mut fragment = String::new();
+        let mut fragment_start = column;
+        loop {
+            if column >= chars.len() {
+                flush(&mut builder, &mut fragment, lineno, fragment_start);
+                chars = lines.next()?;
+                lineno += 1;
+                column = 0;
+                fragment_start = 0;
+                continue;
+            }
+            if chars[column..].starts_with(&closing) {
+                flush(&mut builder, &mut fragment, lineno, fragment_start);
+                break;
+            }
+            let c = chars[column];
+            let skip = match c {
+                '\\' if escapes => {
+                    if chars[column + 1..].iter().all(|c| c.is_whitespace()) {
+                        // a line ending backslash skips all whitespace up to the next content
+                        flush(&mut builder, &mut fragment, lineno, fragment_start);
+                        loop {
+                            chars = lines.next()?;
+                            lineno += 1;
+                            column = chars.iter().take_while(|c| c.is_whitespace()).count();
+                            if column < chars.len() {
+                                break;
+                            }
+                        }
+                        fragment_start = column;
+                        continue;
+                    }
+                    if chars.get(column + 1) == Some(&'u') {
+                        // `\u{..}`
+                        chars[column..].iter().position(|c| *c == '}')? + 1
+                    } else {
+                        2
+                    }
+                }
+                '{' | '}' if chars.get(column + 1) == Some(&c) => 2,
+                // a placeholder, i.e. `{}` or `{name:?}`
+                '{' => chars[column..].iter().position(|c| *c == '}')? + 1,
+                '}' => 1,
+                c => {
+                    fragment.push(c);
+                    column += 1;
+                    continue;
+                }
+            };
+            flush(&mut builder, &mut fragment, lineno, fragment_start);
+            column += skip;
+            fragment_start = column;
+        }
+
+        let chunk = builder.build();
+        if is_prose(chunk.as_str()) {
+            Some(chunk)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// All fragments of the chunk with the line and the columns they are located at.
+    fn fragments(chunk: &CheckableChunk) -> Vec<(String, usize, usize, usize)> {
+        chunk
+            .iter()
+            .map(|(range, span)| {
+                (
+                    crate::util::sub_chars(chunk.as_str(), range.clone()),
+                    span.start.line,
+                    span.start.column,
+                    span.end.column,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn placeholders() {
+        const SOURCE: &str = r#"fn main() {
+    println!("Found {} filse in {dir:?}, {{braces}}\tdone", n, dir = d);
+}
+"#;
+        let chunk = CheckableChunk::from_format_string(
+            SOURCE,
+            LineColumn {
+                line: 2,
+                column: 13,
+            },
+        )
+        .unwrap();
+        assert_eq!(chunk.as_str(), "Found \n filse in \n, \nbraces\ndone");
+        assert_eq!(
+            fragments(&chunk),
+            vec![
+                ("Found ".to_owned(), 2, 14, 19),
+                (" filse in ".to_owned(), 2, 22, 31),
+                (", ".to_owned(), 2, 39, 40),
+                ("braces".to_owned(), 2, 43, 48),
+                ("done".to_owned(), 2, 53, 56),
+            ]
+        );
+    }
+
+    #[test]
+    fn multiline_and_raw() {
+        const SOURCE: &str = "    info!(r#\"Raw \"quoted\"\ntext {}\"#);\n";
+        let chunk = CheckableChunk::from_format_string(
+            SOURCE,
+            LineColumn {
+                line: 1,
+                column: 10,
+            },
+        )
+        .unwrap();
+        assert_eq!(chunk.as_str(), "Raw \"quoted\"\ntext ");
+        assert_eq!(
+            fragments(&chunk),
+            vec![
+                ("Raw \"quoted\"".to_owned(), 1, 13, 24),
+                ("text ".to_owned(), 2, 0, 4),
+            ]
+        );
+
+        const CONTINUED: &str = "eprintln!(\"Line one \\\n    continues here\");\n";
+        let chunk = CheckableChunk::from_format_string(
+            CONTINUED,
+            LineColumn {
+                line: 1,
+                column: 10,
+            },
+        )
+        .unwrap();
+        assert_eq!(chunk.as_str(), "Line one \ncontinues here");
+    }
+
+    #[test]
+    fn not_prose() {
+        for source in &[
+            r#"println!("{}: {:?}", a, b);"#,
+            r#"println!("{}", a);"#,
+            r#"println!("ok");"#,
+            r#"println!("x = {x}, y = {y}");"#,
+        ] {
+            assert!(
+                CheckableChunk::from_format_string(source, LineColumn { line: 1, column: 9 })
+                    .is_none(),
+                "{} must not be prose",
+                source
+            );
+        }
+    }
+}
@@ -50,6 +50,35 @@ impl LiteralSet {
     }
 
     pub fn into_chunk(self) -> crate::documentation::CheckableChunk {
+        self.into_joined_chunk(false)
+    }
+
+    /// Number of leading characters of `next` to skip, if a word split by a
+    /// trailing hyphen of `literal` continues on `next`, and both are joined.
+    ///
+    /// Only applies to single line literals, as `///` comments are.
+    fn hyphenated_continuation(literal: &TrimmedLiteral, next: &TrimmedLiteral) -> Option<usize> {
+        if literal.span().one_line_len().is_none() || next.span().one_line_len().is_none() {
+            return None;
+        }
+        let mut tail = literal.as_str().chars().rev();
+        match (tail.next(), tail.next()) {
+            (Some('-'), Some(c)) if c.is_alphabetic() => {}
+            _ => return None,
+        }
+        let indent = next.chars().take_while(|c| c.is_whitespace()).count();
+        match next.chars().nth(indent) {
+            Some(c) if c.is_lowercase() => Some(indent),
+            _ => None,
+        }
+    }
+
+    /// Convert into a chunk, where literals are joined by newlines.
+    ///
+    /// With `join_hyphenated`, a word split across two lines by a trailing
+    /// hyphen is joined instead, dropping the hyphen and the indentation of
+    /// the following line, so i.e. `valid-` and `ated` become `validated`.
+    pub fn into_joined_chunk(self, join_hyphenated: bool) -> crate::documentation::CheckableChunk {
         let n = self.len();
         let mut source_mapping = indexmap::IndexMap::with_capacity(n);
         let mut content = String::with_capacity(n * 120);
@@ -59,14 +88,28 @@ impl LiteralSet {
             // for use with `Range`
             let mut start; // inclusive
             let mut end; // exclusive
+                         // leading characters of the current literal to skip
+            let mut skip_front = 0usize;
             let mut it = self.literals.iter();
             let mut next = it.next();
             while let Some(literal) = next {
+                next = it.next();
+                let continuation = next
+                    .filter(|_| join_hyphenated)
+                    .and_then(|next| Self::hyphenated_continuation(literal, next));
+                // the trailing hyphen is dropped
+                let skip_back = if continuation.is_some() { 1 } else { 0 };
+                let len = literal.len_in_chars() - skip_front - skip_back;
+
                 start = cursor;
-                cursor += literal.len_in_chars();
+                cursor += len;
                 end = cursor;
 
-                let span = literal.span();
+                let mut span = literal.span();
+                if skip_front + skip_back > 0 {
+                    span.start.column += skip_front;
+                    span.end.column -= skip_back;
+                }
                 let range = Range { start, end };
 
                 if let Some(span_len) = span.one_line_len() {
@@ -74,10 +117,13 @@ impl LiteralSet {
                 }
                 // keep zero length values too, to guarantee continuity
                 source_mapping.insert(range, span);
-                content.push_str(literal.as_str());
+                content.push_str(&crate::util::sub_chars(
+                    literal.as_str(),
+                    skip_front..(skip_front + len),
+                ));
+                skip_front = continuation.unwrap_or(0);
                 // the newline is _not_ covered by a span, after all it's inserted by us!
-                next = it.next();
-                if next.is_some() {
+                if next.is_some() && continuation.is_none() {
                     // for the last, skip the newline
                     content.push('\n');
                     cursor += 1;
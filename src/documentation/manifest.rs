@@ -0,0 +1,285 @@
+//! Extract the prose of a cargo manifest
+//!
+//! The `description` and the `keywords` of the `[package]` table are
+//! displayed on crates.io, both are mapped back to the `Span` they
+//! originate from within the `Cargo.toml`.
+//!
+//! All four kinds of toml strings are supported, escape sequences are
+//! retained as they are, so the column mapping stays exact.
+
+use super::*;
+
+use crate::documentation::chunk::ChunkBuilder;
+
+/// Find the `key` within the `[package]` table, returns the 0-indexed
+/// line and the character column at which its value starts.
+fn find_value(lines: &[&str], key: &str) -> Option<(usize, usize)> {
+    let mut in_package = false;
+    for (lineno, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('[') {
+            in_package = trimmed
+                .trim_end()
+                .strip_prefix('[')
+                .and_then(|header| header.split('#').next())
+                .map(|header| header.trim_end().trim_end_matches(']').trim() == "package")
+                .unwrap_or(false);
+            continue;
+        }
+        if !in_package {
+            continue;
+        }
+        let rest = if let Some(rest) = trimmed.strip_prefix(key) {
+            rest.trim_start()
+        } else {
+            continue;
+        };
+        // `description.workspace = true` and similar are not strings
+        let value = if let Some(value) = rest.strip_prefix('=') {
+            value.trim_start()
+        } else {
+            continue;
+        };
+        let column = line.chars().count() - value.chars().count();
+        return Some((lineno, column));
+    }
+    None
+}
+
+/// Parse the toml string starting at `lineno` (0-indexed) and `column`,
+/// appending its content to `builder`.
+///
+/// Returns the position right after the closing delimiter or `None` if
+/// there is no string or it is never terminated.
+fn string(
+    lines: &[&str],
+    mut lineno: usize,
+    column: usize,
+    builder: &mut ChunkBuilder,
+) -> Option<(usize, usize)> {
+    let first = lines.get(lineno)?.chars().skip(column).collect::<String>();
+    let delimiter = ["\"\"\"", "'''", "\"", "'"]
+        .iter()
+        .find(|delimiter| first.starts_with(*delimiter))?;
+    let delimiter = delimiter.chars().collect::<Vec<char>>();
+    let multiline = delimiter.len() == 3;
+    let escapes = delimiter[0] == '"';
+
+    let mut column = column + delimiter.len();
+    let mut chars = lines[lineno].chars().collect::<Vec<char>>();
+    // a newline immediately following the opening delimiter is trimmed
+    if multiline && chars[column..].is_empty() {
+        lineno += 1;
+        column = 0;
+        chars = lines.get(lineno)?.chars().collect();
+    }
+
+    let mut fragment = String::new();
+    let mut fragment_start = column;
+    loop {
+        if column >= chars.len() {
+            if !multiline {
+                return None;
+            }
+            builder.push(fragment.as_str(), lineno + 1, fragment_start);
+            builder.newline();
+            fragment.clear();
+            lineno += 1;
+            column = 0;
+            fragment_start = 0;
+            chars = lines.get(lineno)?.chars().collect();
+            continue;
+        }
+        if chars[column..].starts_with(&delimiter) {
+            builder.push(fragment.as_str(), lineno + 1, fragment_start);
+            return Some((lineno, column + delimiter.len()));
+        }
+        let c = chars[column];
+        if escapes && c == '\\' {
+            if multiline && chars[column + 1..].iter().all(|c| c.is_whitespace()) {
+                // a line ending backslash trims all whitespace up to the next content
+                builder.push(fragment.as_str(), lineno + 1, fragment_start);
+                fragment.clear();
+                loop {
+                    lineno += 1;
+                    chars = lines.get(lineno)?.chars().collect();
+                    column = chars.iter().take_while(|c| c.is_whitespace()).count();
+                    if column < chars.len() {
+                        break;
+                    }
+                }
+                fragment_start = column;
+                continue;
+            }
+            // retain the escape sequence as it is
+            fragment.extend(chars[column..].iter().take(2));
+            column += 2;
+            continue;
+        }
+        fragment.push(c);
+        column += 1;
+    }
+}
+
+impl CheckableChunk {
+    /// Extract the `description` of the `[package]` table of a manifest.
+    pub fn from_manifest_description(content: &str) -> Option<Self> {
+        let lines = content.lines().collect::<Vec<_>>();
+        let (lineno, column) = find_value(&lines, "description")?;
+        let mut builder = ChunkBuilder::new();
+        string(&lines, lineno, column, &mut builder)?;
+        if builder.is_empty() {
+            None
+        } else {
+            Some(builder.build())
+        }
+    }
+
+    /// Extract the `keywords` of the `[package]` table of a manifest,
+    /// one keyword per line.
+    pub fn from_manifest_keywords(content: &str) -> Option<Self> {
+        let lines = content.lines().collect::<Vec<_>>();
+        let (mut lineno, mut column) = find_value(&lines, "keywords")?;
+        if lines[lineno].chars().nth(column) != Some('[') {
+            return None;
+        }
+        column += 1;
+        let mut builder = ChunkBuilder::new();
+        loop {
+            let c = if let Some(c) = lines.get(lineno)?.chars().nth(column) {
+                c
+            } else {
+                lineno += 1;
+                column = 0;
+                continue;
+            };
+            match c {
+                ']' => break,
+                '#' => {
+                    // comment until the end of the line
+                    lineno += 1;
+                    column = 0;
+                }
+                '"' | '\'' => {
+                    if !builder.is_empty() {
+                        builder.newline();
+                    }
+                    let (end_lineno, end_column) = string(&lines, lineno, column, &mut builder)?;
+                    lineno = end_lineno;
+                    column = end_column;
+                }
+                c if c == ',' || c.is_whitespace() => column += 1,
+                _ => return None,
+            }
+        }
+        if builder.is_empty() {
+            None
+        } else {
+            Some(builder.build())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// All fragments of the chunk with the line and the columns they are located at.
+    fn fragments(chunk: &CheckableChunk) -> Vec<(String, usize, usize, usize)> {
+        chunk
+            .iter()
+            .map(|(range, span)| {
+                (
+                    crate::util::sub_chars(chunk.as_str(), range.clone()),
+                    span.start.line,
+                    span.start.column,
+                    span.end.column,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn description() {
+        const MANIFEST: &str = r#"[package]
+name = "demo"
+description = "A silly dmeo"
+
+[dependencies]
+description = "not this one"
+"#;
+        let chunk = CheckableChunk::from_manifest_description(MANIFEST).unwrap();
+        assert_eq!(chunk.as_str(), "A silly dmeo");
+        assert_eq!(
+            fragments(&chunk),
+            vec![("A silly dmeo".to_owned(), 3, 15, 26)]
+        );
+
+        let literal = "[package]\n  description =   'Itself'\n";
+        assert_eq!(
+            fragments(&CheckableChunk::from_manifest_description(literal).unwrap()),
+            vec![("Itself".to_owned(), 2, 19, 24)]
+        );
+
+        let workspace = "[package]\ndescription.workspace = true\n";
+        assert!(CheckableChunk::from_manifest_description(workspace).is_none());
+        let elsewhere = "[lib]\ndescription = \"no\"\n";
+        assert!(CheckableChunk::from_manifest_description(elsewhere).is_none());
+    }
+
+    #[test]
+    fn description_multiline() {
+        const MANIFEST: &str = r#"[package]
+description = """
+A silly \"demo\"
+  spanning lines, \
+    joined"""
+"#;
+        let chunk = CheckableChunk::from_manifest_description(MANIFEST).unwrap();
+        assert_eq!(
+            chunk.as_str(),
+            "A silly \\\"demo\\\"\n  spanning lines, joined"
+        );
+        assert_eq!(
+            fragments(&chunk),
+            vec![
+                ("A silly \\\"demo\\\"".to_owned(), 3, 0, 15),
+                ("  spanning lines, ".to_owned(), 4, 0, 17),
+                ("joined".to_owned(), 5, 4, 9),
+            ]
+        );
+
+        const LITERAL: &str = "[package]\ndescription = '''Raw\ntext'''\n";
+        let chunk = CheckableChunk::from_manifest_description(LITERAL).unwrap();
+        assert_eq!(chunk.as_str(), "Raw\ntext");
+        assert_eq!(
+            fragments(&chunk),
+            vec![("Raw".to_owned(), 2, 17, 19), ("text".to_owned(), 3, 0, 3)]
+        );
+
+        assert!(
+            CheckableChunk::from_manifest_description("[package]\ndescription = \"open\n")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn keywords() {
+        const MANIFEST: &str = r#"[package]
+keywords = ["spelling", 'grammar', # comment
+    "dcos",
+]
+"#;
+        let chunk = CheckableChunk::from_manifest_keywords(MANIFEST).unwrap();
+        assert_eq!(chunk.as_str(), "spelling\ngrammar\ndcos");
+        assert_eq!(
+            fragments(&chunk),
+            vec![
+                ("spelling".to_owned(), 2, 13, 20),
+                ("grammar".to_owned(), 2, 25, 31),
+                ("dcos".to_owned(), 3, 5, 8),
+            ]
+        );
+        assert!(CheckableChunk::from_manifest_keywords("[package]\nkeywords = []\n").is_none());
+    }
+}
@@ -6,12 +6,44 @@ use super::*;
 
 use indexmap::IndexMap;
 use log::trace;
-use pulldown_cmark::{Event, Options, Parser, Tag};
+use pulldown_cmark::{Event, LinkType, Options, Parser, Tag};
 
+use crate::config::MarkdownConfig;
 use crate::documentation::{CheckableChunk, Range};
 use crate::util::sub_chars;
 use crate::Span;
 
+/// Html elements whose content is not prose.
+const HTML_SKIPPED_ELEMENTS: &[&str] = &["code", "pre", "script", "style", "kbd", "samp"];
+
+/// Html elements which break the line.
+const HTML_LINE_BREAKS: &[&str] = &["br", "hr"];
+
+/// Raw html spans multiple events, the parser yields html blocks line by
+/// line and inline html tag by tag, so the state is carried across them.
+#[derive(Debug, Default)]
+struct HtmlState {
+    /// Within `<!-- .. -->`.
+    comment: bool,
+    /// Within one of the [`HTML_SKIPPED_ELEMENTS`].
+    skipped: Option<String>,
+}
+
+/// Markers which the parser leaves as text if they are not closed.
+const EMPHASIS_MARKERS: &[char] = &['*', '`'];
+
+/// The character offset of every byte offset of `s`, including `s.len()`.
+fn char_offsets(s: &str) -> Vec<usize> {
+    let mut offsets = Vec::with_capacity(s.len() + 1);
+    let mut count = 0;
+    for c in s.chars() {
+        offsets.extend(std::iter::repeat(count).take(c.len_utf8()));
+        count += 1;
+    }
+    offsets.push(count);
+    offsets
+}
+
 /// A plain representation of markdown riddled set of trimmed literals.
 #[derive(Clone)]
 pub struct PlainOverlay<'a> {
@@ -21,6 +53,9 @@ pub struct PlainOverlay<'a> {
     // key: plain string range
     // value: the corresponding areas in the full markdown
     mapping: IndexMap<Range, Range>,
+    /// The first unbalanced emphasis or code marker and its character
+    /// offset within the markdown.
+    unbalanced: Option<(char, usize)>,
 }
 
 impl<'a> PlainOverlay<'a> {
@@ -40,6 +75,112 @@ impl<'a> PlainOverlay<'a> {
         plain.push_str(&s);
     }
 
+    /// Track the `title` of a link or image, which is located somewhere
+    /// within the `markdown` range of the whole link.
+    fn track_title(
+        title: &str,
+        markdown: &str,
+        link: Range,
+        plain: &mut String,
+        mapping: &mut IndexMap<Range, Range>,
+    ) {
+        if title.is_empty() {
+            return;
+        }
+        let idx = if let Some(idx) = markdown.get(link.clone()).and_then(|s| s.rfind(title)) {
+            idx
+        } else {
+            // i.e. a title with escape sequences, which can not be mapped
+            trace!("Failed to locate link title >{}< in {:?}", title, &link);
+            return;
+        };
+        let start = link.start + idx;
+        Self::newlines(plain, 1);
+        Self::track(title, start..(start + title.len()), plain, mapping);
+        Self::newlines(plain, 1);
+    }
+
+    /// Track the text between the tags of the raw `html`, located at
+    /// `markdown`, skipping comments and elements which are not prose.
+    fn track_html(
+        html: &str,
+        markdown: Range,
+        state: &mut HtmlState,
+        plain: &mut String,
+        mapping: &mut IndexMap<Range, Range>,
+    ) {
+        // html blocks are yielded line by line, including the line ending
+        let line = html.trim_end_matches(|c| c == '\n' || c == '\r');
+        let line_ending = line.len() < html.len();
+
+        let mut text_start = None;
+        let mut flush = |text_start: &mut Option<usize>, idx: usize, plain: &mut String| {
+            if let Some(start) = text_start.take() {
+                let text = &line[start..idx];
+                if !text.trim().is_empty() {
+                    let start = markdown.start + start;
+                    Self::track(text, start..(start + text.len()), plain, mapping);
+                }
+            }
+        };
+        let mut idx = 0;
+        while idx < line.len() {
+            let rest = &line[idx..];
+            if state.comment {
+                if let Some(end) = rest.find("-->") {
+                    state.comment = false;
+                    idx += end + 3;
+                } else {
+                    idx = line.len();
+                }
+                continue;
+            }
+            if rest.starts_with("<!--") {
+                flush(&mut text_start, idx, plain);
+                state.comment = true;
+                idx += 4;
+                continue;
+            }
+            if rest.starts_with('<') {
+                if let Some(end) = rest.find('>') {
+                    flush(&mut text_start, idx, plain);
+                    let tag = &rest[1..end];
+                    let closing = tag.starts_with('/');
+                    let self_closing = tag.ends_with('/');
+                    let name = tag
+                        .trim_start_matches('/')
+                        .split(|c: char| c.is_whitespace() || c == '/')
+                        .next()
+                        .unwrap_or_default()
+                        .to_lowercase();
+                    if HTML_LINE_BREAKS.contains(&name.as_str()) {
+                        Self::newlines(plain, 1);
+                    } else if closing {
+                        if state.skipped.as_deref() == Some(name.as_str()) {
+                            state.skipped = None;
+                        }
+                    } else if !self_closing
+                        && state.skipped.is_none()
+                        && HTML_SKIPPED_ELEMENTS.contains(&name.as_str())
+                    {
+                        state.skipped = Some(name);
+                    }
+                    idx += end + 1;
+                    continue;
+                }
+            }
+            if text_start.is_none() && state.skipped.is_none() {
+                text_start = Some(idx);
+            }
+            idx += rest.chars().next().map(char::len_utf8).unwrap_or(1);
+        }
+        flush(&mut text_start, line.len(), plain);
+        // lines without any text do not add up to blank lines
+        if line_ending && !plain.is_empty() && !plain.ends_with('\n') {
+            Self::newlines(plain, 1);
+        }
+    }
+
     fn newlines(plain: &mut String, n: usize) {
         for _ in 0..n {
             plain.push('\n');
@@ -47,26 +188,64 @@ impl<'a> PlainOverlay<'a> {
     }
 
     /// ranges are mapped `cmakr reduced/plain -> raw`
-    fn extract_plain_with_mapping(markdown: &str) -> (String, IndexMap<Range, Range>) {
+    fn extract_plain_with_mapping(
+        markdown: &str,
+        config: &MarkdownConfig,
+    ) -> (String, IndexMap<Range, Range>, Option<(char, usize)>) {
         let mut plain = String::with_capacity(markdown.chars().count());
         let mut mapping = indexmap::IndexMap::with_capacity(128);
 
-        let parser = Parser::new_ext(markdown, Options::all());
+        // references without a definition, i.e. rustdoc's `[text][crate::Item]`,
+        // are treated as links, so only the link text is retained
+        let broken_link_callback =
+            |_normalized: &str, _raw: &str| Some((String::new(), String::new()));
+        let parser = Parser::new_with_broken_link_callback(
+            markdown,
+            Options::all(),
+            Some(&broken_link_callback),
+        );
 
         let rust_fence =
             pulldown_cmark::CodeBlockKind::Fenced(pulldown_cmark::CowStr::Borrowed("rust"));
 
         let mut code_block = false;
+        // the text of autolinks is the url itself, which is not prose
+        let mut autolink = false;
+        let mut html = HtmlState::default();
+        // nesting depth of blockquotes, the parser strips the `>` markers
+        let mut blockquote = 0usize;
+        let skip_blockquotes = !config.check_blockquotes();
+        // the paragraph, heading, list item or table cell currently parsed
+        let mut block = 0..markdown.len();
+        let mut unbalanced = None;
 
         for (event, offset) in parser.into_offset_iter() {
             trace!("Parsing event ({:?}): {:?}", &offset, &event);
+            match event {
+                Event::Start(Tag::BlockQuote) => blockquote += 1,
+                Event::End(Tag::BlockQuote) => blockquote = blockquote.saturating_sub(1),
+                Event::Start(Tag::CodeBlock(_)) => code_block = true,
+                Event::End(Tag::CodeBlock(_)) => code_block = false,
+                Event::Start(Tag::Paragraph)
+                | Event::Start(Tag::Heading(_))
+                | Event::Start(Tag::Item)
+                | Event::Start(Tag::TableCell) => block = offset.clone(),
+                Event::Text(ref s) if !code_block && unbalanced.is_none() => {
+                    unbalanced = Self::unbalanced_marker(markdown, s, offset.start, &block);
+                }
+                _ => {}
+            }
+            if skip_blockquotes && blockquote > 0 {
+                continue;
+            }
             match event {
                 Event::Start(tag) => {
                     // @todo check links
                     match tag {
+                        Tag::Link(LinkType::Autolink, _, _) | Tag::Link(LinkType::Email, _, _) => {
+                            autolink = true;
+                        }
                         Tag::CodeBlock(fenced) => {
-                            code_block = true;
-
                             if fenced == rust_fence {
                                 // @todo validate as if it was another document entity
                             }
@@ -77,19 +256,21 @@ impl<'a> PlainOverlay<'a> {
                 }
                 Event::End(tag) => {
                     match tag {
+                        Tag::Link(LinkType::Autolink, _, _) | Tag::Link(LinkType::Email, _, _) => {
+                            autolink = false;
+                        }
                         Tag::Link(_link_type, _url, title) => {
                             // @todo check links
-                            Self::track(&title, offset, &mut plain, &mut mapping);
+                            Self::track_title(&title, markdown, offset, &mut plain, &mut mapping);
                         }
                         Tag::Image(_link_type, _url, title) => {
-                            Self::track(&title, offset, &mut plain, &mut mapping);
+                            Self::track_title(&title, markdown, offset, &mut plain, &mut mapping);
                         }
                         Tag::Heading(_n) => {
                             Self::newlines(&mut plain, 2);
                         }
-                        Tag::CodeBlock(fenced) => {
-                            code_block = false;
 
+                        Tag::CodeBlock(fenced) => {
                             if fenced == rust_fence {
                                 // @todo validate as if it was another document entity
                             }
@@ -99,7 +280,7 @@ impl<'a> PlainOverlay<'a> {
                     }
                 }
                 Event::Text(s) => {
-                    if code_block {
+                    if code_block || autolink || html.skipped.is_some() || html.comment {
                     } else {
                         Self::track(&s, offset, &mut plain, &mut mapping);
                     }
@@ -109,7 +290,9 @@ impl<'a> PlainOverlay<'a> {
                     // future potentially also check var names with leviatan distance
                     // to wordbook entries, and only complain if there are sane suggestions
                 }
-                Event::Html(_s) => {}
+                Event::Html(s) => {
+                    Self::track_html(&s, offset, &mut html, &mut plain, &mut mapping);
+                }
                 Event::FootnoteReference(_s) => {
                     // @todo handle footnotes
                 }
@@ -132,27 +315,125 @@ impl<'a> PlainOverlay<'a> {
         if trailing_newlines <= plain.len() {
             plain.truncate(plain.len() - trailing_newlines)
         }
+        let plain_len = plain.chars().count();
         if let Some((mut plain_range, raw_range)) = mapping.pop() {
-            if plain_range.end > plain.len() {
-                plain_range.end = plain.len();
+            if plain_range.end > plain_len {
+                plain_range.end = plain_len;
             }
             assert!(plain_range.start <= plain_range.end);
             mapping.insert(plain_range, raw_range);
         }
-        (plain, mapping)
+
+        // the parser yields byte offsets, but chunks are indexed by characters
+        let chars = char_offsets(markdown);
+        let mapping = mapping
+            .into_iter()
+            .map(|(plain_range, raw_range)| {
+                (plain_range, chars[raw_range.start]..chars[raw_range.end])
+            })
+            .collect();
+        let unbalanced = unbalanced.map(|(marker, offset)| (marker, chars[offset]));
+        (plain, mapping, unbalanced)
+    }
+
+    /// Find the first emphasis or code marker within the `text` at byte
+    /// offset `offset`, which is not closed and thus ends up as text, i.e.
+    /// the `*` of `*bold` or the backtick of `` `code ``, which indicates
+    /// the markdown is not parsed as intended. The `block` is the range of
+    /// the enclosing paragraph or the like.
+    ///
+    /// Escaped markers are fine, and so is a `*` which does not open any
+    /// emphasis, as in `2 * 3`, `x*y` or a lone `*const T`. Returns the
+    /// marker and its byte offset.
+    fn unbalanced_marker(
+        markdown: &str,
+        text: &str,
+        offset: usize,
+        block: &Range,
+    ) -> Option<(char, usize)> {
+        for (idx, c) in text.char_indices() {
+            if !EMPHASIS_MARKERS.contains(&c) {
+                continue;
+            }
+            let byte_offset = offset + idx;
+            let mut before = markdown[..byte_offset].chars().rev();
+            let previous = before.next();
+            let escaped =
+                previous == Some('\\') && before.take_while(|c| *c == '\\').count() % 2 == 0;
+            if escaped {
+                continue;
+            }
+            if c == '*' {
+                let after = byte_offset + c.len_utf8();
+                let next = markdown[after..].chars().next();
+                let opens = next.map(|c| !c.is_whitespace()).unwrap_or(false)
+                    && !previous.map(char::is_alphanumeric).unwrap_or(false);
+                // without any `*` to close it, it is rather a pointer or a
+                // glob than emphasis
+                let rest = markdown
+                    .get(after..block.end.max(after))
+                    .unwrap_or_default();
+                let mut previous = c;
+                let closable = rest.chars().any(|c| {
+                    let closing = c == '*' && !previous.is_whitespace();
+                    previous = c;
+                    closing
+                });
+                if !opens || !closable {
+                    continue;
+                }
+            }
+            return Some((c, byte_offset));
+        }
+        None
     }
 
     // @todo consider returning a Vec<PlainOverlay<'a>> to account for list items
     // or other non-linear information which might not pass a grammar check as a whole
     pub fn erase_markdown(chunk: &'a CheckableChunk) -> Self {
-        let (plain, mapping) = Self::extract_plain_with_mapping(chunk.as_str());
+        Self::erase_markdown_with(chunk, &Default::default())
+    }
+
+    /// Erase the markdown of `chunk`, reduced to prose as configured.
+    ///
+    /// If the markdown contains an unbalanced emphasis or code marker and
+    /// `raw_fallback` is configured, the raw text is used as it is, as is
+    /// the content of plain text chunks.
+    pub fn erase_markdown_with(chunk: &'a CheckableChunk, config: &MarkdownConfig) -> Self {
+        let raw = || {
+            let len = chunk.as_str().chars().count();
+            let mut mapping = IndexMap::with_capacity(1);
+            mapping.insert(0..len, 0..len);
+            (chunk.as_str().to_owned(), mapping)
+        };
+        let (plain, mapping, unbalanced) = if chunk.is_plain_text() {
+            let (plain, mapping) = raw();
+            (plain, mapping, None)
+        } else {
+            match Self::extract_plain_with_mapping(chunk.as_str(), config) {
+                (_plain, _mapping, Some(marker)) if config.raw_fallback() => {
+                    let (plain, mapping) = raw();
+                    (plain, mapping, Some(marker))
+                }
+                extracted => extracted,
+            }
+        };
         Self {
             raw: chunk,
             plain,
             mapping,
+            unbalanced,
         }
     }
 
+    /// The first unbalanced emphasis or code marker and its location.
+    pub fn unbalanced(&self) -> Option<(char, Span)> {
+        let (marker, offset) = self.unbalanced?;
+        let spans = self.raw.find_spans(offset..(offset + 1));
+        let (_range, span) = spans.into_iter().next()?;
+        Some((marker, span))
+    }
+
     /// Since most checkers will operate on the plain data, an indirection to map cmark reduced / plain
     /// back to raw ranges, which are then mapped back to `Span`s.
     /// The returned key `Ranges` are in the condensed domain.
@@ -220,9 +501,32 @@ impl<'a> PlainOverlay<'a> {
             })
     }
 
+    /// A single span covering `condensed_range`, to replace a word as a whole.
+    ///
+    /// A word joined across lines maps to multiple spans, which would each
+    /// receive the full replacement, so the span reaches from the start of
+    /// the first to the end of the last one instead.
+    pub fn find_span(&self, condensed_range: Range) -> Option<(Range, Span)> {
+        let spans = self.find_spans(condensed_range);
+        let (first_range, first_span) = spans.iter().next()?;
+        let (last_range, last_span) = spans.iter().next_back()?;
+        Some((
+            first_range.start..last_range.end,
+            Span {
+                start: first_span.start,
+                end: last_span.end,
+            },
+        ))
+    }
+
     pub fn as_str(&self) -> &str {
         self.plain.as_str()
     }
+
+    /// Ranges of the plain text and the markdown ranges they originate from.
+    pub fn mapping(&self) -> &IndexMap<Range, Range> {
+        &self.mapping
+    }
 }
 
 use std::fmt;
@@ -326,7 +630,8 @@ Extra ~pagaph~ paragraph.
 
 
 And a line, or a rule."##;
-        let (reduced, mapping) = PlainOverlay::extract_plain_with_mapping(MARKDOWN);
+        let (reduced, mapping, _unbalanced) =
+            PlainOverlay::extract_plain_with_mapping(MARKDOWN, &Default::default());
 
         assert_eq!(dbg!(&reduced).as_str(), PLAIN);
         assert_eq!(dbg!(&mapping).len(), 19);
@@ -343,7 +648,8 @@ And a line, or a rule."##;
         const MARKDOWN: &str = r#"  Some __underlined__ **bold** text."#;
         const PLAIN: &str = r#"Some underlined bold text."#;
 
-        let (reduced, mapping) = PlainOverlay::extract_plain_with_mapping(MARKDOWN);
+        let (reduced, mapping, _unbalanced) =
+            PlainOverlay::extract_plain_with_mapping(MARKDOWN, &Default::default());
 
         assert_eq!(dbg!(&reduced).as_str(), PLAIN);
         assert_eq!(dbg!(&mapping).len(), 5);
@@ -355,6 +661,145 @@ And a line, or a rule."##;
         }
     }
 
+    /// Plain text of `markdown`, asserting all mapped ranges match.
+    fn plain(markdown: &str) -> String {
+        let (reduced, mapping, _unbalanced) =
+            PlainOverlay::extract_plain_with_mapping(markdown, &Default::default());
+        for (reduced_range, markdown_range) in mapping.iter() {
+            assert_eq!(
+                sub_chars(&reduced, reduced_range.clone()),
+                sub_chars(markdown, markdown_range.clone())
+            );
+        }
+        reduced
+    }
+
+    #[test]
+    fn links() {
+        assert_eq!(
+            plain("A [visible text](https://example.com/dest) end."),
+            "A visible text end."
+        );
+        assert_eq!(
+            plain("A [visible][label] end.\n\n[label]: https://example.com/dest"),
+            "A visible end."
+        );
+        assert_eq!(
+            plain("A [visible] end.\n\n[visible]: dest"),
+            "A visible end."
+        );
+        // without a definition, as with rustdoc's intra doc links
+        assert_eq!(plain("A [visible][crate::Struct] end."), "A visible end.");
+        assert_eq!(
+            plain("See <https://example.com/dest> or <mail@example.com>."),
+            "See  or ."
+        );
+        // alt text is prose
+        assert_eq!(
+            plain("An ![alt text](image.png) here."),
+            "An alt text here."
+        );
+        // as is the title, which must map to its own location
+        assert_eq!(
+            plain(r#"A [link](https://example.com/dest "The title") here."#),
+            "A link\nThe title\n here."
+        );
+    }
+
+    #[test]
+    fn html() {
+        assert_eq!(plain("Some <b>bold</b> text."), "Some bold text.");
+        assert_eq!(
+            plain("<p>Some prose</p>\n<p>and <i>more</i></p>\n"),
+            "Some prose\nand more"
+        );
+        assert_eq!(plain("Before <!-- a comment --> after."), "Before  after.");
+        assert_eq!(
+            plain("<div>\n<!--\nspanning\nlines\n-->\nVisible\n</div>\n"),
+            "Visible"
+        );
+        assert_eq!(
+            plain("Line<br/>break and <img src=\"x.png\"/> image."),
+            "Line\nbreak and  image."
+        );
+        assert_eq!(
+            plain("<p>Run <code>cargo test</code> now</p>\n"),
+            "Run  now"
+        );
+    }
+
+    #[test]
+    fn non_ascii() {
+        const MARKDOWN: &str = "*Grüße* wrold and über *naïve* `cöde` tset.";
+        assert_eq!(plain(MARKDOWN), "Grüße wrold and über naïve  tset.");
+        let (_reduced, mapping, _unbalanced) =
+            PlainOverlay::extract_plain_with_mapping(MARKDOWN, &Default::default());
+        assert_eq!(
+            mapping.values().cloned().collect::<Vec<_>>(),
+            vec![1..6, 7..23, 24..29, 30..31, 37..43]
+        );
+    }
+
+    #[test]
+    fn blockquotes() {
+        const MARKDOWN: &str = "Intro.\n\n> Quoted text\n> > nested quote\n\nOutro.";
+        assert_eq!(
+            plain(MARKDOWN),
+            "Intro.\n\nQuoted text\n\nnested quote\n\nOutro."
+        );
+        let skip = MarkdownConfig {
+            check_blockquotes: Some(false),
+            ..Default::default()
+        };
+        let (reduced, _mapping, _unbalanced) =
+            PlainOverlay::extract_plain_with_mapping(MARKDOWN, &skip);
+        assert_eq!(reduced, "Intro.\n\nOutro.");
+    }
+
+    #[test]
+    fn unbalanced() {
+        let unbalanced = |markdown: &str| {
+            PlainOverlay::extract_plain_with_mapping(markdown, &Default::default()).2
+        };
+        assert_eq!(unbalanced("Some *bold* and `code`."), None);
+        assert_eq!(unbalanced("Yields 2 * 3 and \\*stars\\*."), None);
+        assert_eq!(unbalanced("```\nlet a = `;\n```"), None);
+        assert_eq!(unbalanced("Computes x*y of a *const T and *mut T."), None);
+        assert_eq!(unbalanced("Some **bold text"), Some(('*', 5)));
+        assert_eq!(unbalanced("Über *bold `text* here`"), Some(('*', 5)));
+        assert_eq!(
+            unbalanced("Call `foo( with\nbaz`) and `bar"),
+            Some(('`', 26))
+        );
+
+        let chunk = CheckableChunk::from_str(
+            "A `mispeled cde",
+            indexmap::indexmap! { 0..15 => Span {
+                    start: LineColumn { line: 7, column: 4 },
+                    end: LineColumn { line: 7, column: 18 },
+                }
+            },
+        );
+        let plain = chunk.erase_markdown();
+        assert_eq!(
+            plain.unbalanced(),
+            Some((
+                '`',
+                Span {
+                    start: LineColumn { line: 7, column: 6 },
+                    end: LineColumn { line: 7, column: 6 },
+                }
+            ))
+        );
+        let raw = chunk.erase_markdown_with(&MarkdownConfig {
+            raw_fallback: Some(true),
+            ..Default::default()
+        });
+        assert_eq!(raw.as_str(), "A `mispeled cde");
+        let (_, span) = raw.find_spans(3..11).into_iter().next().unwrap();
+        assert_eq!(span.start, LineColumn { line: 7, column: 7 });
+    }
+
     #[test]
     fn range_test() {
         let mut x = IndexMap::<Range, Range>::new();
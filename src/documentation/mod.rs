@@ -14,20 +14,28 @@
 
 use super::*;
 
+use crate::config::MarkdownConfig;
 use indexmap::IndexMap;
 use log::trace;
 pub use proc_macro2::LineColumn;
 use proc_macro2::{Spacing, TokenTree};
-use std::convert::{TryFrom, TryInto};
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryInto;
+use std::path::{Path, PathBuf};
 
 pub type Range = core::ops::Range<usize>;
 
+mod asciidoc;
 mod chunk;
 mod cluster;
+mod formatstring;
 mod literal;
 pub(crate) mod literalset;
+mod manifest;
 mod markdown;
+mod plaintext;
+mod restructuredtext;
+mod toggle;
 
 pub use chunk::*;
 pub use cluster::*;
@@ -39,12 +47,30 @@ pub use markdown::*;
 pub struct Documentation {
     /// Mapping of a path to documentation literals
     index: IndexMap<ContentOrigin, Vec<CheckableChunk>>,
+    /// Words which are correct for a particular origin, in addition
+    /// to the ones known by the checkers.
+    accepted: HashMap<ContentOrigin, HashSet<String>>,
+    /// Origins skipped, since their content failed to parse, with the reason.
+    unparsable: Vec<(ContentOrigin, String)>,
+    /// Files included as documentation via `include_str!`, relative to
+    /// the directory of the including file.
+    includes: Vec<PathBuf>,
+    /// Inclusive, 1-indexed line ranges per file, in which checking was
+    /// disabled with `spellcheck: off`.
+    disabled: HashMap<PathBuf, Vec<(usize, usize)>>,
+    /// How the markdown of all chunks is reduced to prose.
+    markdown: MarkdownConfig,
 }
 
 impl Documentation {
     pub fn new() -> Self {
         Self {
             index: IndexMap::with_capacity(64),
+            accepted: HashMap::new(),
+            unparsable: Vec::new(),
+            includes: Vec::new(),
+            disabled: HashMap::new(),
+            markdown: MarkdownConfig::default(),
         }
     }
 
@@ -61,14 +87,67 @@ impl Documentation {
     }
 
     pub fn join(&mut self, other: Documentation) -> &mut Self {
-        other
+        let Documentation {
+            index,
+            accepted,
+            unparsable,
+            includes,
+            disabled,
+            // the configuration of `self` prevails
+            markdown: _,
+        } = other;
+        index
             .into_iter()
             .for_each(|(source, chunks): (_, Vec<CheckableChunk>)| {
                 let _ = self.add(source, chunks);
             });
+        accepted.into_iter().for_each(|(origin, words)| {
+            self.accept_words(&origin, words);
+        });
+        self.unparsable.extend(unparsable);
+        self.includes.extend(includes);
+        for (path, regions) in disabled {
+            self.disabled
+                .entry(path)
+                .or_insert_with(Vec::new)
+                .extend(regions);
+        }
         self
     }
 
+    /// A copy with only the chunks for which `predicate` returns `true`,
+    /// origins without any chunk left are dropped. Everything besides the
+    /// chunks is retained.
+    pub fn filter_chunks(
+        &self,
+        mut predicate: impl FnMut(&ContentOrigin, &CheckableChunk) -> bool,
+    ) -> Self {
+        let index = self
+            .index
+            .iter()
+            .filter_map(|(origin, chunks)| {
+                let chunks = chunks
+                    .iter()
+                    .filter(|chunk| predicate(origin, chunk))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if chunks.is_empty() {
+                    None
+                } else {
+                    Some((origin.clone(), chunks))
+                }
+            })
+            .collect();
+        Self {
+            index,
+            accepted: self.accepted.clone(),
+            unparsable: self.unparsable.clone(),
+            includes: self.includes.clone(),
+            disabled: self.disabled.clone(),
+            markdown: self.markdown.clone(),
+        }
+    }
+
     pub fn extend<I, J>(&mut self, docs: I)
     where
         I: IntoIterator<Item = Documentation, IntoIter = J>,
@@ -88,26 +167,190 @@ impl Documentation {
             .or_insert_with(|| chunks);
         // Ok(()) @todo make this failable
     }
+
+    /// Accept `words` as correct within `origin`.
+    pub fn accept_words(
+        &mut self,
+        origin: &ContentOrigin,
+        words: impl IntoIterator<Item = String>,
+    ) {
+        self.accepted
+            .entry(origin.clone())
+            .or_insert_with(HashSet::new)
+            .extend(words);
+    }
+
+    /// Accept `words` as correct within all origins.
+    pub fn accept_words_everywhere(&mut self, words: &[String]) {
+        let origins = self.index.keys().cloned().collect::<Vec<_>>();
+        for origin in origins.iter() {
+            self.accept_words(origin, words.iter().cloned());
+        }
+    }
+
+    /// Record that the content of `origin` could not be parsed and is not checked.
+    pub fn mark_unparsable(&mut self, origin: ContentOrigin, reason: String) {
+        self.unparsable.push((origin, reason));
+    }
+
+    /// All origins skipped, since their content failed to parse.
+    pub fn unparsable(&self) -> &[(ContentOrigin, String)] {
+        self.unparsable.as_slice()
+    }
+
+    /// Files included as documentation via `include_str!`.
+    pub fn includes(&self) -> &[PathBuf] {
+        self.includes.as_slice()
+    }
+
+    /// Record the regions of the file `path` in which checking is
+    /// disabled by `spellcheck: off` toggles within its `content`.
+    pub fn disable_regions(&mut self, path: &Path, content: &str) {
+        let regions = toggle::disabled_regions(content);
+        if !regions.is_empty() {
+            trace!("Checking disabled in {}: {:?}", path.display(), &regions);
+            self.disabled
+                .entry(path.to_owned())
+                .or_insert_with(Vec::new)
+                .extend(regions);
+        }
+    }
+
+    /// Check if checking is disabled for any of the lines `start..=end` of `path`.
+    pub fn is_disabled(&self, path: &Path, start: usize, end: usize) -> bool {
+        self.disabled
+            .get(path)
+            .map(|regions| {
+                regions
+                    .iter()
+                    .any(|(first, last)| *first <= end && start <= *last)
+            })
+            .unwrap_or(false)
+    }
+
+    /// Words which are accepted as correct within `origin`, those in lower
+    /// case regardless of the case they are used in.
+    pub fn accepted_words(&self, origin: &ContentOrigin) -> Option<&HashSet<String>> {
+        self.accepted.get(origin)
+    }
+
+    /// Reduce the markdown of all chunks to prose as configured.
+    pub fn configure_markdown(&mut self, config: MarkdownConfig) {
+        self.markdown = config;
+    }
+
+    /// Erase the markdown of `chunk`, which is part of the documentation,
+    /// as configured.
+    pub fn erase_markdown<'c>(&self, chunk: &'c CheckableChunk) -> PlainOverlay<'c> {
+        chunk.erase_markdown_with(&self.markdown)
+    }
+
+    /// Check if `chunk` may be nothing but code, i.e. a doc comment
+    /// consisting of a fenced code block only, which is the case if there is
+    /// no prose left once the markdown is erased.
+    ///
+    /// Only chunks which are blank or contain a code fence or an indented
+    /// line qualify, all others contain prose. None do if skipping such
+    /// chunks is not configured.
+    pub fn may_be_code_only(&self, chunk: &CheckableChunk) -> bool {
+        if !self.markdown.skip_code_only() || chunk.is_plain_text() {
+            return false;
+        }
+        let content = chunk.as_str();
+        content.trim().is_empty()
+            || content.contains("```")
+            || content.contains("~~~")
+            || content
+                .lines()
+                .any(|line| line.starts_with("    ") || line.starts_with('\t'))
+    }
+
+    /// Check the raw text of chunks with unbalanced markers.
+    pub fn raw_fallback(&self) -> bool {
+        self.markdown.raw_fallback()
+    }
+
+    /// Render the internals of all chunks, to debug mistakes pointing at
+    /// the wrong location.
+    ///
+    /// For each chunk, its content is listed with the spans of its fragments,
+    /// followed by the plain text after erasing the markdown and the chunk
+    /// ranges its parts map to. Ranges are in characters, spans as
+    /// `line:column` with lines starting at 1 and columns at 0.
+    pub fn dump_chunks(&self) -> String {
+        use std::fmt::Write;
+
+        let mut dump = String::new();
+        for (origin, chunks) in self.iter() {
+            let _ = writeln!(dump, "{} {}", origin.kind(), origin);
+            for (idx, chunk) in chunks.iter().enumerate() {
+                let _ = writeln!(dump, "  chunk {} of {}", idx + 1, chunks.len());
+                let _ = writeln!(dump, "    content: {:?}", chunk.as_str());
+                for (range, span) in chunk.iter() {
+                    let _ = writeln!(
+                        dump,
+                        "      {:?} {:?} -> {}:{}..{}:{}",
+                        range,
+                        crate::util::sub_chars(chunk.as_str(), range.clone()),
+                        span.start.line,
+                        span.start.column,
+                        span.end.line,
+                        span.end.column
+                    );
+                }
+                let plain = self.erase_markdown(chunk);
+                let _ = writeln!(dump, "    plain: {:?}", plain.as_str());
+                for (plain_range, range) in plain.mapping() {
+                    let _ = writeln!(
+                        dump,
+                        "      {:?} {:?} -> {:?}",
+                        plain_range,
+                        crate::util::sub_chars(plain.as_str(), plain_range.clone()),
+                        range
+                    );
+                }
+            }
+        }
+        dump
+    }
 }
 
-/// only a shortcut to avoid duplicate code
-impl From<(ContentOrigin, &str)> for Documentation {
-    fn from((origin, content): (ContentOrigin, &str)) -> Self {
+impl Documentation {
+    /// Extract the docs of the rust source `content`, including the string
+    /// literals configured in `config`.
+    pub fn load_from_str(
+        origin: ContentOrigin,
+        content: &str,
+        config: &crate::config::RustConfig,
+    ) -> Self {
         let mut docs = Documentation::new();
+        docs.disable_regions(origin.as_path(), content);
 
-        match Clusters::try_from(content) {
+        match Clusters::load_from_str(content, config) {
             Ok(cluster) => {
+                if let Some(dir) = origin.as_path().parent() {
+                    docs.includes
+                        .extend(cluster.includes.iter().map(|include| dir.join(include)));
+                }
                 let chunks = Vec::<CheckableChunk>::from(cluster);
                 docs.add(origin, chunks);
             }
             Err(e) => {
-                log::error!("BUG: Failed to create cluster from {}: {}", &origin, e);
+                log::warn!("Skipping {}, failed to parse: {}", &origin, e);
+                docs.mark_unparsable(origin, e.to_string());
             }
         }
         docs
     }
 }
 
+/// only a shortcut to avoid duplicate code
+impl From<(ContentOrigin, &str)> for Documentation {
+    fn from((origin, content): (ContentOrigin, &str)) -> Self {
+        Self::load_from_str(origin, content, &Default::default())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -117,6 +360,662 @@ mod tests {
 
     use std::convert::From;
 
+    #[test]
+    fn inner_attribute_include() {
+        let origin = ContentOrigin::RustSourceFile(PathBuf::from("/tmp/crate/src/lib.rs"));
+        let docs = Documentation::from((
+            origin.clone(),
+            r#"#![doc = include_str!("../README.md")]
+#![doc = include_str!(r"../docs/guide.md")]
+#![doc = "Inner attribute wrold"]
+
+/// Outer
+struct A;
+"#,
+        ));
+        assert_eq!(
+            docs.includes(),
+            &[
+                PathBuf::from("/tmp/crate/src/../README.md"),
+                PathBuf::from("/tmp/crate/src/../docs/guide.md"),
+            ]
+        );
+        let (_, chunks) = docs.iter().next().expect("Must contain the inline docs");
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].as_str(), "Inner attribute wrold");
+    }
+
+    #[test]
+    fn skip_marker() {
+        let origin = ContentOrigin::RustSourceFile(PathBuf::from("dummy/generated.rs"));
+        let docs = Documentation::from((
+            origin.clone(),
+            r#"//! Crate docs
+
+#[cfg_attr(spellcheck, spellcheck::skip)]
+/// Generatd wrold
+mod generated {}
+
+/// Sibling
+#[derive(Debug)]
+struct A;
+
+/// Skipped as well
+#[spellcheck::skip]
+struct B;
+
+#[cfg_attr(spellcheck, derive(Debug), spellcheck::skip)]
+/// Also skipped
+struct C;
+"#,
+        ));
+        let (_, chunks) = docs.iter().next().expect("Must contain the remaining docs");
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|chunk| chunk.as_str())
+                .collect::<Vec<_>>(),
+            vec![" Crate docs", " Sibling"]
+        );
+    }
+
+    #[test]
+    fn extra_doc_attributes() {
+        const SOURCE: &str = r#"/// Errors of the crate.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Reading failed.
+    #[error("Failed to raed the file")]
+    Read,
+    #[error("Invalid nmber {0}", some)]
+    Parse(String),
+    #[error(transparent)]
+    Other,
+    #[clap(long, about = "Verbose otuput")]
+    #[serde(rename = "not prose")]
+    Verbose,
+}
+"#;
+        let config = crate::config::RustConfig {
+            extra_doc_attributes: Some(vec![
+                crate::config::DocAttribute {
+                    path: "error".to_owned(),
+                    argument: None,
+                },
+                crate::config::DocAttribute {
+                    path: "clap".to_owned(),
+                    argument: Some("about".to_owned()),
+                },
+            ]),
+            ..Default::default()
+        };
+        let origin = ContentOrigin::RustSourceFile(PathBuf::from("/tmp/errors.rs"));
+        let docs = Documentation::load_from_str(origin.clone(), SOURCE, &config);
+        let chunks = docs.index.get(&origin).expect("Must contain the docs");
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|chunk| chunk.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                " Errors of the crate.",
+                " Reading failed.",
+                "Failed to raed the file",
+                "Invalid nmber {0}",
+                "Verbose otuput",
+            ]
+        );
+        let (_, span) = chunks[2].iter().next().unwrap();
+        assert_eq!(
+            span.start,
+            LineColumn {
+                line: 5,
+                column: 13
+            }
+        );
+        assert_eq!(
+            span.end,
+            LineColumn {
+                line: 5,
+                column: 35
+            }
+        );
+        let (_, span) = chunks[4].iter().next().unwrap();
+        assert_eq!(
+            span.start,
+            LineColumn {
+                line: 11,
+                column: 26
+            }
+        );
+
+        // without configuration, only the docs are extracted
+        let docs = Documentation::from((origin.clone(), SOURCE));
+        assert_eq!(docs.index.get(&origin).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn dump_chunks() {
+        let origin = ContentOrigin::RustSourceFile(PathBuf::from("/tmp/dump.rs"));
+        let docs = Documentation::from((origin, "/// Über *bold* claim.\nstruct A;\n"));
+        assert_eq!(
+            docs.dump_chunks(),
+            r#"rust-source /tmp/dump.rs
+  chunk 1 of 1
+    content: " Über *bold* claim."
+      0..19 " Über *bold* claim." -> 1:3..1:21
+    plain: "Über bold claim."
+      0..5 "Über " -> 1..6
+      5..9 "bold" -> 7..11
+      9..16 " claim." -> 12..19
+"#
+        );
+    }
+
+    #[test]
+    fn doc_url_attributes() {
+        const SOURCE: &str = r#"//! Crate docs.
+#![doc(html_root_url = "https://docs.rs/frobnicate/0.1.0")]
+#![doc(
+    html_logo_url = "https://example.com/lgoo.png",
+    html_favicon_url = "https://example.com/favcion.ico"
+)]
+#![cfg_attr(docsrs, doc(html_playground_url = "https://play.rust-lang.org/"))]
+#![doc(issue_tracker_base_url = "https://github.com/frob/frobnicate/issues/")]
+
+/// Item docs.
+#[doc(alias = "frbo")]
+pub fn frobnicate() {}
+"#;
+        let origin = ContentOrigin::RustSourceFile(PathBuf::from("/tmp/lib.rs"));
+        let contents = |docs: &Documentation| {
+            docs.index
+                .get(&origin)
+                .expect("Must contain the docs")
+                .iter()
+                .map(|chunk| chunk.as_str().to_owned())
+                .collect::<Vec<_>>()
+        };
+        // urls are never checked, even if configured as doc attributes
+        let config = crate::config::RustConfig {
+            extra_doc_attributes: Some(
+                ["html_root_url", "html_logo_url", "issue_tracker_base_url"]
+                    .iter()
+                    .map(|argument| crate::config::DocAttribute {
+                        path: "doc".to_owned(),
+                        argument: Some((*argument).to_owned()),
+                    })
+                    .collect(),
+            ),
+            ..Default::default()
+        };
+        let docs = Documentation::load_from_str(origin.clone(), SOURCE, &config);
+        assert_eq!(contents(&docs), vec![" Crate docs.", " Item docs."]);
+        let docs = Documentation::from((origin.clone(), SOURCE));
+        assert_eq!(contents(&docs), vec![" Crate docs.", " Item docs."]);
+    }
+
+    #[test]
+    fn attribute_notes() {
+        const SOURCE: &str = r#"/// Old entry.
+#[deprecated(since = "0.2.0", note = "use nwe instead")]
+pub fn old() {}
+
+#[must_use = "the reuslt must be checked"]
+#[must_use]
+pub fn check() -> bool {
+    true
+}
+
+#[deprecated = "gone for godo"]
+pub fn gone() {}
+"#;
+        let origin = ContentOrigin::RustSourceFile(PathBuf::from("/tmp/deprecated.rs"));
+        let docs = Documentation::from((origin.clone(), SOURCE));
+        let chunks = docs.index.get(&origin).expect("Must contain the docs");
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|chunk| chunk.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                " Old entry.",
+                "use nwe instead",
+                "the reuslt must be checked",
+                "gone for godo",
+            ]
+        );
+        let (_, span) = chunks[1].iter().next().unwrap();
+        assert_eq!(
+            span.start,
+            LineColumn {
+                line: 2,
+                column: 38
+            }
+        );
+        assert_eq!(
+            span.end,
+            LineColumn {
+                line: 2,
+                column: 52
+            }
+        );
+
+        let config = crate::config::RustConfig {
+            check_attribute_notes: Some(false),
+            ..Default::default()
+        };
+        let docs = Documentation::load_from_str(origin.clone(), SOURCE, &config);
+        assert_eq!(docs.index.get(&origin).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn hyphenated_lines() {
+        const SOURCE: &str =
+            "/// Each entry is valid-\n///   ated once.\n/// Keep-\n/// Going\nstruct A;\n";
+        let origin = ContentOrigin::RustSourceFile(PathBuf::from("/tmp/hyphen.rs"));
+        let docs = Documentation::from((origin.clone(), SOURCE));
+        let chunk = &docs.index.get(&origin).unwrap()[0];
+        assert_eq!(
+            chunk.as_str(),
+            " Each entry is valid-\n   ated once.\n Keep-\n Going"
+        );
+
+        let config = crate::config::RustConfig {
+            join_hyphenated_lines: Some(true),
+            ..Default::default()
+        };
+        let docs = Documentation::load_from_str(origin.clone(), SOURCE, &config);
+        let chunk = &docs.index.get(&origin).unwrap()[0];
+        // only lowercase continuations are joined
+        assert_eq!(
+            chunk.as_str(),
+            " Each entry is validated once.\n Keep-\n Going"
+        );
+        let plain = docs.erase_markdown(chunk);
+        let words = crate::checker::Tokenizer::default()
+            .tokenize(plain.as_str())
+            .into_iter()
+            .map(|range| crate::util::sub_chars(plain.as_str(), range))
+            .collect::<Vec<_>>();
+        assert!(words.contains(&"validated".to_owned()));
+
+        // a mistake within the joined word covers both lines
+        let spans = chunk.find_spans(15..24);
+        let spans = spans.values().collect::<Vec<_>>();
+        assert_eq!(spans.len(), 2);
+        assert_eq!(
+            spans[0].start,
+            LineColumn {
+                line: 1,
+                column: 18
+            }
+        );
+        assert_eq!(
+            spans[0].end,
+            LineColumn {
+                line: 1,
+                column: 22
+            }
+        );
+        assert_eq!(spans[1].start, LineColumn { line: 2, column: 6 });
+        assert_eq!(spans[1].end, LineColumn { line: 2, column: 9 });
+
+        // which is replaced once, as a whole
+        let start = plain.as_str().find("validated").unwrap();
+        let (range, span) = plain.find_span(start..(start + 9)).unwrap();
+        assert_eq!(range, 15..24);
+        assert_eq!(span.start, spans[0].start);
+        assert_eq!(span.end, spans[1].end);
+        let bytes = span.to_byte_range(SOURCE).unwrap();
+        let patched = format!(
+            "{}validated{}",
+            &SOURCE[..bytes.start],
+            &SOURCE[bytes.end..]
+        );
+        assert!(patched.starts_with("/// Each entry is validated once.\n/// Keep-\n"));
+    }
+
+    #[test]
+    fn blockquotes() {
+        const SOURCE: &str = "/// Intro.\n///\n/// > Quoted wrold\n/// > > nested\nstruct A;\n";
+        let origin = ContentOrigin::RustSourceFile(PathBuf::from("/tmp/quote.rs"));
+        let mut docs = Documentation::from((origin.clone(), SOURCE));
+        let chunk = &docs.index.get(&origin).unwrap()[0];
+        let plain = docs.erase_markdown(chunk);
+        assert_eq!(plain.as_str(), "Intro.\n\nQuoted wrold\n\nnested");
+        // the `> ` markers are stripped, the spans point at the text
+        let spans = plain.find_spans(15..20);
+        let (_, span) = spans.iter().next().unwrap();
+        assert_eq!(
+            span.start,
+            LineColumn {
+                line: 3,
+                column: 13
+            }
+        );
+        assert_eq!(
+            span.end,
+            LineColumn {
+                line: 3,
+                column: 17
+            }
+        );
+        let spans = plain.find_spans(22..28);
+        let (_, span) = spans.iter().next().unwrap();
+        assert_eq!(span.start, LineColumn { line: 4, column: 8 });
+
+        docs.configure_markdown(MarkdownConfig {
+            check_blockquotes: Some(false),
+            ..Default::default()
+        });
+        let chunk = &docs.index.get(&origin).unwrap()[0];
+        assert_eq!(docs.erase_markdown(chunk).as_str(), "Intro.");
+    }
+
+    #[test]
+    fn format_strings() {
+        const SOURCE: &str = r#"/// Entry.
+fn main() {
+    println!("Found {} filse", n);
+    log::warn!(target: "fs", "Could not raed {:?}", path);
+    eprintln!("{}: {}", a, b);
+    format!("Not checkd");
+}
+"#;
+        let config = crate::config::RustConfig {
+            extra_doc_attributes: None,
+            check_format_strings: Some(true),
+            ..Default::default()
+        };
+        let origin = ContentOrigin::RustSourceFile(PathBuf::from("/tmp/main.rs"));
+        let docs = Documentation::load_from_str(origin.clone(), SOURCE, &config);
+        let chunks = docs.index.get(&origin).expect("Must contain the docs");
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|chunk| chunk.as_str())
+                .collect::<Vec<_>>(),
+            vec![" Entry.", "Found \n filse", "Could not raed "]
+        );
+        let (_, span) = chunks[2].iter().next().unwrap();
+        assert_eq!(
+            span.start,
+            LineColumn {
+                line: 4,
+                column: 30
+            }
+        );
+
+        // opt-in only
+        let docs = Documentation::from((origin.clone(), SOURCE));
+        assert_eq!(docs.index.get(&origin).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn only_public() {
+        const SOURCE: &str = r#"//! File.
+
+/// Public.
+pub struct A {
+    /// Public field.
+    pub a: Vec<u8>,
+    /// Private field.
+    b: HashMap<u8, u8>,
+    /// Another public field.
+    pub c: u8,
+}
+/// Private.
+struct B {
+    /// Public field of a private struct.
+    pub a: u8,
+}
+/// Crate.
+pub(crate) fn c() {
+    /// Local.
+    struct D;
+}
+/// Enum.
+pub enum E<X, Y> {
+    /// Variant.
+    V,
+}
+impl A {
+    /// Private method.
+    fn private(&self) {}
+    /// Public method.
+    pub fn public(&self) {}
+}
+impl Default for A {
+    /// Trait impl.
+    fn default() -> Self { unimplemented!() }
+}
+mod private {
+    //! Private module.
+    /// Nested.
+    pub fn nested() {}
+}
+pub mod public {
+    //! Public module.
+}
+/// Exported.
+#[macro_export]
+macro_rules! m { () => {} }
+"#;
+        let config = crate::config::RustConfig {
+            only_public: Some(true),
+            ..Default::default()
+        };
+        let origin = ContentOrigin::RustSourceFile(PathBuf::from("/tmp/public.rs"));
+        let docs = Documentation::load_from_str(origin.clone(), SOURCE, &config);
+        let chunks = |docs: &Documentation| {
+            docs.index
+                .get(&origin)
+                .unwrap()
+                .iter()
+                .map(|chunk| chunk.as_str().to_owned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            chunks(&docs),
+            vec![
+                " File.",
+                " Public.",
+                " Public field.",
+                " Another public field.",
+                " Enum.",
+                " Variant.",
+                " Public method.",
+                " Trait impl.",
+                " Public module.",
+                " Exported.",
+            ]
+        );
+
+        let config = crate::config::RustConfig {
+            only_public: Some(true),
+            include_pub_crate: Some(true),
+            ..Default::default()
+        };
+        let docs = Documentation::load_from_str(origin.clone(), SOURCE, &config);
+        assert!(chunks(&docs).contains(&" Crate.".to_owned()));
+        assert!(!chunks(&docs).contains(&" Local.".to_owned()));
+
+        // everything by default
+        let docs = Documentation::from((origin.clone(), SOURCE));
+        assert_eq!(chunks(&docs).len(), 17);
+    }
+
+    #[test]
+    fn code_only() {
+        const SOURCE: &str = r#"/// ```rust
+/// let x = Example::new();
+/// ```
+struct Example;
+
+/// Prose before the code.
+///
+/// ```rust
+/// let y = Example::new();
+/// ```
+struct Other;
+
+///
+struct Blank;
+
+/// Only prose.
+struct Prose;
+"#;
+        let origin = ContentOrigin::RustSourceFile(PathBuf::from("/tmp/crate/src/lib.rs"));
+        let mut docs = Documentation::from((origin.clone(), SOURCE));
+        let chunks = docs.index.get(&origin).unwrap().clone();
+        assert_eq!(chunks.len(), 4);
+        // not skipped by default
+        assert!(chunks.iter().all(|chunk| !docs.may_be_code_only(chunk)));
+
+        docs.configure_markdown(MarkdownConfig {
+            skip_code_only: Some(true),
+            ..Default::default()
+        });
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|chunk| docs.may_be_code_only(chunk))
+                .collect::<Vec<_>>(),
+            vec![true, true, true, false]
+        );
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|chunk| docs.erase_markdown(chunk).as_str().trim().is_empty())
+                .collect::<Vec<_>>(),
+            vec![true, false, true, false]
+        );
+    }
+
+    #[test]
+    fn item_kinds() {
+        const SOURCE: &str = r#"/// A trait.
+pub trait Shape {
+    /// Associated const of the trait.
+    const SIDES: usize;
+    /// Associated type.
+    type Unit;
+    /// Declared method.
+    fn area(&self) -> f64;
+    /// Provided method.
+    fn name(&self) -> &'static str {
+        "shape"
+    }
+}
+
+pub struct Square(f64);
+
+impl Shape for Square {
+    /// Associated const of the impl.
+    const SIDES: usize = 4;
+    type Unit = f64;
+    /// Implemented method.
+    fn area(&self) -> f64 {
+        self.0 * self.0
+    }
+}
+
+impl Square {
+    /// Inherent const.
+    pub const UNIT: Self = Self(1.);
+    /// Generic method,
+    /// over two lines.
+    pub fn scale<T: Into<f64>>(&self, factor: T) -> Self
+    where
+        T: Copy,
+    {
+        Self(self.0 * factor.into())
+    }
+}
+
+/// An exported macro.
+#[macro_export]
+macro_rules! square {
+    ($side:expr) => {
+        /// Generated item.
+        $crate::Square($side)
+    };
+}
+
+extern "C" {
+    /// Foreign function.
+    pub fn foreign(x: i32) -> i32;
+}
+"#;
+        let origin = ContentOrigin::RustSourceFile(PathBuf::from("/tmp/items.rs"));
+        let docs = Documentation::from((origin.clone(), SOURCE));
+        let chunks = docs.index.get(&origin).unwrap();
+        // text of each chunk, with the line and the first column of its first fragment
+        let expected = [
+            (" A trait.", 1, 3),
+            (" Associated const of the trait.", 3, 7),
+            (" Associated type.", 5, 7),
+            (" Declared method.", 7, 7),
+            (" Provided method.", 9, 7),
+            (" Associated const of the impl.", 18, 7),
+            (" Implemented method.", 21, 7),
+            (" Inherent const.", 28, 7),
+            (" Generic method,\n over two lines.", 30, 7),
+            (" An exported macro.", 40, 3),
+            (" Generated item.", 44, 11),
+            (" Foreign function.", 50, 7),
+        ];
+        assert_eq!(chunks.len(), expected.len());
+        for (chunk, (text, line, column)) in chunks.iter().zip(expected.iter()) {
+            assert_eq!(chunk.as_str(), *text);
+            let (_range, span) = chunk.iter().next().unwrap();
+            assert_eq!(
+                span.start,
+                LineColumn {
+                    line: *line,
+                    column: *column
+                }
+            );
+            // every fragment covers exactly its text within the source
+            for (range, span) in chunk.iter() {
+                assert_eq!(
+                    load_span_from(SOURCE.as_bytes(), *span).unwrap(),
+                    crate::util::sub_chars(chunk.as_str(), range.clone())
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn disabled_regions() {
+        let path = PathBuf::from("dummy/table.rs");
+        let docs = Documentation::from((
+            ContentOrigin::RustSourceFile(path.clone()),
+            "/// Table\n/// spellcheck: off\n/// | rwa |\n/// spellcheck: on\nstruct A;\n",
+        ));
+        assert!(!docs.is_disabled(&path, 1, 1));
+        assert!(docs.is_disabled(&path, 3, 3));
+        assert!(!docs.is_disabled(&path, 5, 5));
+        assert!(!docs.is_disabled(Path::new("dummy/other.rs"), 3, 3));
+    }
+
+    #[test]
+    fn unparsable_is_skipped() {
+        let origin = ContentOrigin::RustSourceFile(PathBuf::from("dummy/broken.rs"));
+        let mut docs = Documentation::from((origin.clone(), "/// Docs\nfn broken( {"));
+        assert!(docs.is_empty());
+        assert_eq!(docs.unparsable().len(), 1);
+        assert_eq!(docs.unparsable()[0].0, origin);
+
+        docs.join(Documentation::from((
+            ContentOrigin::RustSourceFile(PathBuf::from("dummy/fine.rs")),
+            "/// Docs\nstruct Fine;",
+        )));
+        assert_eq!(docs.iter().count(), 1);
+        assert_eq!(docs.unparsable().len(), 1);
+    }
+
     #[test]
     fn parse_and_construct() {
         let _ = env_logger::from_env(
@@ -0,0 +1,90 @@
+//! Take plain text as it is
+//!
+//! A plain text file is prose without any markup, so all of it is checked.
+//! Every line is mapped back to the `Span` it originates from, so
+//! suggestions can be applied to the file.
+
+use super::*;
+
+use crate::documentation::chunk::ChunkBuilder;
+
+impl CheckableChunk {
+    /// Take all of a plain text file as prose, line by line.
+    pub fn from_plain_text(content: &str) -> Self {
+        let mut builder = ChunkBuilder::new();
+        for (lineno, line) in content.lines().enumerate() {
+            if lineno > 0 {
+                builder.newline();
+            }
+            builder.push(line, lineno + 1, 0);
+        }
+        builder.build_plain_text()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::{self, load_span_from};
+
+    const TEXT: &str = r#"Release notes for the next versoin.
+
+Fixes a crash  when *nothing* is
+configured, see `config.toml`.
+
+  Indented paragraph with ümlauts.
+"#;
+
+    #[test]
+    fn plain_text() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let chunk = CheckableChunk::from_plain_text(TEXT);
+        assert_eq!(chunk.as_str(), TEXT.trim_end_matches('\n'));
+        assert_eq!(chunk.iter().count(), 4);
+
+        for (range, span) in chunk.iter() {
+            assert_eq!(
+                load_span_from(TEXT.as_bytes(), *span).expect("Span must be loadable"),
+                util::sub_chars(chunk.as_str(), range.clone())
+            );
+        }
+
+        // nothing is erased, neither emphasis nor code
+        let plain = chunk.erase_markdown();
+        assert_eq!(plain.as_str(), chunk.as_str());
+        assert!(plain.unbalanced().is_none());
+
+        let start = chunk.as_str().find("versoin").unwrap();
+        let spans = plain.find_spans(start..(start + 7));
+        assert_eq!(
+            spans.values().copied().collect::<Vec<_>>(),
+            vec![Span {
+                start: LineColumn {
+                    line: 1,
+                    column: 27
+                },
+                end: LineColumn {
+                    line: 1,
+                    column: 33
+                },
+            }]
+        );
+
+        let start = chunk.as_str().chars().position(|c| c == 'ü').unwrap();
+        let spans = plain.find_spans(start..(start + 7));
+        assert_eq!(
+            spans.values().copied().collect::<Vec<_>>(),
+            vec![Span {
+                start: LineColumn {
+                    line: 6,
+                    column: 26
+                },
+                end: LineColumn {
+                    line: 6,
+                    column: 32
+                },
+            }]
+        );
+    }
+}
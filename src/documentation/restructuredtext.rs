@@ -0,0 +1,293 @@
+//! Erase reStructuredText syntax
+//!
+//! Only the prose of a reStructuredText document is retained, every
+//! fragment of it is mapped back to the `Span` it originates from, so
+//! suggestions can be applied to the file.
+//!
+//! Literal blocks, doctest blocks, comments, hyperlink targets and the
+//! content of directives which do not contain prose are skipped entirely,
+//! as are roles and inline literals.
+
+use super::*;
+
+use fancy_regex::Regex;
+
+use crate::documentation::chunk::{inline_prose, match_end, ChunkBuilder};
+
+lazy_static::lazy_static! {
+    static ref ADORNMENT: Regex = Regex::new(r#"^([=\-`:'"~^_*+#<>.])\1+$"#).expect("ADORNMENT regex compiles");
+    static ref TABLE_BORDER: Regex = Regex::new(r"^(?:\+(?:[-=]+\+)+|=+(?:\s+=+)+)$").expect("TABLE_BORDER regex compiles");
+    static ref DIRECTIVE: Regex = Regex::new(r"^\s*\.\.\s+([\w.+-]+(?::[\w.+-]+)*)::(?:\s+|$)").expect("DIRECTIVE regex compiles");
+    static ref FOOTNOTE: Regex = Regex::new(r"^\s*\.\.\s+\[[^\]]+\](?:\s+|$)").expect("FOOTNOTE regex compiles");
+    static ref LIST_ITEM: Regex = Regex::new(r"^\s*(?:[-*+•‣⁃]|\(?(?:\d+|#|[a-zA-Z]|[ivxlcdmIVXLCDM]+)[.)])\s+").expect("LIST_ITEM regex compiles");
+    static ref FIELD: Regex = Regex::new(r"^\s*:[^:\s`][^:`]*:(?:\s+|$)").expect("FIELD regex compiles");
+
+    /// Inline elements, paired with the capture group which is prose and must be retained.
+    static ref INLINE: Vec<(Regex, Option<usize>)> = vec![
+        // inline literals
+        (Regex::new(r"``.+?``").expect("LITERAL regex compiles"), None),
+        // roles, as prefix or suffix
+        (Regex::new(r"(?<![\w`]):[\w.+:-]+:`[^`]*`").expect("ROLE regex compiles"), None),
+        (Regex::new(r"`[^`]*`:[\w.+:-]+:").expect("ROLE_SUFFIX regex compiles"), None),
+        // hyperlink references with an embedded target, anonymous or named
+        (Regex::new(r"`([^`<]*?)\s*<[^>`]+>`__?").expect("EMBEDDED_REFERENCE regex compiles"), Some(1)),
+        (Regex::new(r"`([^`<]+)`__?").expect("PHRASE_REFERENCE regex compiles"), Some(1)),
+        (Regex::new(r"\b(\w+)__?(?!\w)").expect("REFERENCE regex compiles"), Some(1)),
+        // inline internal targets
+        (Regex::new(r"_`([^`]+)`").expect("TARGET regex compiles"), Some(1)),
+        // interpreted text with the default role
+        (Regex::new(r"(?<![\w:_`])`[^`]+`(?![\w:_`])").expect("INTERPRETED regex compiles"), None),
+        // substitution, footnote and citation references
+        (Regex::new(r"\|[^|\s](?:[^|]*[^|\s])?\|_{0,2}").expect("SUBSTITUTION regex compiles"), None),
+        (Regex::new(r"\[(?:\d+|#[\w-]*|\*|[\w.-]+)\]_").expect("FOOTNOTE_REFERENCE regex compiles"), None),
+        // standalone urls
+        (Regex::new(r"\b(?:https?|ftp|file)://[^\s<>`]+").expect("URL regex compiles"), None),
+        // emphasis, line blocks and table cell separators
+        (Regex::new(r"\*+").expect("EMPHASIS regex compiles"), None),
+        (Regex::new(r"(?<!\S)\|(?!\S)").expect("BAR regex compiles"), None),
+    ];
+}
+
+/// Directives with prose as content.
+const PROSE_DIRECTIVES: &[&str] = &[
+    "admonition",
+    "centered",
+    "compound",
+    "container",
+    "deprecated",
+    "epigraph",
+    "glossary",
+    "highlights",
+    "hlist",
+    "pull-quote",
+    "rubric",
+    "seealso",
+    "sidebar",
+    "topic",
+    "versionadded",
+    "versionchanged",
+];
+
+/// Directives with prose as argument and content.
+const ADMONITIONS: &[&str] = &[
+    "attention",
+    "caution",
+    "danger",
+    "error",
+    "hint",
+    "important",
+    "note",
+    "tip",
+    "todo",
+    "warning",
+];
+
+/// Number of leading whitespace characters of `line`.
+fn indentation(line: &str) -> usize {
+    line.chars().take_while(|c| c.is_whitespace()).count()
+}
+
+impl CheckableChunk {
+    /// Extract the prose from a reStructuredText document.
+    pub fn from_restructuredtext(content: &str) -> Self {
+        let mut builder = ChunkBuilder::new();
+
+        // newlines to insert before the next fragment
+        let mut pending = 0usize;
+        // skipping all lines indented deeper than this
+        let mut skip_indented: Option<usize> = None;
+        // skipping a doctest block, which ends with an empty line
+        let mut verbatim_paragraph = false;
+
+        for (lineno, line) in content.lines().enumerate() {
+            let lineno = lineno + 1;
+            let trimmed = line.trim_end();
+            let indent = indentation(trimmed);
+
+            if let Some(depth) = skip_indented {
+                if trimmed.is_empty() || indent > depth {
+                    continue;
+                }
+                skip_indented = None;
+            }
+
+            if trimmed.is_empty() {
+                verbatim_paragraph = false;
+                pending = pending.max(2);
+                continue;
+            }
+
+            if verbatim_paragraph {
+                continue;
+            }
+
+            let stripped = trimmed.trim_start();
+            if stripped == "::" || stripped == ".." {
+                // expanded literal block marker or an empty comment
+                skip_indented = Some(indent);
+                pending = pending.max(2);
+                continue;
+            }
+            if match_end(&ADORNMENT, stripped).is_some()
+                || match_end(&TABLE_BORDER, stripped).is_some()
+            {
+                pending = pending.max(2);
+                continue;
+            }
+            if stripped.starts_with(">>>") {
+                verbatim_paragraph = true;
+                continue;
+            }
+
+            // strip the block level markup
+            let (offset, standalone) = if stripped.starts_with(".. ") {
+                if let Some(captures) = DIRECTIVE.captures(trimmed).ok().flatten() {
+                    let name = captures.get(1).map(|m| m.as_str()).unwrap_or_default();
+                    let end = captures.get(0).map(|m| m.end()).unwrap_or_default();
+                    if !ADMONITIONS.contains(&name) {
+                        if !PROSE_DIRECTIVES.contains(&name) && !name.contains(':') {
+                            skip_indented = Some(indent);
+                        }
+                        pending = pending.max(2);
+                        continue;
+                    }
+                    (end, true)
+                } else if let Some(end) = match_end(&FOOTNOTE, trimmed) {
+                    (end, true)
+                } else {
+                    // comments, hyperlink targets and substitution definitions
+                    skip_indented = Some(indent);
+                    pending = pending.max(2);
+                    continue;
+                }
+            } else if let Some(end) = match_end(&LIST_ITEM, trimmed) {
+                (end, true)
+            } else if let Some(end) = match_end(&FIELD, trimmed) {
+                (end, true)
+            } else {
+                (0, false)
+            };
+            if standalone {
+                pending = pending.max(2);
+            }
+
+            if stripped.ends_with("::") {
+                // the following indented block is a literal block
+                skip_indented = Some(indent);
+            }
+
+            let body = &trimmed[offset..];
+            let column_offset = trimmed[..offset].chars().count();
+            let ranges = inline_prose(body, &INLINE);
+            if ranges.is_empty() {
+                continue;
+            }
+
+            if !builder.is_empty() {
+                for _ in 0..pending.max(1) {
+                    builder.newline();
+                }
+            }
+            pending = if standalone { 2 } else { 0 };
+
+            builder.push_ranges(body, ranges, lineno, column_offset);
+        }
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::load_span_from;
+
+    const RST: &str = r#"=====
+Titel
+=====
+
+Introdution
+-----------
+
+This is *very* **importent** text with ``code_here`` and
+:func:`some_function`, a `linkk <https://example.com>`_ to `Refernce`_ somewhere_.
+
+.. code-block:: python
+
+   x = "mistkae"
+
+.. note:: Admonitons are prose.
+
+   Also in the bodie.
+
+* Firts item
+* Second itme
+
+Example of a literal block::
+
+    let skipped = "mistkae";
+
+.. a commment line
+   continued
+
+.. _Refernce: https://example.com
+
+>>> print("mistkae")
+mistkae
+
+Last paragrah.
+"#;
+
+    const PLAIN: &str = r#"Titel
+
+Introdution
+
+This is very importent text with  and
+, a linkk to Refernce somewhere.
+
+Admonitons are prose.
+
+Also in the bodie.
+
+Firts item
+
+Second itme
+
+Example of a literal block::
+
+Last paragrah."#;
+
+    #[test]
+    fn restructuredtext_reduction() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let chunk = CheckableChunk::from_restructuredtext(RST);
+        assert_eq!(chunk.as_str(), PLAIN);
+
+        for (range, span) in chunk.iter() {
+            assert_eq!(
+                load_span_from(RST.as_bytes(), *span).expect("Span must be loadable"),
+                util::sub_chars(chunk.as_str(), range.clone())
+            );
+        }
+    }
+
+    #[test]
+    fn restructuredtext_roles() {
+        const SOURCE: &str = "Use :py:class:`Foo` or `Bar`:ref: here.";
+        let chunk = CheckableChunk::from_restructuredtext(SOURCE);
+        assert_eq!(chunk.as_str(), "Use  or  here.");
+        assert_eq!(
+            chunk.iter().last().map(|(_range, span)| *span),
+            Some(Span {
+                start: LineColumn {
+                    line: 1,
+                    column: 33
+                },
+                end: LineColumn {
+                    line: 1,
+                    column: 38
+                },
+            })
+        );
+    }
+}
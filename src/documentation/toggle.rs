@@ -0,0 +1,175 @@
+//! Regions of a file in which checking is disabled.
+//!
+//! A region starts with a line containing nothing but a comment
+//! `spellcheck: off` and ends with `spellcheck: on`, both lines included.
+//! Supported are Rust comments `//`, `///` and `//!`, html comments
+//! `<!-- spellcheck: off -->` as used in common mark, as well as
+//! reStructuredText comments `.. spellcheck: off`.
+//!
+//! A region which is never closed extends to the end of the file.
+//!
+//! A single occurrence of a word is accepted by an html comment
+//! `<!-- spellcheck: ok word -->` following it on the same line, as in
+//! `` Runs on tokio <!-- spellcheck: ok tokio -->``. The comment is erased
+//! along with all other markdown, so it does not show up in rendered docs.
+//! It accepts the last occurrence of each listed word before it, as a whole
+//! word, matching case.
+
+use crate::Range;
+
+/// Comment prefixes a toggle may be preceded by, longest first.
+const PREFIXES: &[&str] = &["//!", "///", "//", ".."];
+
+/// Parse a toggle, `Some(true)` if the line enables checking again,
+/// `Some(false)` if it disables checking.
+fn toggle(line: &str) -> Option<bool> {
+    let line = line.trim();
+    let inner = if let Some(html) = line.strip_prefix("<!--") {
+        html.strip_suffix("-->")?
+    } else {
+        PREFIXES
+            .iter()
+            .find_map(|prefix| line.strip_prefix(prefix))?
+    };
+    let mut split = inner.splitn(2, ':');
+    if split.next()?.trim() != "spellcheck" {
+        return None;
+    }
+    match split.next()?.trim() {
+        "on" => Some(true),
+        "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parse the words accepted by the inner text of an html comment,
+/// `spellcheck: ok word..`.
+fn accepted_by(inner: &str) -> Option<Vec<&str>> {
+    let mut split = inner.splitn(2, ':');
+    if split.next()?.trim() != "spellcheck" {
+        return None;
+    }
+    let mut words = split.next()?.split_whitespace();
+    if words.next()? != "ok" {
+        return None;
+    }
+    let words = words.collect::<Vec<_>>();
+    if words.is_empty() {
+        None
+    } else {
+        Some(words)
+    }
+}
+
+/// Byte offset of the last occurrence of `word` within `line`, which is
+/// not part of a longer word.
+fn last_occurrence(line: &str, word: &str) -> Option<usize> {
+    let boundary = |c: Option<char>| !c.map(char::is_alphanumeric).unwrap_or(false);
+    line.rmatch_indices(word).map(|(idx, _)| idx).find(|idx| {
+        boundary(line[..*idx].chars().next_back())
+            && boundary(line[idx + word.len()..].chars().next())
+    })
+}
+
+/// Character ranges of the occurrences of words within `content` accepted
+/// by a trailing `<!-- spellcheck: ok word -->`, in order.
+pub fn accepted_occurrences(content: &str) -> Vec<Range> {
+    let mut acc = Vec::new();
+    for (start, _) in content.match_indices("<!--") {
+        let inner = &content[(start + 4)..];
+        let inner = if let Some(end) = inner.find("-->") {
+            &inner[..end]
+        } else {
+            continue;
+        };
+        let words = if let Some(words) = accepted_by(inner) {
+            words
+        } else {
+            continue;
+        };
+        let line_start = content[..start].rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+        let line = &content[line_start..start];
+        for word in words {
+            if let Some(idx) = last_occurrence(line, word) {
+                let char_start = content[..(line_start + idx)].chars().count();
+                acc.push(char_start..(char_start + word.chars().count()));
+            }
+        }
+    }
+    acc.sort_by_key(|range| range.start);
+    acc
+}
+
+/// Inclusive, 1-indexed line ranges of `content` in which checking is disabled.
+pub fn disabled_regions(content: &str) -> Vec<(usize, usize)> {
+    let mut regions = Vec::new();
+    let mut off_since = None;
+    let mut last = 0usize;
+    for (idx, line) in content.lines().enumerate() {
+        let lineno = idx + 1;
+        last = lineno;
+        match (toggle(line), off_since) {
+            (Some(false), None) => off_since = Some(lineno),
+            (Some(true), Some(start)) => {
+                regions.push((start, lineno));
+                off_since = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(start) = off_since {
+        regions.push((start, last));
+    }
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggles() {
+        assert_eq!(toggle("// spellcheck: off"), Some(false));
+        assert_eq!(toggle("    /// spellcheck:on"), Some(true));
+        assert_eq!(toggle("//! spellcheck: off"), Some(false));
+        assert_eq!(toggle("<!-- spellcheck: off -->"), Some(false));
+        assert_eq!(toggle(".. spellcheck: on"), Some(true));
+        assert_eq!(toggle("spellcheck: off"), None);
+        assert_eq!(toggle("/// spellcheck: maybe"), None);
+        assert_eq!(toggle("/// Run the spellcheck: off we go"), None);
+    }
+
+    #[test]
+    fn regions() {
+        const CONTENT: &str = r#"/// Checked
+/// spellcheck: off
+/// | rwa | dtaa |
+/// spellcheck: on
+/// Checked again
+// spellcheck: off
+// spellcheck: off
+struct A;
+"#;
+        assert_eq!(disabled_regions(CONTENT), vec![(2, 4), (6, 8)]);
+        assert!(disabled_regions("/// spellcheck: on\n").is_empty());
+    }
+
+    #[test]
+    fn accepted() {
+        assert_eq!(accepted_by(" spellcheck: ok tokio "), Some(vec!["tokio"]));
+        assert_eq!(
+            accepted_by("spellcheck:ok serde tokio"),
+            Some(vec!["serde", "tokio"])
+        );
+        assert_eq!(accepted_by(" spellcheck: ok "), None);
+        assert_eq!(accepted_by(" spellcheck: off "), None);
+        assert_eq!(accepted_by(" ok:tokio "), None);
+
+        const CONTENT: &str = "A tokio tokio<!-- spellcheck: ok tokio -->\nÜber tokios sërde <!-- spellcheck: ok tokio sërde Tokio -->\ntokio";
+        assert_eq!(accepted_occurrences(CONTENT), vec![8..13, 55..60]);
+        // only on the same line, as a whole word
+        assert!(accepted_occurrences("tokio\n<!-- spellcheck: ok tokio -->").is_empty());
+        assert!(accepted_occurrences("tokios <!-- spellcheck: ok tokio -->").is_empty());
+        assert!(accepted_occurrences("An unclosed <!-- spellcheck: ok unclosed").is_empty());
+    }
+}
@@ -0,0 +1,234 @@
+//! Interaction with `git`
+//!
+//! Used to restrict the checked content to what is about to be committed
+//! or was changed by a range of commits.
+
+use anyhow::{anyhow, bail, Result};
+use log::trace;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Check if `path` has one of the extensions of content which can be
+/// checked, plain text files are skipped later on unless enabled.
+fn is_checkable(path: &Path) -> bool {
+    crate::traverse::FileKind::of(path).is_some()
+}
+
+/// Run `git` with `args` in the current working directory and return its `stdout`.
+fn git(args: &[&str]) -> Result<String> {
+    trace!("Running git {:?}", args);
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|e| anyhow!("Failed to execute git {}", args.join(" ")).context(e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("not a git repository") {
+            bail!("Not inside a git repository, which is required to determine changed files")
+        }
+        bail!("git {} failed: {}", args.join(" "), stderr.trim())
+    }
+    String::from_utf8(output.stdout)
+        .map_err(|e| anyhow!("git {} printed invalid utf8", args.join(" ")).context(e))
+}
+
+/// The top level directory of the repository the working directory belongs to.
+pub fn toplevel() -> Result<PathBuf> {
+    Ok(PathBuf::from(
+        git(&["rev-parse", "--show-toplevel"])?.trim(),
+    ))
+}
+
+/// Convert a list of paths relative to `toplevel`, one per line, into
+/// absolute paths, retaining only files which can be checked.
+fn checkable_files(toplevel: &Path, name_list: &str) -> Vec<PathBuf> {
+    name_list
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| toplevel.join(line))
+        .filter(|path| is_checkable(path))
+        .collect()
+}
+
+/// All checkable files staged for the next commit, deleted files excluded,
+/// with their staged content, which is what gets committed regardless of
+/// any unstaged changes within the working tree.
+pub fn staged_files() -> Result<Vec<(PathBuf, String)>> {
+    let toplevel = toplevel()?;
+    let name_list = git(&["diff", "--cached", "--name-only", "--diff-filter=ACMR"])?;
+    let files = checkable_files(&toplevel, &name_list);
+    trace!("Staged files to check {:?}", &files);
+    files
+        .into_iter()
+        .map(|path| {
+            let relative = path
+                .strip_prefix(&toplevel)
+                .expect("Staged paths are joined with the toplevel directory. qed");
+            // the index entry, its path relative to the toplevel directory
+            let content = git(&["show", &format!(":{}", relative.display())])?;
+            Ok((path, content))
+        })
+        .collect()
+}
+
+/// Lines added or modified per file, by a range of commits.
+#[derive(Debug, Clone, Default)]
+pub struct ChangedLines {
+    /// Inclusive, 1-indexed line ranges per absolute file path.
+    per_file: HashMap<PathBuf, Vec<(usize, usize)>>,
+}
+
+impl ChangedLines {
+    /// Parse the output of `git diff --unified=0 --dst-prefix=b/`, where
+    /// the paths are relative to `toplevel`.
+    fn parse(toplevel: &Path, diff: &str) -> Self {
+        let mut per_file = HashMap::<PathBuf, Vec<(usize, usize)>>::new();
+        let mut current: Option<PathBuf> = None;
+        for line in diff.lines() {
+            if line.starts_with("+++ ") {
+                current = line.strip_prefix("+++ b/").map(|path| toplevel.join(path));
+            } else if line.starts_with("@@ ") {
+                let path = if let Some(ref path) = current {
+                    path
+                } else {
+                    continue;
+                };
+                // @@ -start[,count] +start[,count] @@
+                let added = line
+                    .split_whitespace()
+                    .find(|item| item.starts_with('+'))
+                    .map(|item| &item[1..]);
+                let mut added = if let Some(added) = added {
+                    added.splitn(2, ',')
+                } else {
+                    continue;
+                };
+                let start = added.next().and_then(|start| start.parse::<usize>().ok());
+                let count = added
+                    .next()
+                    .map(|count| count.parse::<usize>().ok())
+                    .unwrap_or(Some(1));
+                match (start, count) {
+                    (Some(start), Some(count)) if count > 0 => per_file
+                        .entry(path.clone())
+                        .or_insert_with(Vec::new)
+                        .push((start, start + count - 1)),
+                    _ => {}
+                }
+            }
+        }
+        Self { per_file }
+    }
+
+    /// All checkable files with changed lines.
+    pub fn checkable_files(&self) -> Vec<PathBuf> {
+        let mut files = self
+            .per_file
+            .keys()
+            .filter(|path| is_checkable(path))
+            .cloned()
+            .collect::<Vec<_>>();
+        files.sort();
+        files
+    }
+
+    /// Check if any of the lines `start..=end` of `path` was changed.
+    pub fn intersects(&self, path: &Path, start: usize, end: usize) -> bool {
+        self.per_file
+            .get(path)
+            .map(|changed| {
+                changed
+                    .iter()
+                    .any(|(first, last)| *first <= end && start <= *last)
+            })
+            .unwrap_or(false)
+    }
+}
+
+/// Determine the lines changed by the commit range `range`, i.e. `main..HEAD`.
+///
+/// All lines of newly added files count as changed, deleted files have none.
+pub fn changed_lines(range: &str) -> Result<ChangedLines> {
+    let toplevel = toplevel()?;
+    let diff = git(&[
+        "diff",
+        "--unified=0",
+        "--no-color",
+        "--no-ext-diff",
+        // the paths are parsed with these, regardless of `diff.noprefix`
+        // or `diff.mnemonicPrefix` being configured
+        "--src-prefix=a/",
+        "--dst-prefix=b/",
+        "--diff-filter=ACMR",
+        range,
+    ])?;
+    let changed = ChangedLines::parse(&toplevel, &diff);
+    trace!("Changed lines in {}: {:?}", range, &changed);
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_checkable() {
+        let toplevel = PathBuf::from("/repo");
+        assert_eq!(
+            checkable_files(
+                &toplevel,
+                "README.md\nsrc/main.rs\nCargo.toml\nlogo.svg\ndocs/guide.adoc\n\ndocs/index.rst\n"
+            ),
+            vec![
+                toplevel.join("README.md"),
+                toplevel.join("src/main.rs"),
+                toplevel.join("docs/guide.adoc"),
+                toplevel.join("docs/index.rst"),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_changed_lines() {
+        const DIFF: &str = r#"diff --git a/README.md b/README.md
+index 3b18e51..a8c7f2d 100644
+--- a/README.md
++++ b/README.md
+@@ -3,0 +4,2 @@ Intro
++added one
++added two
+@@ -10 +12 @@ more
+-old
++new
+@@ -20,3 +22,0 @@ removed
+-gone
+-gone
+-gone
+diff --git a/docs/new.rst b/docs/new.rst
+new file mode 100644
+index 0000000..e69de29
+--- /dev/null
++++ b/docs/new.rst
+@@ -0,0 +1,3 @@
++Brand
++new
++file
+"#;
+        let toplevel = PathBuf::from("/repo");
+        let changed = ChangedLines::parse(&toplevel, DIFF);
+        let readme = toplevel.join("README.md");
+        let new = toplevel.join("docs/new.rst");
+
+        assert_eq!(changed.checkable_files(), vec![readme.clone(), new.clone()]);
+        assert!(changed.intersects(&readme, 4, 4));
+        assert!(changed.intersects(&readme, 1, 5));
+        assert!(!changed.intersects(&readme, 6, 11));
+        assert!(changed.intersects(&readme, 12, 12));
+        assert!(!changed.intersects(&readme, 22, 22));
+        assert!(changed.intersects(&new, 1, 1));
+        assert!(changed.intersects(&new, 3, 3));
+        assert!(!changed.intersects(&new, 4, 4));
+        assert!(!changed.intersects(&toplevel.join("src/main.rs"), 1, 100));
+    }
+}
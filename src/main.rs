@@ -1,35 +1,48 @@
 mod action;
+mod cache;
 mod checker;
 mod config;
 mod documentation;
+mod git;
 mod span;
 mod suggestion;
+mod suppressions;
+mod timings;
 mod traverse;
 mod util;
 
 pub use self::action::*;
+pub use self::cache::*;
 pub use self::config::{Config, HunspellConfig, LanguageToolConfig};
 pub use self::documentation::*;
 pub use self::span::*;
 pub use self::suggestion::*;
+pub use self::suppressions::*;
+pub use self::timings::*;
 pub use self::util::*;
 
 use docopt::Docopt;
 
-use log::{info, trace, warn};
+use log::{debug, info, trace, warn};
 use serde::Deserialize;
 use signal_hook::{iterator, SIGINT, SIGQUIT, SIGTERM};
 
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 const USAGE: &str = r#"
 Spellcheck all your doc comments
 
 Usage:
-    cargo-spellcheck [(-v...|-q)] check [--cfg=<cfg>] [--code=<code>] [--skip-readme] [--checkers=<checkers>] [[--recursive] <paths>... ]
-    cargo-spellcheck [(-v...|-q)] fix [--cfg=<cfg>] [--code=<code>] [--skip-readme] [--checkers=<checkers>] [[--recursive] <paths>... ]
+    cargo-spellcheck [(-v...|-q)] check [--cfg=<cfg>] [--profile=<profile>] [--code=<code>] [--skip-readme] [--skip-markdown] [--hidden] [--follow-symlinks] [--checkers=<checkers>] [--explain] [--staged] [--diff-range=<range>] [--context-lines=<n>] [--width=<columns>] [--format=<format>] [--count-only] [--quiet-success] [--list-files [--fail-on-empty]] [--no-default-search-dirs] [--merge-adjacent] [--fail-on-parse-error] [--strict-config] [--unknown-words-baseline=<file>] [--suppressions=<file>] [--print-unknown-words] [--rules] [--ignore-word=<word>...] [--disable-rules=<rules>] [--min-score=<score>] [--max-mistakes=<n>] [--jobs=<jobs>] [--cache=<path>] [--timings] [--dump-chunks] [--stdin] [--stdin-filename=<name>] [[--recursive] <paths>... ]
+    cargo-spellcheck [(-v...|-q)] fix [--cfg=<cfg>] [--profile=<profile>] [--code=<code>] [--skip-readme] [--skip-markdown] [--hidden] [--follow-symlinks] [--checkers=<checkers>] [--explain] [--width=<columns>] [--no-default-search-dirs] [--merge-adjacent] [--fail-on-parse-error] [--strict-config] [--unknown-words-baseline=<file>] [--suppressions=<file>] [--ignore-word=<word>...] [--disable-rules=<rules>] [--min-score=<score>] [--jobs=<jobs>] [--cache=<path>] [--timings] [--resume|--replace-with-first] [--backup [--backup-suffix=<suffix>]] [[--recursive] <paths>... ]
     cargo-spellcheck [(-v...|-q)] config (--user|--stdout|--cfg=<cfg>) [--force]
-    cargo-spellcheck [(-v...|-q)] [--cfg=<cfg>] [--fix] [--code=<code>] [--skip-readme] [--checkers=<checkers>] [[--recursive] <paths>... ]
+    cargo-spellcheck [(-v...|-q)] config --stdout --cfg=<cfg>
+    cargo-spellcheck [(-v...|-q)] config --check [--cfg=<cfg>] [--profile=<profile>]
+    cargo-spellcheck [(-v...|-q)] config --list-quirks
+    cargo-spellcheck [(-v...|-q)] [--cfg=<cfg>] [--profile=<profile>] [--fix [--resume|--replace-with-first] [--backup [--backup-suffix=<suffix>]]] [--code=<code>] [--skip-readme] [--skip-markdown] [--hidden] [--follow-symlinks] [--checkers=<checkers>] [--explain] [--staged] [--diff-range=<range>] [--context-lines=<n>] [--width=<columns>] [--format=<format>] [--count-only] [--quiet-success] [--list-files [--fail-on-empty]] [--no-default-search-dirs] [--merge-adjacent] [--fail-on-parse-error] [--strict-config] [--unknown-words-baseline=<file>] [--suppressions=<file>] [--print-unknown-words] [--rules] [--ignore-word=<word>...] [--disable-rules=<rules>] [--min-score=<score>] [--max-mistakes=<n>] [--jobs=<jobs>] [--cache=<path>] [--timings] [--dump-chunks] [[--recursive] <paths>... ]
     cargo-spellcheck --help
     cargo-spellcheck --version
 
@@ -41,23 +54,104 @@ Options:
   -r --recursive            If a path is provided, if recursion into subdirectories is desired.
   --checkers=<checkers>     Calculate the intersection between
                             configured by config file and the ones provided on commandline.
+                            A comma separated list of hunspell, languagetool, reflow, external
+                            and repeated_words.
   -f --force                Overwrite any existing configuration file. [default=false]
   -c --cfg=<cfg>            Use a non default configuration file.
                             Passing a directory will attempt to open `cargo_spellcheck.toml` in that directory.
+                            Files ending with `.yaml` or `.yml` are read and written as yaml.
+                            Defaults to `$CARGO_SPELLCHECK_CONFIG` if set.
+  --profile=<profile>       Use the configuration file `cargo_spellcheck.<profile>.toml`, searched
+                            in the current directory and then in the user configuration directory,
+                            or only in the directory given with `--cfg`.
   --user                    Write the configuration file to the default user configuration directory.
-  --stdout                  Print the configuration file to stdout and exit.
-  -v --verbose              Verbosity level.
+  --stdout                  Print the configuration file to stdout and exit, as yaml if `--cfg`
+                            ends with `.yaml` or `.yml`.
+  --check                   Verify the configuration file does not contain any unknown keys.
+  --list-quirks             Print all quirks, which can be enabled via `hunspell.quirks`, and exit.
+  --strict-config           Fail if the configuration file contains unknown keys, i.e. typos,
+                            instead of ignoring them.
+  -v --verbose              Verbosity level, also lists the checked files without any mistakes.
   -q --quiet                Silences all printed messages. Overrules `-v`.
-  -m --code=<code>          Overwrite the exit value for a successful run with content mistakes found. [default=0]
+  -m --code=<code>          Overwrite the exit value for a successful run with content mistakes found,
+                            errors exit with 2 and interruptions with 130. [default=0]
   --skip-readme             Do not attempt to process README.md files listed in Cargo.toml manifests.
+  --skip-markdown           Only check Rust doc comments, skip all markdown files, READMEs included.
+  --hidden                  Include hidden files and directories, those starting with a `.`,
+                            when searching directories. Explicitly passed paths are always checked.
+  --follow-symlinks         Descend into symlinked directories and take symlinked files, products
+                            and modules into account when searching directories and resolving
+                            modules, symlink loops are skipped and each file is checked only once.
+                            Explicitly passed paths are always checked.
+  --stdin                   Check the content passed via stdin instead of any files, only with `check`.
+  --stdin-filename=<name>   Attribute the content passed via stdin to the given path, its
+                            extension determines how the content is parsed. Defaults to markdown.
+  --staged                  Only check the files staged for the next git commit, as staged.
+  --diff-range=<range>      Only report mistakes on lines changed by the git commit range, i.e. `main..HEAD`.
+                            Newly added files are checked entirely, modified files only on changed lines.
+  --explain                 Log why each mistake was not accepted, regardless of the verbosity.
+  --context-lines=<n>       Print the given number of source lines before and after each mistake. [default=0]
+  --width=<columns>         Lay out mistakes for a terminal of the given width instead of the detected one,
+                            for reproducible output. Longer lines are cut around the mistake.
+  --format=<format>         Print mistakes as `human` readable excerpts, as `checkstyle` xml or as `github`
+                            actions workflow commands. [default: human]
+  --list-files              Print the files which would be checked and their type, without checking them.
+  --fail-on-empty           Fail if `--list-files` resolves no files.
+  --count-only              Print nothing but the number of mistakes found.
+  --quiet-success           Print nothing at all if no mistakes are found, regardless of the format.
+  --no-default-search-dirs  Never look for dictionaries in OS specific defaults, not even without configured `search_dirs`.
+  --merge-adjacent          Merge mistakes of adjacent words on the same line into a single one.
+  --fail-on-parse-error     Abort if any file fails to parse, instead of skipping it with a warning.
+  --unknown-words-baseline=<file>  Only report unknown words not listed in the given file,
+                            which is then updated with all unknown words of this run.
+  --suppressions=<file>     Drop the mistakes listed in the given file, one `path:line:column word`
+                            or `path:line word` per line.
+  --print-unknown-words     Print all unknown words with the number of their occurrences at the end,
+                            most frequent first, one `count<TAB>word` per line to stderr.
+  --ignore-word=<word>      Accept the word within all files for this run only, without touching
+                            any dictionary. May be given multiple times.
+  --rules                   Print how often each rule of a detector with rules, currently only
+                            `languagetool`, fired at the end to stderr, one `count<TAB>rule` per line.
+  --disable-rules=<rules>   Drop the mistakes of the given comma separated rule ids, i.e.
+                            `--disable-rules=EN_A_VS_AN,WHITESPACE_RULE`.
+  --min-score=<score>       Drop mistakes whose detector reported a confidence below `score`, between 0.0
+                            and 1.0. Mistakes without a confidence are always kept.
+  --max-mistakes=<n>        Succeed as long as no more than `n` mistakes are found, to ratchet the
+                            number of mistakes down over time. Exceeding it exits with `--code`.
+  -j --jobs=<jobs>          Number of threads to check with, `0` picks the available parallelism,
+                            which honours CPU limits of containers. [default: 0]
+  --cache=<path>            Reuse the mistakes of unchanged doc comments and files found by previous
+                            runs, saved to `path`. A change of the configuration or a dictionary
+                            discards the cache.
+  --timings                 Print how long the traversal, each checker and printing or applying
+                            the mistakes took to stderr, unless `-q` is given.
+  --dump-chunks             Print the content of all chunks with the spans of their fragments, and
+                            their plain text with its mapping, without checking them. For debugging.
+  --resume                  Continue an interactive fix session saved with `s`, decisions of
+                            the saved session are applied without asking again.
+  --replace-with-first      Apply the first replacement of every mistake without asking, the ones
+                            flagged by multiple checkers are left as they are.
+  --backup                  Copy each file to `<file>.bak` before fixing it, an existing backup is
+                            kept and the next free `<file>.bak.<n>` is used instead.
+  --backup-suffix=<suffix>  Suffix of the backups, which must not end with the extension of a
+                            checked file. [default: .bak]
 "#;
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// The exit code of a run, which lets scripts tell mistakes found from
+/// the tool failing.
+///
+/// * `0` if no mistakes were found, or at most `--max-mistakes`
+/// * `--code` if mistakes were found, `0` unless given
+/// * `2` if the arguments, the configuration or a file could not be processed
+/// * `130` if interrupted by a signal or aborted by the user
 pub enum ExitCode {
     Success,
     Signal,
     Custom(u8),
-    // Failure is already default for `Err(anyhow::Error)`
+    /// The run failed with an error, the arguments were invalid or could not
+    /// be parsed.
+    Failure,
 }
 
 impl ExitCode {
@@ -66,6 +160,17 @@ impl ExitCode {
             Self::Success => 0u8,
             Self::Signal => 130u8,
             Self::Custom(code) => code,
+            Self::Failure => 2u8,
+        }
+    }
+
+    /// Map how the action finished to an exit code, where `code` is used
+    /// if more than `max_mistakes` mistakes remain.
+    fn from_finish(finish: Finish, code: u8, max_mistakes: usize) -> Self {
+        match finish {
+            Finish::MistakeCount(n) if n <= max_mistakes => Self::Success,
+            Finish::MistakeCount(_n) => Self::Custom(code),
+            Finish::Abort => Self::Signal,
         }
     }
 }
@@ -81,11 +186,49 @@ struct Args {
     flag_help: bool,
     flag_checkers: Option<String>,
     flag_cfg: Option<PathBuf>,
+    flag_profile: Option<String>,
     flag_force: bool,
     flag_user: bool,
     flag_skip_readme: bool,
+    flag_skip_markdown: bool,
+    flag_hidden: bool,
+    flag_follow_symlinks: bool,
+    flag_stdin: bool,
+    flag_stdin_filename: Option<PathBuf>,
     flag_code: u8,
+    flag_context_lines: usize,
+    flag_width: Option<usize>,
+    flag_format: String,
+    flag_count_only: bool,
+    flag_quiet_success: bool,
+    flag_list_files: bool,
+    flag_fail_on_empty: bool,
+    flag_explain: bool,
+    flag_staged: bool,
+    flag_diff_range: Option<String>,
+    flag_no_default_search_dirs: bool,
+    flag_merge_adjacent: bool,
+    flag_fail_on_parse_error: bool,
+    flag_unknown_words_baseline: Option<PathBuf>,
+    flag_suppressions: Option<PathBuf>,
+    flag_print_unknown_words: bool,
+    flag_ignore_word: Vec<String>,
+    flag_rules: bool,
+    flag_disable_rules: Option<String>,
+    flag_min_score: Option<f32>,
+    flag_max_mistakes: Option<usize>,
+    flag_jobs: usize,
+    flag_cache: Option<PathBuf>,
+    flag_timings: bool,
+    flag_dump_chunks: bool,
+    flag_resume: bool,
+    flag_replace_with_first: bool,
+    flag_backup: bool,
+    flag_backup_suffix: String,
     flag_stdout: bool,
+    flag_check: bool,
+    flag_list_quirks: bool,
+    flag_strict_config: bool,
     cmd_fix: bool,
     cmd_check: bool,
     cmd_config: bool,
@@ -100,7 +243,7 @@ fn signal_handler() {
                 if let Err(e) = action::interactive::ScopedRaw::restore_terminal() {
                     warn!("Failed to restore terminal: {}", e);
                 }
-                std::process::exit(130);
+                std::process::exit(ExitCode::Signal.as_u8() as i32);
             }
             sig => warn!("Received unhandled signal {}, ignoring", sig),
         }
@@ -142,8 +285,131 @@ fn parse_args(mut argv_iter: impl Iterator<Item = String>) -> Result<Args, docop
     })
 }
 
+/// Environment variable to provide the configuration file path, unless
+/// given on the command line.
+const CONFIG_ENV_VAR: &str = "CARGO_SPELLCHECK_CONFIG";
+
+/// Directories searched for the configuration file of a profile, in order.
+///
+/// The directory given with `--cfg` replaces the default ones, the current
+/// directory and the user configuration directory.
+fn profile_dirs(flag_cfg: Option<&PathBuf>) -> anyhow::Result<Vec<PathBuf>> {
+    if let Some(dir) = flag_cfg {
+        if !dir.is_dir() {
+            return Err(anyhow::anyhow!(
+                "`--profile` requires `--cfg` to be a directory, but {} is not",
+                dir.display()
+            ));
+        }
+        return Ok(vec![dir.to_owned()]);
+    }
+    let mut dirs = vec![std::env::current_dir()?];
+    if let Some(dir) = Config::default_path()?.parent() {
+        dirs.push(dir.to_owned());
+    }
+    Ok(dirs)
+}
+
+/// Locate `cargo_spellcheck.<profile>.toml` within the first of `dirs`
+/// containing it.
+fn profile_path(profile: &str, dirs: &[PathBuf]) -> anyhow::Result<PathBuf> {
+    if profile.is_empty() || profile.contains(std::path::is_separator) {
+        return Err(anyhow::anyhow!("Invalid profile name {:?}", profile));
+    }
+    let name = format!("cargo_spellcheck.{}.toml", profile);
+    let candidates = dirs.iter().map(|dir| dir.join(&name)).collect::<Vec<_>>();
+    if let Some(path) = candidates.iter().find(|path| path.is_file()) {
+        trace!(
+            "Using configuration file {} of profile {}",
+            path.display(),
+            profile
+        );
+        return Ok(path.to_owned());
+    }
+    Err(anyhow::anyhow!(
+        "Configuration file of profile {} does not exist, searched:\n{}",
+        profile,
+        candidates
+            .iter()
+            .map(|path| format!("  {}", path.display()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    ))
+}
+
+/// Select the configuration file path, returns `true` if it was
+/// explicitly provided either by argument, profile or environment.
+///
+/// A `profile` takes precedence over the environment, and is searched for
+/// in the directory given by `flag_cfg` if any.
+fn config_path(
+    flag_cfg: Option<&PathBuf>,
+    profile: Option<&str>,
+    env_cfg: Option<PathBuf>,
+) -> anyhow::Result<(bool, PathBuf)> {
+    if let Some(profile) = profile {
+        Ok((true, profile_path(profile, &profile_dirs(flag_cfg)?)?))
+    } else if let Some(path) = flag_cfg {
+        trace!("Using configuration file {} from --cfg", path.display());
+        Ok((true, path.to_owned()))
+    } else if let Some(path) = env_cfg {
+        trace!(
+            "Using configuration file {} from ${}",
+            path.display(),
+            CONFIG_ENV_VAR
+        );
+        Ok((true, path))
+    } else {
+        let path = Config::default_path()?;
+        trace!("Using default configuration file {}", path.display());
+        Ok((false, path))
+    }
+}
+
+/// Load the unknown words of a previous run, one per line.
+///
+/// A missing file is treated as an empty set.
+fn load_unknown_words(path: &Path) -> anyhow::Result<HashSet<String>> {
+    if !path.exists() {
+        debug!("No unknown words baseline at {} yet", path.display());
+        return Ok(HashSet::new());
+    }
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        anyhow::anyhow!("Failed to read unknown words baseline {}", path.display()).context(e)
+    })?;
+    Ok(content
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_owned())
+        .collect())
+}
+
+/// Store `words` as the unknown words baseline, one per line.
+fn store_unknown_words<'w>(
+    path: &Path,
+    words: impl IntoIterator<Item = &'w String>,
+) -> anyhow::Result<()> {
+    let content = words.into_iter().fold(String::new(), |mut acc, word| {
+        acc.push_str(word);
+        acc.push('\n');
+        acc
+    });
+    std::fs::write(path, content).map_err(|e| {
+        anyhow::anyhow!("Failed to write unknown words baseline {}", path.display()).context(e)
+    })
+}
+
 fn run() -> anyhow::Result<ExitCode> {
-    let args = parse_args(std::env::args()).unwrap_or_else(|e| e.exit());
+    let args = parse_args(std::env::args()).unwrap_or_else(|e| {
+        if e.fatal() {
+            eprintln!("{}", e);
+            std::process::exit(ExitCode::Failure.as_u8() as i32)
+        } else {
+            // `--help` and `--version`
+            e.exit()
+        }
+    });
 
     let verbosity = match args.flag_verbose {
         _ if args.flag_quiet => log::LevelFilter::Off,
@@ -154,9 +420,13 @@ fn run() -> anyhow::Result<ExitCode> {
         _ => log::LevelFilter::Error,
     };
 
-    env_logger::from_env(env_logger::Env::new().filter_or("CARGO_SPELLCHECK", "warn"))
-        .filter_level(verbosity)
-        .init();
+    let mut logger =
+        env_logger::from_env(env_logger::Env::new().filter_or("CARGO_SPELLCHECK", "warn"));
+    logger.filter_level(verbosity);
+    if args.flag_explain {
+        logger.filter_module("explain", log::LevelFilter::Debug);
+    }
+    logger.init();
 
     if args.flag_version {
         println!("cargo-spellcheck {}", env!("CARGO_PKG_VERSION"));
@@ -170,31 +440,95 @@ fn run() -> anyhow::Result<ExitCode> {
 
     std::thread::spawn(move || signal_handler());
 
-    let checkers = |config: &mut Config| {
+    let checkers = |config: &mut Config| -> anyhow::Result<()> {
         // overwrite checkers
-        if let Some(checkers) = args.flag_checkers.clone() {
+        if let Some(checkers) = args.flag_checkers.as_ref() {
             let checkers = checkers
                 .split(',')
-                .map(|checker| checker.to_lowercase())
-                .collect::<Vec<_>>();
-            if !checkers.contains(&"hunspell".to_owned()) {
+                .map(|checker| checker.trim().parse::<Detector>())
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            if !checkers.contains(&Detector::Hunspell) {
                 if !config.hunspell.take().is_some() {
                     warn!("Hunspell was never configured.")
                 }
+            } else if let Some(ref mut hunspell) = config.hunspell {
+                // explicitly requested, regardless of the configured state
+                hunspell.enabled = Some(true);
             }
-            if !checkers.contains(&"languagetool".to_owned()) {
+            if !checkers.contains(&Detector::LanguageTool) {
                 if !config.languagetool.take().is_some() {
                     warn!("Languagetool was never configured.")
                 }
+            } else if let Some(ref mut languagetool) = config.languagetool {
+                languagetool.enabled = Some(true);
+            }
+            if !checkers.contains(&Detector::Reflow) {
+                config.reflow.take();
+            } else if let Some(ref mut reflow) = config.reflow {
+                reflow.enabled = Some(true);
+            } else {
+                // explicitly requested, the configuration is optional
+                config.reflow = Some(Default::default());
+            }
+            if !checkers.contains(&Detector::RepeatedWord) {
+                config.repeated_words.take();
+            } else if let Some(ref mut repeated_words) = config.repeated_words {
+                repeated_words.enabled = Some(true);
+            } else {
+                // explicitly requested, the configuration is optional
+                config.repeated_words = Some(Default::default());
+            }
+            if !checkers.contains(&Detector::External) {
+                config.external.take();
+            } else if let Some(ref mut external) = config.external {
+                external.enabled = Some(true);
+            } else {
+                warn!("External was never configured, it requires a command.")
             }
         }
+        Ok(())
     };
 
+    // handle `config --list-quirks`
+    if args.cmd_config && args.flag_list_quirks {
+        for quirk in checker::Quirk::ALL {
+            let (token, checked_as) = quirk.example();
+            println!("{}", quirk);
+            println!("    {}", quirk.description());
+            println!("    `{}` is checked as `{}`", token, checked_as);
+        }
+        println!();
+        println!("Usage within the configuration file:");
+        println!();
+        println!("[hunspell]");
+        println!(
+            "quirks = [{}]",
+            checker::Quirk::ALL
+                .iter()
+                .map(|quirk| format!("\"{}\"", quirk))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        return Ok(ExitCode::Success);
+    }
+
+    // handle `config --check`, before `config` writes anything
+    if args.cmd_config && args.flag_check {
+        let (_explicit_cfg, config_path) = config_path(
+            args.flag_cfg.as_ref(),
+            args.flag_profile.as_deref(),
+            std::env::var_os(CONFIG_ENV_VAR).map(PathBuf::from),
+        )?;
+        Config::load_from_strict(&config_path)?;
+        println!("Configuration file {} is valid", config_path.display());
+        return Ok(ExitCode::Success);
+    }
+
     // handle `config` sub command
     if args.cmd_config {
         trace!("Configuration chore");
         let mut config = Config::full();
-        checkers(&mut config);
+        checkers(&mut config)?;
 
         let config_path = match args.flag_cfg.as_ref() {
             Some(path) => Some(path.to_owned()),
@@ -203,7 +537,12 @@ fn run() -> anyhow::Result<ExitCode> {
         };
 
         if args.flag_stdout {
-            println!("{}", config.to_toml()?);
+            // the format is implied by the target path, if any
+            let format = config_path
+                .as_deref()
+                .map(config::ConfigFormat::of)
+                .unwrap_or(config::ConfigFormat::Toml);
+            println!("{}", config.to_format(format)?);
             return Ok(ExitCode::Success);
         }
 
@@ -222,10 +561,14 @@ fn run() -> anyhow::Result<ExitCode> {
         trace!("Not configuration sub command");
     }
 
-    let (explicit_cfg, config_path) = match args.flag_cfg.as_ref() {
-        Some(path) => (true, path.to_owned()),
-        _ => (false, Config::default_path()?),
-    };
+    let (explicit_cfg, config_path) = config_path(
+        args.flag_cfg.as_ref(),
+        args.flag_profile.as_deref(),
+        std::env::var_os(CONFIG_ENV_VAR).map(PathBuf::from),
+    )?;
+    if args.flag_strict_config && config_path.is_file() {
+        Config::load_from_strict(&config_path)?;
+    }
     let mut config = match Config::load_from(&config_path) {
         Ok(config) => config,
         Err(e) => {
@@ -244,38 +587,384 @@ fn run() -> anyhow::Result<ExitCode> {
         }
     };
 
-    checkers(&mut config);
+    checkers(&mut config)?;
+
+    if args.flag_no_default_search_dirs {
+        if let Some(ref mut hunspell) = config.hunspell {
+            hunspell.skip_default_search_dirs = Some(true);
+        }
+    }
 
     // extract operation mode
     let action = if args.cmd_fix || args.flag_fix {
-        Action::Fix
+        if args.flag_count_only {
+            return Err(anyhow::anyhow!(
+                "`--count-only` can not be combined with `--fix`"
+            ));
+        }
+        let backup = if args.flag_backup {
+            validate_backup_suffix(&args.flag_backup_suffix)?;
+            Some(args.flag_backup_suffix.clone())
+        } else {
+            None
+        };
+        if args.flag_replace_with_first {
+            Action::ReplaceWithFirst { backup }
+        } else {
+            Action::Fix {
+                resume: args.flag_resume,
+                backup,
+            }
+        }
+    } else if args.flag_count_only {
+        Action::CountOnly
     } else {
         // check
         Action::Check
     };
 
+    let format = args.flag_format.parse::<Format>()?;
+    if let Some(width) = args.flag_width {
+        if width == 0 {
+            return Err(anyhow::anyhow!("`--width` must be at least 1"));
+        }
+        set_terminal_width(width);
+    }
+
     trace!("Executing: {:?} with {:?}", action, &config);
 
-    let combined = traverse::extract(
-        args.arg_paths,
-        args.flag_recursive,
-        args.flag_skip_readme,
-        &config,
-    )?;
+    if args.flag_stdin_filename.is_some() && !args.flag_stdin {
+        return Err(anyhow::anyhow!("`--stdin-filename` requires `--stdin`"));
+    }
+    if args.flag_stdin
+        && (!args.arg_paths.is_empty() || args.flag_staged || args.flag_diff_range.is_some())
+    {
+        return Err(anyhow::anyhow!(
+            "`--stdin` can not be combined with paths, `--staged` or `--diff-range`"
+        ));
+    }
+    if args.flag_stdin && (args.cmd_fix || args.flag_fix || args.flag_replace_with_first) {
+        // fixes would be written to the file the buffer is attributed to
+        return Err(anyhow::anyhow!(
+            "`--stdin` can not be combined with `--fix`"
+        ));
+    }
 
-    let suggestion_set = checker::check(&combined, &config)?;
+    let changed_lines = if let Some(ref range) = args.flag_diff_range {
+        if args.flag_staged {
+            return Err(anyhow::anyhow!(
+                "`--staged` can not be combined with `--diff-range`"
+            ));
+        }
+        Some(git::changed_lines(range)?)
+    } else {
+        None
+    };
 
-    let finish = action.run(suggestion_set, &config)?;
+    let staged = if args.flag_staged {
+        if !args.arg_paths.is_empty() {
+            return Err(anyhow::anyhow!(
+                "`--staged` can not be combined with explicit paths"
+            ));
+        }
+        if args.cmd_fix || args.flag_fix {
+            // mistakes are located within the staged content, not the working tree
+            return Err(anyhow::anyhow!(
+                "`--staged` can not be combined with `--fix`"
+            ));
+        }
+        let staged = git::staged_files()?;
+        if staged.is_empty() {
+            info!("No staged files to check");
+            return Ok(ExitCode::Success);
+        }
+        Some(staged)
+    } else {
+        None
+    };
+    let (paths, recursive) =
+        if let (Some(changed_lines), true) = (&changed_lines, args.arg_paths.is_empty()) {
+            let changed = changed_lines.checkable_files();
+            if changed.is_empty() {
+                info!("No changed files to check");
+                return Ok(ExitCode::Success);
+            }
+            (changed, false)
+        } else {
+            (args.arg_paths, args.flag_recursive)
+        };
+
+    let print_timings = args.flag_timings && !args.flag_quiet;
+    let mut timings = Timings::new();
+    let traversal = Instant::now();
+    let mut combined = if let Some(staged) = staged {
+        // the staged content, not the one of the working tree
+        let mut docs = Documentation::new();
+        for (path, content) in staged {
+            if traverse::FileKind::of(&path) == Some(traverse::FileKind::PlainText)
+                && !config.check_plain_text()
+            {
+                debug!(
+                    "Skipping plain text file {}, `plain_text.enabled` is not set",
+                    path.display()
+                );
+                continue;
+            }
+            docs.join(traverse::extract_content(path, content, &config)?);
+        }
+        docs.configure_markdown(config.markdown());
+        docs
+    } else if args.flag_stdin {
+        let mut content = String::new();
+        std::io::stdin()
+            .read_to_string(&mut content)
+            .map_err(|e| anyhow::anyhow!("Failed to read from stdin").context(e))?;
+        let path = match args.flag_stdin_filename {
+            Some(ref path) if path.is_relative() => std::env::current_dir()?.join(path),
+            Some(ref path) => path.to_owned(),
+            None => PathBuf::from("<stdin>"),
+        };
+        traverse::extract_content(path, content, &config)?
+    } else {
+        traverse::extract(
+            paths,
+            recursive,
+            args.flag_skip_readme,
+            args.flag_skip_markdown,
+            args.flag_hidden,
+            args.flag_follow_symlinks,
+            &config,
+        )?
+    };
+    timings.record("traversal", traversal.elapsed());
 
-    match finish {
-        Finish::MistakeCount(0) => Ok(ExitCode::Success),
-        Finish::MistakeCount(_n) => Ok(ExitCode::Custom(args.flag_code)),
-        Finish::Abort => Ok(ExitCode::Signal),
+    let unparsable = combined.unparsable();
+    if !unparsable.is_empty() && args.flag_fail_on_parse_error {
+        let (origin, reason) = &unparsable[0];
+        return Err(anyhow::anyhow!(
+            "Failed to parse {} files, first {}: {}",
+            unparsable.len(),
+            origin,
+            reason
+        ));
+    }
+    // reported along with the other summaries once done
+    let skipped = unparsable.len();
+
+    if args.flag_list_files {
+        let mut empty = true;
+        for (origin, _chunks) in combined.iter() {
+            empty = false;
+            match origin {
+                ContentOrigin::RustDocTest(path, span) => {
+                    println!("{} {}:{}", origin.kind(), path.display(), span.start.line)
+                }
+                origin => println!("{} {}", origin.kind(), origin),
+            }
+        }
+        if empty && args.flag_fail_on_empty {
+            return Err(anyhow::anyhow!("No files to check"));
+        }
+        return Ok(ExitCode::Success);
+    }
+
+    if args.flag_dump_chunks {
+        print!("{}", combined.dump_chunks());
+        return Ok(ExitCode::Success);
+    }
+
+    if !args.flag_ignore_word.is_empty() {
+        debug!("Accepting {:?} within all files", &args.flag_ignore_word);
+        combined.accept_words_everywhere(&args.flag_ignore_word);
+    }
+
+    let available = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    let jobs = effective_jobs(args.flag_jobs, available);
+    debug!("Using {} of {} available jobs", jobs, available);
+
+    let mut cache = match args.flag_cache {
+        Some(ref _path) if config.is_enabled(Detector::LanguageTool) => {
+            warn!("Not using `--cache`, since LanguageTool may change its results at any time");
+            None
+        }
+        Some(ref path) => Some(Cache::load(path, &config)?),
+        None => None,
+    };
+    let (to_check, cached) = if let Some(ref mut cache) = cache {
+        let (to_check, cached) = cache.split(&combined);
+        (Some(to_check), cached)
+    } else {
+        (None, SuggestionSet::new())
+    };
+    let checked = to_check.as_ref().unwrap_or(&combined);
+    let mut suggestion_set = checker::check(checked, &config, jobs, &mut timings)?;
+    if let (Some(cache), Some(path)) = (cache.as_mut(), args.flag_cache.as_ref()) {
+        cache.update(checked, &suggestion_set);
+        cache.store(path)?;
+        suggestion_set.join(cached);
+        suggestion_set.sort();
+    }
+
+    // single occurrences accepted with `<!-- spellcheck: ok word -->`
+    suggestion_set.retain(|_origin, suggestion| !suggestion.chunk.is_accepted(&suggestion.range));
+
+    // regions disabled with `spellcheck: off`
+    suggestion_set.retain(|origin, suggestion| {
+        let (first, last) = suggestion.lines();
+        !combined.is_disabled(origin.as_path(), first, last)
+    });
+
+    if let Some(ref path) = args.flag_suppressions {
+        let mut suppressions = Suppressions::load(path)?;
+        suggestion_set.retain(|origin, suggestion| !suppressions.suppresses(origin, suggestion));
+        let checked = combined
+            .iter()
+            .map(|(origin, _chunks)| origin.as_path())
+            .collect::<Vec<_>>();
+        for suppression in suppressions.stale(&checked) {
+            warn!(
+                "Suppression `{}` of {} does not match any mistake",
+                suppression,
+                path.display()
+            );
+        }
+    }
+
+    if let Some(ref baseline) = args.flag_unknown_words_baseline {
+        let known = load_unknown_words(baseline)?;
+        let unknown = suggestion_set.unknown_words();
+        suggestion_set.retain(|_origin, suggestion| !known.contains(&suggestion.word()));
+        info!(
+            "{} of {} unknown words are new",
+            unknown.iter().filter(|word| !known.contains(*word)).count(),
+            unknown.len()
+        );
+        store_unknown_words(baseline, &unknown)?;
+    }
+
+    if let Some(ref changed_lines) = changed_lines {
+        suggestion_set.retain(|origin, suggestion| {
+            let (first, last) = suggestion.lines();
+            changed_lines.intersects(origin.as_path(), first, last)
+        });
+    }
+
+    if let Some(ref disabled) = args.flag_disable_rules {
+        suggestion_set.disable_rules(disabled);
+    }
+
+    if let Some(min_score) = args.flag_min_score {
+        let min_score = Score::new(min_score).ok_or_else(|| {
+            anyhow::anyhow!(
+                "--min-score must be within 0.0 and 1.0, found {}",
+                min_score
+            )
+        })?;
+        suggestion_set.retain(|_origin, suggestion| {
+            suggestion
+                .score
+                .map(|score| score >= min_score)
+                .unwrap_or(true)
+        });
+    }
+
+    if args.flag_merge_adjacent {
+        suggestion_set.merge_adjacent();
+    }
+
+    if args.flag_quiet_success && suggestion_set.total_count() == 0 {
+        debug!("No mistakes found, printing nothing");
+        if print_timings {
+            eprint!("{}", timings.table());
+        }
+        return Ok(ExitCode::Success);
+    }
+
+    let unknown_word_counts = if args.flag_print_unknown_words {
+        suggestion_set.unknown_word_counts()
+    } else {
+        Vec::new()
+    };
+    let rule_counts = if args.flag_rules {
+        suggestion_set.rule_counts()
+    } else {
+        Vec::new()
+    };
+
+    let checked = combined
+        .iter()
+        .map(|(origin, _chunks)| origin.clone())
+        .collect::<Vec<_>>();
+    let context_lines = args.flag_context_lines;
+    let verbose = args.flag_verbose > 0 && !args.flag_quiet;
+    let finish = timings.measure("action", || {
+        action.run(
+            suggestion_set,
+            &checked,
+            &config,
+            context_lines,
+            format,
+            verbose,
+        )
+    })?;
+
+    // stdout is reserved for the report, which might be machine readable
+    for (count, word) in unknown_word_counts {
+        eprintln!("{}\t{}", count, word);
+    }
+    for (count, rule_id) in rule_counts {
+        eprintln!("{}\t{}", count, rule_id);
+    }
+
+    if print_timings {
+        eprint!("{}", timings.table());
+    }
+
+    let max_mistakes = args.flag_max_mistakes.unwrap_or(0);
+    if let (Some(max_mistakes), Finish::MistakeCount(count), false) =
+        (args.flag_max_mistakes, finish, args.flag_quiet)
+    {
+        eprintln!(
+            "Found {} mistakes, {} are allowed at most",
+            count, max_mistakes
+        );
+    }
+    if skipped > 0 && !args.flag_quiet {
+        eprintln!(
+            "Skipped {} files which failed to parse, use `--fail-on-parse-error` to abort instead",
+            skipped
+        );
+    }
+
+    Ok(ExitCode::from_finish(finish, args.flag_code, max_mistakes))
+}
+
+/// The number of threads to use for `requested` ones, where `0` picks the
+/// available parallelism. More threads than available are not useful,
+/// so the result is clamped to `1..=available`.
+fn effective_jobs(requested: usize, available: usize) -> usize {
+    let available = available.max(1);
+    match requested {
+        0 => available,
+        n if n > available => {
+            warn!(
+                "Requested {} jobs, but only {} are available, using {}",
+                n, available, available
+            );
+            available
+        }
+        n => n,
     }
 }
 
-fn main() -> anyhow::Result<()> {
-    std::process::exit(run()?.as_u8() as i32)
+fn main() {
+    let code = run().unwrap_or_else(|e| {
+        eprintln!("Error: {:?}", e);
+        ExitCode::Failure
+    });
+    std::process::exit(code.as_u8() as i32)
 }
 
 #[cfg(test)]
@@ -301,9 +990,185 @@ mod tests {
             "cargo-spellcheck -q fix Cargo.toml",
             "cargo spellcheck -v fix Cargo.toml",
             "cargo spellcheck -m 11 check",
+            "cargo spellcheck check --context-lines=3",
+            "cargo spellcheck --explain check",
+            "cargo spellcheck check --staged",
+            "cargo spellcheck check --diff-range=main..HEAD",
+            "cargo spellcheck check --unknown-words-baseline=words.txt",
+            "cargo spellcheck check --suppressions=suppressions.txt",
+            "cargo spellcheck fix --suppressions=suppressions.txt",
+            "cargo-spellcheck check --stdin --stdin-filename=src/lib.rs",
+            "cargo spellcheck check -j 0",
+            "cargo-spellcheck config --check",
+            "cargo-spellcheck config --list-quirks",
+            "cargo spellcheck check --strict-config",
+            "cargo spellcheck check --print-unknown-words",
+            "cargo spellcheck check --follow-symlinks",
+            "cargo spellcheck check --format=github",
+            "cargo spellcheck check --min-score=0.5",
+            "cargo spellcheck fix --jobs=4",
+            "cargo spellcheck check --quiet-success --format=checkstyle",
+            "cargo spellcheck --count-only --quiet-success",
+            "cargo spellcheck check --timings",
+            "cargo spellcheck fix --timings",
+            "cargo spellcheck check --profile=strict",
+            "cargo spellcheck --profile strict",
+            "cargo-spellcheck config --check --profile=lenient",
+            "cargo spellcheck fix --resume",
+            "cargo spellcheck --fix --resume",
+            "cargo spellcheck fix --replace-with-first",
+            "cargo spellcheck --fix --replace-with-first",
+            "cargo spellcheck fix --backup",
+            "cargo spellcheck fix --backup --backup-suffix=.orig",
+            "cargo spellcheck --fix --replace-with-first --backup",
+            "cargo spellcheck check --dump-chunks src/lib.rs",
+            "cargo spellcheck --dump-chunks",
+            "cargo-spellcheck config --stdout --cfg=spellcheck.yaml",
+            "cargo spellcheck check --max-mistakes=12 --code=1",
+            "cargo spellcheck --max-mistakes 3",
+            "cargo spellcheck fix --ignore-word=tokio",
+            "cargo spellcheck check --rules --disable-rules=EN_A_VS_AN,WHITESPACE_RULE",
+            "cargo spellcheck fix --disable-rules=EN_A_VS_AN",
+            "cargo spellcheck --rules",
+            "cargo spellcheck check --width=60",
+            "cargo spellcheck fix --width 120",
+            "cargo spellcheck --width=40 --context-lines=1",
+            "cargo spellcheck check --cache=.spellcheck-cache.json",
+            "cargo spellcheck fix --cache=.spellcheck-cache.json",
         ];
         for command in commands {
             assert!(parse_args(commandline_to_iter(command)).is_ok());
         }
+
+        let args = parse_args(commandline_to_iter(
+            "cargo spellcheck check --ignore-word foo --ignore-word=bar",
+        ))
+        .unwrap();
+        assert_eq!(args.flag_ignore_word, vec!["foo", "bar"]);
+
+        // fixes can not be applied to the content passed via stdin
+        let args = parse_args(commandline_to_iter("cargo-spellcheck check --stdin")).unwrap();
+        assert!(args.cmd_check && args.flag_stdin);
+        for command in &[
+            "cargo-spellcheck fix --stdin",
+            "cargo-spellcheck --fix --stdin --stdin-filename=src/lib.rs",
+            "cargo-spellcheck --fix --replace-with-first --stdin",
+            "cargo-spellcheck --stdin",
+        ] {
+            assert!(parse_args(commandline_to_iter(command)).is_err());
+        }
+    }
+
+    #[test]
+    fn config_path_precedence() {
+        let flag = PathBuf::from("/flag/cfg.toml");
+        let env = PathBuf::from("/env/cfg.toml");
+        assert_eq!(
+            config_path(Some(&flag), None, Some(env.clone())).unwrap(),
+            (true, flag.clone())
+        );
+        assert_eq!(
+            config_path(None, None, Some(env.clone())).unwrap(),
+            (true, env)
+        );
+        if let Ok(default) = Config::default_path() {
+            assert_eq!(config_path(None, None, None).unwrap(), (false, default));
+        }
+        // `--cfg` must be the directory to search for profiles
+        assert!(config_path(Some(&flag), Some("strict"), None).is_err());
+    }
+
+    #[test]
+    fn profiles() {
+        let root =
+            std::env::temp_dir().join(format!("cargo-spellcheck-profiles-{}", std::process::id()));
+        let project = root.join("project");
+        let user = root.join("user");
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::create_dir_all(&user).unwrap();
+        std::fs::write(project.join("cargo_spellcheck.strict.toml"), "").unwrap();
+        std::fs::write(user.join("cargo_spellcheck.strict.toml"), "").unwrap();
+        std::fs::write(user.join("cargo_spellcheck.lenient.toml"), "").unwrap();
+        let dirs = [project.clone(), user.clone()];
+
+        let strict = profile_path("strict", &dirs);
+        let lenient = profile_path("lenient", &dirs);
+        let missing = profile_path("missing", &dirs);
+        let explicit = config_path(Some(&user), Some("strict"), None);
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(
+            strict.unwrap(),
+            project.join("cargo_spellcheck.strict.toml")
+        );
+        assert_eq!(lenient.unwrap(), user.join("cargo_spellcheck.lenient.toml"));
+        let missing = missing.unwrap_err().to_string();
+        assert!(missing.contains("missing"));
+        assert!(missing.contains(
+            &user
+                .join("cargo_spellcheck.missing.toml")
+                .display()
+                .to_string()
+        ));
+        assert_eq!(
+            explicit.unwrap(),
+            (true, user.join("cargo_spellcheck.strict.toml"))
+        );
+        assert!(profile_path("../strict", &dirs).is_err());
+    }
+
+    #[test]
+    fn unknown_words_roundtrip() {
+        let path = std::env::temp_dir().join(format!(
+            "cargo-spellcheck-unknown-words-{}",
+            std::process::id()
+        ));
+        assert!(load_unknown_words(&path).unwrap().is_empty());
+        let words = vec!["Teh".to_owned(), "quikc".to_owned()];
+        store_unknown_words(&path, &words).unwrap();
+        let loaded = load_unknown_words(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(loaded, words.into_iter().collect::<HashSet<_>>());
+    }
+
+    #[test]
+    fn jobs() {
+        assert_eq!(effective_jobs(0, 8), 8);
+        assert_eq!(effective_jobs(3, 8), 3);
+        assert_eq!(effective_jobs(16, 8), 8);
+        assert_eq!(effective_jobs(0, 0), 1);
+    }
+
+    #[test]
+    fn exit_code_from_finish() {
+        assert_eq!(
+            ExitCode::from_finish(Finish::MistakeCount(0), 11, 0),
+            ExitCode::Success
+        );
+        assert_eq!(
+            ExitCode::from_finish(Finish::MistakeCount(2), 11, 0),
+            ExitCode::Custom(11)
+        );
+        assert_eq!(
+            ExitCode::from_finish(Finish::Abort, 11, 0),
+            ExitCode::Signal
+        );
+        // up to the threshold of `--max-mistakes`
+        assert_eq!(
+            ExitCode::from_finish(Finish::MistakeCount(5), 11, 5),
+            ExitCode::Success
+        );
+        assert_eq!(
+            ExitCode::from_finish(Finish::MistakeCount(6), 11, 5),
+            ExitCode::Custom(11)
+        );
+        assert_eq!(
+            ExitCode::from_finish(Finish::Abort, 11, 5),
+            ExitCode::Signal
+        );
+        assert_eq!(ExitCode::Success.as_u8(), 0);
+        assert_eq!(ExitCode::Custom(1).as_u8(), 1);
+        assert_eq!(ExitCode::Failure.as_u8(), 2);
+        assert_eq!(ExitCode::Signal.as_u8(), 130);
     }
 }
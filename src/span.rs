@@ -60,11 +60,6 @@ impl Span {
         Ok(range)
     }
 
-    /// Check if `self` span covers provided `line` number, which is 1-indexed.
-    pub fn covers_line(&self, line: usize) -> bool {
-        self.end.line <= line && line >= self.start.line
-    }
-
     /// If this one resembles a single line, returns the a `Some(len)` value.
     /// For multilines this cannot account for the length.
     pub fn one_line_len(&self) -> Option<usize> {
@@ -109,6 +104,31 @@ impl Span {
         }
         bail!("The chunk internal map from range to span did not contain an overlapping entry")
     }
+
+    /// Convert `self` into a range of byte offsets within `source`, the
+    /// content the span was created from.
+    ///
+    /// Columns count characters, so multi byte characters are accounted for.
+    /// Line endings, including the `\r` of a `\r\n`, are not part of any
+    /// column a span is expected to start or end at.
+    pub fn to_byte_range(&self, source: &str) -> Result<Range> {
+        let mut start = None;
+        for ((byte_offset, c), (_c, _idx, cursor)) in source
+            .char_indices()
+            .zip(util::iter_with_line_column(source))
+        {
+            if cursor == self.start {
+                start = Some(byte_offset);
+            }
+            if cursor == self.end {
+                if let Some(start) = start {
+                    return Ok(start..(byte_offset + c.len_utf8()));
+                }
+                bail!("End of {:?} precedes its start", self)
+            }
+        }
+        bail!("{:?} is not contained in the source", self)
+    }
 }
 
 use std::convert::{From, TryInto};
@@ -491,4 +511,46 @@ three"#;
             assert_eq!(range, 5..7);
         }
     }
+
+    #[test]
+    fn to_byte_range_multibyte() {
+        const SOURCE: &'static str = "Grüße 🦀\r\nzwei Äpfel\r\n";
+
+        let span = Span {
+            start: LineColumn { line: 1, column: 0 },
+            end: LineColumn { line: 1, column: 4 },
+        };
+        let range = span.to_byte_range(SOURCE).unwrap();
+        assert_eq!(&SOURCE[range], "Grüße");
+
+        let span = Span {
+            start: LineColumn { line: 1, column: 6 },
+            end: LineColumn { line: 1, column: 6 },
+        };
+        let range = span.to_byte_range(SOURCE).unwrap();
+        assert_eq!(&SOURCE[range], "🦀");
+
+        let span = Span {
+            start: LineColumn { line: 2, column: 5 },
+            end: LineColumn { line: 2, column: 9 },
+        };
+        let range = span.to_byte_range(SOURCE).unwrap();
+        assert_eq!(&SOURCE[range], "Äpfel");
+
+        let span = Span {
+            start: LineColumn { line: 1, column: 6 },
+            end: LineColumn { line: 2, column: 3 },
+        };
+        let range = span.to_byte_range(SOURCE).unwrap();
+        assert_eq!(&SOURCE[range], "🦀\r\nzwei");
+
+        let span = Span {
+            start: LineColumn { line: 2, column: 5 },
+            end: LineColumn {
+                line: 2,
+                column: 42,
+            },
+        };
+        assert!(span.to_byte_range(SOURCE).is_err());
+    }
 }
@@ -13,25 +13,45 @@
 
 use crate::documentation::{CheckableChunk, ContentOrigin};
 
+use anyhow::{anyhow, Result};
+
 use std::cmp;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::convert::TryFrom;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use enumflags2::BitFlags;
 
 use crate::{Range, Span};
 
 /// Bitflag of available checkers by compilation / configuration.
-#[derive(Debug, Clone, Copy, BitFlags, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy, BitFlags, Eq, PartialEq, Hash, PartialOrd, Ord)]
 #[repr(u8)]
 pub enum Detector {
     Hunspell = 0b0001,
     LanguageTool = 0b0010,
+    Reflow = 0b0100,
+    External = 0b1000,
+    RepeatedWord = 0b1_0000,
     #[cfg(test)]
-    Dummy = 0b1000,
+    Dummy = 0b10_0000,
+}
+
+/// Width suggestions are laid out for, `0` to detect the terminal width.
+static TERMINAL_WIDTH: AtomicUsize = AtomicUsize::new(0);
+
+/// Lay out suggestions for a terminal of a fixed `width` instead of the
+/// detected one, for output which does not depend on the terminal.
+pub fn set_terminal_width(width: usize) {
+    TERMINAL_WIDTH.store(width, Ordering::Relaxed);
 }
 
 pub fn get_terminal_size() -> usize {
     const DEFAULT_TERMINAL_SIZE: usize = 80;
+    let width = TERMINAL_WIDTH.load(Ordering::Relaxed);
+    if width > 0 {
+        return width;
+    }
     #[cfg(not(test))]
     match crossterm::terminal::size() {
         Ok((terminal_size, _)) => terminal_size as usize,
@@ -58,19 +78,82 @@ pub fn get_terminal_size() -> usize {
 
 use std::fmt;
 
+impl Detector {
+    /// All detectors which can be selected by the user.
+    pub const ALL: &'static [Detector] = &[
+        Self::Hunspell,
+        Self::LanguageTool,
+        Self::Reflow,
+        Self::External,
+        Self::RepeatedWord,
+    ];
+
+    /// The stable name, as used for `--checkers`, in the configuration
+    /// file and all output formats.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::LanguageTool => "languagetool",
+            Self::Hunspell => "hunspell",
+            Self::Reflow => "reflow",
+            Self::External => "external",
+            Self::RepeatedWord => "repeated_words",
+            #[cfg(test)]
+            Self::Dummy => "dummy",
+        }
+    }
+}
+
 impl fmt::Display for Detector {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
-        formatter.write_str(match self {
-            Self::LanguageTool => "LanguageTool",
-            Self::Hunspell => "Hunspell",
-            #[cfg(test)]
-            Self::Dummy => "Dummy",
-        })
+        formatter.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Detector {
+    type Err = anyhow::Error;
+    /// Parse the stable name, ignoring ascii case.
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .iter()
+            .find(|detector| detector.as_str().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| {
+                anyhow!(
+                    "Unknown checker {}, expected one of {}",
+                    s,
+                    Self::ALL
+                        .iter()
+                        .map(|detector| detector.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })
+    }
+}
+
+impl serde::Serialize for Detector {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Detector {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
     }
 }
 
 // For long lines, literal will be trimmed to display in one terminal line.
 // Misspelled words that are too long shall also be ellipsized.
+//
+// The shown excerpt is a window around the mistake, which always contains
+// the (possibly ellipsized) mistake, no matter how narrow the terminal is.
 pub fn condition_display_content(
     terminal_size: usize,
     _indent: usize,
@@ -105,7 +188,6 @@ pub fn condition_display_content(
     const CENTER_DOTS: &'static str = "...";
     const LEFT_DOTS: &'static str = "..";
     const RIGHT_DOTS: &'static str = "..";
-    const NO_DOTS: &'static str = "";
 
     // guarantees that `marker_size` is always less than the max length.
     assert!(HEAD_DISPLAY_LEN + CENTER_DOTS.len() + TAIL_DISPLAY_LEN <= MAX_MISTAKE_LEN);
@@ -121,156 +203,103 @@ pub fn condition_display_content(
     //
     // Obs: paddings are not being considered in the illustration, but info is above.
 
+    let chars = stripped_line.chars().collect::<Vec<char>>();
+    let sub = |range: Range| chars[range].iter().collect::<String>();
+
     // Misspelled words that are too long will be shortened by ellipsizing parts of it.
     let (marker_size, shortened) = if mistake_range.len() > MAX_MISTAKE_LEN {
-        let head_sub_range = Range {
-            start: mistake_range.start,
-            end: mistake_range.start + HEAD_DISPLAY_LEN,
-        };
-        let tail_sub_range = Range {
-            start: mistake_range
-                .end //non inclusive
-                .saturating_sub(TAIL_DISPLAY_LEN),
-            end: mistake_range.end,
-        };
-
         //  too long word will be shorter as it follows:
         //            |-------------------| > MAX_MISTAKE_LEN
         //            therieeeeeeeeeeeeeeee
         //   4 chars  ^^^^   ...       ^^^^  4 chars
         //
         //  result:      ther...eeee
-
-        let head_sub = stripped_line
-            .chars()
-            .skip(head_sub_range.start)
-            .take(HEAD_DISPLAY_LEN)
-            .collect::<String>();
-        let tail_sub = stripped_line
-            .chars()
-            .skip(tail_sub_range.start)
-            .take(TAIL_DISPLAY_LEN)
-            .collect::<String>();
-
-        let shortened = format!("{}...{}", head_sub, tail_sub);
-        let marker_size = head_sub_range.len() + CENTER_DOTS.len() + tail_sub_range.len();
-
-        (marker_size, shortened)
+        let head_sub = sub(mistake_range.start..(mistake_range.start + HEAD_DISPLAY_LEN));
+        let tail_sub = sub((mistake_range.end - TAIL_DISPLAY_LEN)..mistake_range.end);
+
+        let shortened = format!("{}{}{}", head_sub, CENTER_DOTS, tail_sub);
+        (
+            HEAD_DISPLAY_LEN + CENTER_DOTS.len() + TAIL_DISPLAY_LEN,
+            shortened,
+        )
     } else {
-        let full: String = stripped_line
-            .chars()
-            .skip(mistake_range.start)
-            .take(mistake_range.len())
-            .collect();
-        (marker_size, full)
+        (marker_size, sub(mistake_range.clone()))
     };
 
-    let stripped_line_len = stripped_line.chars().count();
-
     // full, uncut context coverage
-    let left_context = Range {
-        start: 0,
-        end: mistake_range.start,
-    };
-    let right_context = Range {
-        start: mistake_range.end,
-        end: stripped_line_len,
-    };
+    let left_len = mistake_range.start;
+    let right_len = chars.len() - mistake_range.end;
 
     let avail_space = terminal_size
         .saturating_sub(terminal_print_offset_left + marker_size + TOTAL_CONTEXT_CHAR_COUNT);
 
-    // left and right we would like to partition the remaining space equally
-    let avail_space_half = avail_space / 2usize;
-
-    // @todo introduce a threshold, so the shortened version is not longer than than the original
-    let (left_context, right_context, left_dots, right_dots) = match (
-        avail_space_half > left_context.len(),
-        avail_space_half > right_context.len(),
-    ) {
-        (true, false) => {
-            // left context does not use all the capacity avail
-            // allow the right context to consume the excess.
-            let right_avail_space = avail_space - left_context.len();
-            let rdots = if mistake_range.end + right_avail_space < stripped_line_len {
-                NO_DOTS
-            } else {
-                RIGHT_DOTS
-            };
-            (
-                left_context,
-                Range {
-                    start: right_context.end,
-                    end: cmp::min(mistake_range.end + right_avail_space, stripped_line_len),
-                },
-                NO_DOTS,
-                rdots,
-            )
-        }
-        (false, true) => {
-            // right context does not use all the capacity avail
-            // allow the left context to consume the excess.
-            let left_avail_space = avail_space - right_context.len();
-            let ldots = if left_avail_space > left_context.end {
-                NO_DOTS
-            } else {
-                LEFT_DOTS
-            };
-            (
-                Range {
-                    start: left_context.end.saturating_sub(left_avail_space),
-                    end: left_context.end,
-                },
-                right_context,
-                ldots,
-                NO_DOTS,
-            )
-        }
-        (false, false) => {
-            // both sides have excess chars, so yield `avail_space_half` to both sides
-            (
-                Range {
-                    start: left_context.end.saturating_sub(avail_space_half),
-                    end: left_context.end,
-                },
-                Range {
-                    start: right_context.start,
-                    end: right_context.start + avail_space_half,
-                },
-                LEFT_DOTS,
-                RIGHT_DOTS,
-            )
-        }
-        _ => {
-            // both sides are less than the allowed context, no need to modify
-            (left_context, right_context, NO_DOTS, NO_DOTS)
-        }
+    // left and right we would like to partition the remaining space equally,
+    // a side which does not need all of its half yields the excess to the other
+    let left_avail_space = cmp::max(avail_space / 2, avail_space.saturating_sub(right_len));
+    let left_shown = cmp::min(left_len, left_avail_space);
+    let right_shown = cmp::min(right_len, avail_space - left_shown);
+
+    // cut context is replaced by dots, which are part of the shown window
+    let left = if left_shown < left_len {
+        let keep = left_shown.saturating_sub(LEFT_DOTS.len());
+        format!(
+            "{}{}",
+            LEFT_DOTS,
+            sub((mistake_range.start - keep)..mistake_range.start)
+        )
+    } else {
+        sub(0..mistake_range.start)
+    };
+    let right = if right_shown < right_len {
+        let keep = right_shown.saturating_sub(RIGHT_DOTS.len());
+        format!(
+            "{}{}",
+            sub(mistake_range.end..(mistake_range.end + keep)),
+            RIGHT_DOTS
+        )
+    } else {
+        sub(mistake_range.end..chars.len())
     };
 
-    assert!(left_context.end == mistake_range.start);
-    assert!(right_context.end <= stripped_line_len);
-    assert!(left_context.len() + mistake_range.len() + right_context.len() <= stripped_line_len);
-
-    let offset = left_context.len();
-    let conditioned_line = format!(
-        "{}{}{}{}{}",
-        left_dots,
-        stripped_line
-            .chars()
-            .skip(left_context.start + left_dots.len())
-            .take(left_context.len() - left_dots.len())
-            .collect::<String>(),
-        shortened,
-        stripped_line
-            .chars()
-            .skip(right_context.start)
-            .take(right_context.len() - right_dots.len())
-            .collect::<String>(),
-        right_dots,
-    );
+    let offset = left.chars().count();
+    let conditioned_line = format!("{}{}{}", left, shortened, right);
     (conditioned_line, offset, marker_size)
 }
 
+/// Confidence of a detector in a suggestion, between `0.0` and `1.0`.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Score(f32);
+
+impl Score {
+    /// Returns `None` unless `value` is within `0.0..=1.0`.
+    pub fn new(value: f32) -> Option<Self> {
+        if (0.0..=1.0).contains(&value) {
+            Some(Self(value))
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self) -> f32 {
+        self.0
+    }
+}
+
+// a score is never `NaN`, so equality is total
+impl Eq for Score {}
+
+impl std::hash::Hash for Score {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state)
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{:.2}", self.0)
+    }
+}
+
 /// A suggestion for certain offending span.
 #[derive(Clone, Hash, PartialEq, Eq)]
 pub struct Suggestion<'s> {
@@ -288,6 +317,10 @@ pub struct Suggestion<'s> {
     pub replacements: Vec<String>,
     /// Descriptive reason for the suggestion.
     pub description: Option<String>,
+    /// Confidence of the detector, if it provides one.
+    pub score: Option<Score>,
+    /// Identifier of the rule which fired, if the detector has rules.
+    pub rule_id: Option<String>,
 }
 
 impl<'s> fmt::Display for Suggestion<'s> {
@@ -305,9 +338,15 @@ impl<'s> fmt::Display for Suggestion<'s> {
         let indent = 3 + line_number_digit_count;
 
         error.apply_to("error").fmt(formatter)?;
-        highlight
-            .apply_to(format!(": spellcheck({})", &self.detector))
-            .fmt(formatter)?;
+        let mut header = format!(": spellcheck({}", &self.detector);
+        if let Some(score) = self.score {
+            header.push_str(&format!(", score {}", score));
+        }
+        if let Some(ref rule_id) = self.rule_id {
+            header.push_str(&format!(", rule {}", rule_id));
+        }
+        header.push(')');
+        highlight.apply_to(header).fmt(formatter)?;
         formatter.write_str("\n")?;
 
         arrow_marker
@@ -484,6 +523,234 @@ impl<'s> fmt::Display for Suggestion<'s> {
     }
 }
 
+impl<'s> Suggestion<'s> {
+    /// The offending text, i.e. the word unknown to the checker.
+    pub fn word(&self) -> String {
+        crate::util::sub_chars(self.chunk.as_str(), self.range.clone())
+    }
+
+    /// The first and last line of the span within the file of its origin,
+    /// doc tests are located relative to their doc comment.
+    pub fn lines(&self) -> (usize, usize) {
+        let offset = match self.origin {
+            ContentOrigin::RustDocTest(_, ref doctest) => doctest.start.line,
+            _ => 0,
+        };
+        (self.span.start.line + offset, self.span.end.line + offset)
+    }
+
+    /// The description followed by the offending text, as reported by
+    /// the machine readable output formats.
+    pub fn message(&self) -> String {
+        if let Some(ref description) = self.description {
+            format!("{} `{}`", description, self.word())
+        } else {
+            format!("`{}`", self.word())
+        }
+    }
+
+    /// Display the suggestion followed by `lines` lines of source before
+    /// and after the offending span, loaded from the file of its origin.
+    pub fn with_context<'a>(&'a self, lines: usize) -> Result<SuggestionContext<'a, 's>> {
+        let path = self.origin.as_path();
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read {} for context", path.display()).context(e))?;
+        Ok(SuggestionContext {
+            suggestion: self,
+            source,
+            lines,
+        })
+    }
+
+    /// Replace the region of `source` covered by the span with
+    /// `replacement`, where `source` is the content of the origin.
+    ///
+    /// Fails if the span is not contained in `source`.
+    pub fn apply_to(&self, source: &str, replacement: &str) -> Result<String> {
+        let range = self.span.to_byte_range(source)?;
+        let mut patched = String::with_capacity(source.len() - range.len() + replacement.len());
+        patched.push_str(&source[..range.start]);
+        patched.push_str(replacement);
+        patched.push_str(&source[range.end..]);
+        Ok(patched)
+    }
+
+    /// Upper bound of replacement candidates of a merged suggestion.
+    pub const MAX_MERGED_REPLACEMENTS: usize = 10;
+
+    /// Merge `other`, which must start at or after `self`, if both were
+    /// found by the same detector in the same chunk on the same line, and
+    /// are either overlapping or only separated by whitespace.
+    ///
+    /// Replacement candidates of adjacent suggestions are combined
+    /// pairwise, joined by the separating whitespace, ordered by the sum
+    /// of the ranks of both candidates. A side without any candidates
+    /// contributes its original text. For overlapping suggestions, every
+    /// candidate is extended by the text the merged range covers beyond
+    /// its own suggestion. At most [`Self::MAX_MERGED_REPLACEMENTS`]
+    /// candidates are kept.
+    pub fn merge(&self, other: &Self) -> Option<Self> {
+        if self.detector != other.detector
+            || self.origin != other.origin
+            || !std::ptr::eq(self.chunk, other.chunk)
+            || self.span.start.line != self.span.end.line
+            || other.span.start.line != other.span.end.line
+            || self.span.start.line != other.span.start.line
+            || other.range.start < self.range.start
+        {
+            return None;
+        }
+
+        let content = self.chunk.as_str();
+        let text = |range: Range| crate::util::sub_chars(content, range);
+        let range = self.range.start..cmp::max(self.range.end, other.range.end);
+
+        let replacements = if other.range.start >= self.range.end {
+            let separator = text(self.range.end..other.range.start);
+            if !separator.chars().all(|c| c.is_whitespace() && c != '\n') {
+                return None;
+            }
+            let or_original = |suggestion: &Self| {
+                if suggestion.replacements.is_empty() {
+                    vec![text(suggestion.range.clone())]
+                } else {
+                    suggestion.replacements.clone()
+                }
+            };
+            let first = or_original(self);
+            let second = or_original(other);
+            let mut pairs = first
+                .iter()
+                .enumerate()
+                .flat_map(|(i, a)| second.iter().enumerate().map(move |(j, b)| (i + j, a, b)))
+                .collect::<Vec<_>>();
+            // stable, so ties retain the order of the first candidates
+            pairs.sort_by_key(|(rank, _, _)| *rank);
+            pairs
+                .into_iter()
+                .map(|(_, a, b)| format!("{}{}{}", a, separator, b))
+                .collect::<Vec<_>>()
+        } else {
+            let prefix = text(range.start..other.range.start);
+            let suffix = text(self.range.end..range.end);
+            let extended_other = other
+                .replacements
+                .iter()
+                .map(|replacement| format!("{}{}", prefix, replacement))
+                .collect::<Vec<_>>();
+            let extended_self = self
+                .replacements
+                .iter()
+                .map(|replacement| format!("{}{}", replacement, suffix))
+                .collect::<Vec<_>>();
+            // alternate, so both contribute their best candidates first
+            let mut replacements = Vec::with_capacity(extended_self.len() + extended_other.len());
+            let mut a = extended_self.into_iter();
+            let mut b = extended_other.into_iter();
+            loop {
+                match (a.next(), b.next()) {
+                    (None, None) => break,
+                    (x, y) => replacements.extend(x.into_iter().chain(y)),
+                }
+            }
+            replacements
+        };
+        let mut unique = Vec::with_capacity(Self::MAX_MERGED_REPLACEMENTS);
+        for replacement in replacements {
+            if unique.len() >= Self::MAX_MERGED_REPLACEMENTS {
+                break;
+            }
+            if !unique.contains(&replacement) {
+                unique.push(replacement);
+            }
+        }
+
+        let description = match (&self.description, &other.description) {
+            (Some(a), Some(b)) if a != b => Some(format!("{} {}", a, b)),
+            (Some(a), _) => Some(a.clone()),
+            (None, b) => b.clone(),
+        };
+
+        Some(Self {
+            detector: self.detector,
+            origin: self.origin.clone(),
+            chunk: self.chunk,
+            span: Span {
+                start: self.span.start,
+                end: cmp::max(self.span.end, other.span.end),
+            },
+            range,
+            replacements: unique,
+            description,
+            score: match (self.score, other.score) {
+                (Some(a), Some(b)) => Some(if a < b { a } else { b }),
+                (a, b) => a.or(b),
+            },
+            rule_id: self.rule_id.clone().or_else(|| other.rule_id.clone()),
+        })
+    }
+}
+
+/// A suggestion together with the source it originates from.
+pub struct SuggestionContext<'a, 's> {
+    suggestion: &'a Suggestion<'s>,
+    source: String,
+    lines: usize,
+}
+
+impl<'a, 's> fmt::Display for SuggestionContext<'a, 's> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use console::Style;
+
+        self.suggestion.fmt(formatter)?;
+        if self.lines == 0 {
+            return Ok(());
+        }
+
+        let context_marker = Style::new().bold().blue();
+        let help = Style::new().yellow().bold();
+
+        let span = self.suggestion.span;
+        let (mistake_line, end_line) = self.suggestion.lines();
+        let first = cmp::max(1, mistake_line.saturating_sub(self.lines));
+        let last = end_line + self.lines;
+
+        let line_number_digit_count = cmp::max(mistake_line, last).to_string().len();
+        let indent = 3 + line_number_digit_count;
+
+        for (lineno, line) in self
+            .source
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| (idx + 1, line))
+            .skip(first - 1)
+            .take(last + 1 - first)
+        {
+            context_marker
+                .apply_to(format!("{:>width$} |", lineno, width = indent - 2))
+                .fmt(formatter)?;
+            writeln!(formatter, " {}", line)?;
+
+            if lineno == mistake_line {
+                let marker_size = span
+                    .one_line_len()
+                    .unwrap_or_else(|| line.chars().count().saturating_sub(span.start.column));
+                context_marker
+                    .apply_to(format!("{:>width$}", "|", width = indent))
+                    .fmt(formatter)?;
+                help.apply_to(format!(" {:>offset$}", "", offset = span.start.column))
+                    .fmt(formatter)?;
+                help.apply_to(format!("{:^>size$}", "", size = marker_size))
+                    .fmt(formatter)?;
+                formatter.write_str("\n")?;
+            }
+        }
+        context_marker
+            .apply_to(format!("{:>width$}", "|\n", width = indent + 1))
+            .fmt(formatter)
+    }
+}
+
 impl<'s> fmt::Debug for Suggestion<'s> {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
         match crate::documentation::ChunkDisplay::try_from((self.chunk, self.span)) {
@@ -606,6 +873,166 @@ impl<'s> SuggestionSet<'s> {
     pub fn total_count(&self) -> usize {
         self.per_file.iter().map(|(_origin, vec)| vec.len()).sum()
     }
+
+    /// Count the number of suggestions accross all files in total
+    ///
+    /// Identical to `total_count`.
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.total_count()
+    }
+
+    /// Iterate over all suggestions for the given `origin`
+    ///
+    /// Yields nothing if there is no such origin.
+    pub fn iter_for_origin<'a>(
+        &'a self,
+        origin: &ContentOrigin,
+    ) -> impl DoubleEndedIterator<Item = &'a Suggestion<'s>> {
+        self.per_file
+            .get(origin)
+            .map(|suggestions| suggestions.as_slice())
+            .unwrap_or(&[])
+            .iter()
+    }
+
+    /// Count the number of suggestions per detector accross all files
+    pub fn count_by_detector(&self) -> HashMap<Detector, usize> {
+        self.per_file
+            .values()
+            .flatten()
+            .fold(HashMap::with_capacity(4), |mut acc, suggestion| {
+                *acc.entry(suggestion.detector).or_insert(0) += 1;
+                acc
+            })
+    }
+
+    /// Only keep the suggestions for which `predicate` returns `true`
+    ///
+    /// Origins without any remaining suggestions are removed.
+    pub fn retain<F>(&mut self, mut predicate: F)
+    where
+        F: FnMut(&ContentOrigin, &Suggestion<'s>) -> bool,
+    {
+        self.per_file.retain(|origin, suggestions| {
+            suggestions.retain(|suggestion| predicate(origin, suggestion));
+            !suggestions.is_empty()
+        });
+    }
+
+    /// The distinct text covered by any of the suggestions, i.e. the
+    /// words unknown to the checkers.
+    pub fn unknown_words(&self) -> BTreeSet<String> {
+        self.per_file
+            .values()
+            .flatten()
+            .map(Suggestion::word)
+            .collect()
+    }
+
+    /// How often each rule fired, most frequent first, rules of equal
+    /// frequency in alphabetical order.
+    ///
+    /// Suggestions of detectors without rules are not counted.
+    pub fn rule_counts(&self) -> Vec<(usize, String)> {
+        let mut counts = HashMap::<String, usize>::new();
+        for rule_id in self
+            .per_file
+            .values()
+            .flatten()
+            .filter_map(|suggestion| suggestion.rule_id.as_ref())
+        {
+            *counts.entry(rule_id.clone()).or_insert(0) += 1;
+        }
+        let mut counts = counts
+            .into_iter()
+            .map(|(rule_id, count)| (count, rule_id))
+            .collect::<Vec<_>>();
+        counts.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        counts
+    }
+
+    /// Drop the suggestions of the given comma separated rule ids.
+    ///
+    /// Suggestions of detectors without rules are kept.
+    pub fn disable_rules(&mut self, rule_ids: &str) {
+        let disabled = rule_ids
+            .split(',')
+            .map(str::trim)
+            .filter(|rule_id| !rule_id.is_empty())
+            .collect::<HashSet<_>>();
+        self.retain(|_origin, suggestion| {
+            suggestion
+                .rule_id
+                .as_deref()
+                .map(|rule_id| !disabled.contains(rule_id))
+                .unwrap_or(true)
+        });
+    }
+
+    /// How often each unknown word occurs, most frequent first, words
+    /// of equal frequency in alphabetical order.
+    ///
+    /// Reflowed paragraphs and repeated words are not unknown words.
+    pub fn unknown_word_counts(&self) -> Vec<(usize, String)> {
+        let mut counts = HashMap::<String, usize>::new();
+        for suggestion in self.per_file.values().flatten() {
+            match suggestion.detector {
+                Detector::Reflow | Detector::RepeatedWord => continue,
+                _ => {}
+            }
+            *counts.entry(suggestion.word()).or_insert(0) += 1;
+        }
+        let mut counts = counts
+            .into_iter()
+            .map(|(word, count)| (count, word))
+            .collect::<Vec<_>>();
+        counts.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        counts
+    }
+
+    /// Merge suggestions of adjacent or overlapping spans on the same
+    /// line into one, see [`Suggestion::merge`].
+    ///
+    /// Expects the suggestions to be sorted.
+    pub fn merge_adjacent(&mut self) {
+        for suggestions in self.per_file.values_mut() {
+            let mut merged: Vec<Suggestion<'s>> = Vec::with_capacity(suggestions.len());
+            for suggestion in suggestions.drain(..) {
+                // only the same detector merges, so look past the ones of others on the same line
+                let candidate = merged.iter_mut().rev().find(|previous| {
+                    previous.detector == suggestion.detector
+                        && previous.span.start.line == suggestion.span.start.line
+                });
+                if let Some(previous) = candidate {
+                    if let Some(combined) = previous.merge(&suggestion) {
+                        *previous = combined;
+                        continue;
+                    }
+                }
+                merged.push(suggestion);
+            }
+            *suggestions = merged;
+        }
+    }
+
+    /// Sort all suggestions by origin path, line, column and detector.
+    ///
+    /// Assures the output order does not depend on the order of checker invocations
+    /// or file traversal.
+    pub fn sort(&mut self) {
+        self.per_file
+            .sort_by(|origin_a, _, origin_b, _| origin_a.as_path().cmp(origin_b.as_path()));
+        for suggestions in self.per_file.values_mut() {
+            suggestions.sort_by_key(|suggestion| {
+                (
+                    suggestion.span.start.line,
+                    suggestion.span.start.column,
+                    suggestion.detector,
+                )
+            });
+        }
+    }
 }
 
 impl<'s> IntoIterator for SuggestionSet<'s> {
@@ -624,12 +1051,56 @@ impl<'s> IntoIterator for &'s SuggestionSet<'s> {
     }
 }
 
+/// A chunk of `content` on a single line, starting at `line` and `column`.
+#[cfg(test)]
+pub(crate) fn single_line_chunk(content: &str, line: usize, column: usize) -> CheckableChunk {
+    let len = content.chars().count();
+    CheckableChunk::from_str(
+        content,
+        indexmap::indexmap! { 0..len => Span {
+                start: crate::LineColumn { line, column },
+                end: crate::LineColumn {
+                    line,
+                    column: column + len - 1,
+                },
+            }
+        },
+    )
+}
+
+/// A suggestion of the dummy detector without replacements, covering
+/// `range` of a single line `chunk`.
+#[cfg(test)]
+pub(crate) fn dummy_suggestion<'s>(
+    origin: ContentOrigin,
+    chunk: &'s CheckableChunk,
+    range: Range,
+) -> Suggestion<'s> {
+    let span = *chunk
+        .find_spans(range.clone())
+        .values()
+        .next()
+        .expect("The range is within the chunk. qed");
+    Suggestion {
+        detector: Detector::Dummy,
+        origin,
+        chunk,
+        span,
+        range,
+        replacements: vec![],
+        description: None,
+        score: None,
+        rule_id: None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::LineColumn;
     use console;
     use std::fmt;
+    use std::path::PathBuf;
 
     /// A test helper comparing the output against an expected output.
     ///
@@ -680,9 +1151,11 @@ mod tests {
                 .map(std::borrow::ToOwned::to_owned)
                 .collect(),
             description: Some("Possible spelling mistake found.".to_owned()),
+            score: None,
+            rule_id: None,
         };
 
-        const EXPECTED: &'static str = r#"error: spellcheck(Dummy)
+        const EXPECTED: &'static str = r#"error: spellcheck(dummy)
   --> /tmp/test/entity:1
    |
  1 |  Is it dyrck again?
@@ -695,6 +1168,69 @@ mod tests {
         assert_display_eq(suggestion, EXPECTED);
     }
 
+    #[test]
+    fn word_lines_message() {
+        let chunk = single_line_chunk(" Is it dyrck again?", 2, 0);
+        let doc_comment = Span {
+            start: LineColumn { line: 7, column: 4 },
+            end: LineColumn {
+                line: 12,
+                column: 7,
+            },
+        };
+        let mut suggestion = dummy_suggestion(ContentOrigin::TestEntity, &chunk, 7..12);
+        assert_eq!(suggestion.word(), "dyrck");
+        assert_eq!(suggestion.lines(), (2, 2));
+        assert_eq!(suggestion.message(), "`dyrck`");
+
+        suggestion.origin = ContentOrigin::RustDocTest(PathBuf::from("/tmp/lib.rs"), doc_comment);
+        suggestion.description = Some("Possible spelling mistake found.".to_owned());
+        assert_eq!(suggestion.lines(), (9, 9));
+        assert_eq!(
+            suggestion.message(),
+            "Possible spelling mistake found. `dyrck`"
+        );
+    }
+
+    #[test]
+    fn fmt_with_context() {
+        const SOURCE: &'static str = "First line
+Second line
+ Is it dyrck again?
+Fourth line
+Fifth line
+";
+        let chunk = single_line_chunk(" Is it dyrck again?", 3, 0);
+        let suggestion = Suggestion {
+            replacements: vec!["dirk".to_owned()],
+            description: Some("Possible spelling mistake found.".to_owned()),
+            ..dummy_suggestion(ContentOrigin::TestEntity, &chunk, 7..12)
+        };
+
+        let context = SuggestionContext {
+            suggestion: &suggestion,
+            source: SOURCE.to_owned(),
+            lines: 1,
+        };
+
+        const EXPECTED: &'static str = r#"error: spellcheck(dummy)
+  --> /tmp/test/entity:3
+   |
+ 3 |  Is it dyrck again?
+   |        ^^^^^
+   | - dirk
+   |
+   |   Possible spelling mistake found.
+   |
+ 2 | Second line
+ 3 |  Is it dyrck again?
+   |        ^^^^^
+ 4 | Fourth line
+   |
+"#;
+        assert_display_eq(context, EXPECTED);
+    }
+
     #[test]
     fn fmt_1_multi() {
         const CONTENT: &'static str = r#" Line mitake 1
@@ -757,9 +1293,11 @@ mod tests {
                 .map(std::borrow::ToOwned::to_owned)
                 .collect(),
             description: Some("Possible spelling mistake found.".to_owned()),
+            score: None,
+            rule_id: None,
         };
 
-        const EXPECTED: &'static str = r#"error: spellcheck(Dummy)
+        const EXPECTED: &'static str = r#"error: spellcheck(dummy)
   --> /tmp/test/entity:1
    |
  1 |  Line mitake 1
@@ -822,9 +1360,11 @@ mod tests {
                 .map(std::borrow::ToOwned::to_owned)
                 .collect(),
             description: Some("Possible spelling mistake found.".to_owned()),
+            score: None,
+            rule_id: None,
         };
 
-        const EXPECTED: &'static str = r#"error: spellcheck(Dummy)
+        const EXPECTED: &'static str = r#"error: spellcheck(dummy)
   --> /tmp/test/entity:2
    |
  2 | ..uuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuper duuu...uper too long
@@ -838,6 +1378,261 @@ mod tests {
         assert_display_eq(suggestion, EXPECTED);
     }
 
+    #[test]
+    fn sort_by_origin_line_column_detector() {
+        let chunk = single_line_chunk("abc def", 1, 0);
+        let suggestion =
+            |origin: &ContentOrigin, line: usize, column: usize, detector: Detector| Suggestion {
+                detector,
+                span: Span {
+                    start: LineColumn { line, column },
+                    end: LineColumn { line, column },
+                },
+                ..dummy_suggestion(origin.clone(), &chunk, 0..1)
+            };
+
+        let a = ContentOrigin::RustSourceFile(PathBuf::from("/tmp/a.rs"));
+        let b = ContentOrigin::CommonMarkFile(PathBuf::from("/tmp/b.md"));
+
+        let mut set = SuggestionSet::new();
+        set.add(b.clone(), suggestion(&b, 3, 0, Detector::Hunspell));
+        set.add(b.clone(), suggestion(&b, 1, 5, Detector::Hunspell));
+        set.add(a.clone(), suggestion(&a, 2, 7, Detector::LanguageTool));
+        set.add(a.clone(), suggestion(&a, 2, 7, Detector::Hunspell));
+        set.add(a.clone(), suggestion(&a, 2, 1, Detector::Dummy));
+        set.add(a.clone(), suggestion(&a, 1, 9, Detector::LanguageTool));
+
+        set.sort();
+
+        let order = set
+            .iter()
+            .flat_map(|(origin, suggestions)| {
+                suggestions.iter().map(move |suggestion| {
+                    (
+                        origin.clone(),
+                        suggestion.span.start.line,
+                        suggestion.span.start.column,
+                        suggestion.detector,
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            order,
+            vec![
+                (a.clone(), 1, 9, Detector::LanguageTool),
+                (a.clone(), 2, 1, Detector::Dummy),
+                (a.clone(), 2, 7, Detector::Hunspell),
+                (a.clone(), 2, 7, Detector::LanguageTool),
+                (b.clone(), 1, 5, Detector::Hunspell),
+                (b.clone(), 3, 0, Detector::Hunspell),
+            ]
+        );
+    }
+
+    #[test]
+    fn query_and_filter() {
+        let chunk = single_line_chunk("abc def", 1, 0);
+        let suggestion = |origin: &ContentOrigin, column: usize, detector: Detector| Suggestion {
+            detector,
+            ..dummy_suggestion(origin.clone(), &chunk, column..(column + 1))
+        };
+
+        let a = ContentOrigin::RustSourceFile(PathBuf::from("/tmp/a.rs"));
+        let b = ContentOrigin::CommonMarkFile(PathBuf::from("/tmp/b.md"));
+        let c = ContentOrigin::CommonMarkFile(PathBuf::from("/tmp/c.md"));
+
+        let mut set = SuggestionSet::new();
+        set.add(a.clone(), suggestion(&a, 0, Detector::Hunspell));
+        set.add(a.clone(), suggestion(&a, 4, Detector::LanguageTool));
+        set.add(b.clone(), suggestion(&b, 0, Detector::Hunspell));
+
+        assert_eq!(set.count(), 3);
+        assert_eq!(set.iter_for_origin(&a).count(), 2);
+        assert_eq!(set.iter_for_origin(&b).count(), 1);
+        assert_eq!(set.iter_for_origin(&c).count(), 0);
+
+        let by_detector = set.count_by_detector();
+        assert_eq!(by_detector.get(&Detector::Hunspell), Some(&2));
+        assert_eq!(by_detector.get(&Detector::LanguageTool), Some(&1));
+        assert_eq!(by_detector.get(&Detector::Dummy), None);
+
+        set.retain(|_origin, suggestion| suggestion.detector == Detector::LanguageTool);
+        assert_eq!(set.count(), 1);
+        assert_eq!(set.len(), 1);
+        assert_eq!(set.iter_for_origin(&b).count(), 0);
+        assert_eq!(
+            set.iter_for_origin(&a)
+                .map(|suggestion| suggestion.span.start.column)
+                .collect::<Vec<_>>(),
+            vec![4]
+        );
+    }
+
+    #[test]
+    fn apply_to_multibyte() {
+        const SOURCE: &str = "Grüße\nSöme wörd ünd möre\n";
+        let chunk = single_line_chunk("wörd", 2, 5);
+        let suggestion = dummy_suggestion(ContentOrigin::TestEntity, &chunk, 0..4);
+        assert_eq!(
+            suggestion.apply_to(SOURCE, "wört").unwrap(),
+            "Grüße\nSöme wört ünd möre\n"
+        );
+        assert_eq!(
+            suggestion.apply_to(SOURCE, "").unwrap(),
+            "Grüße\nSöme  ünd möre\n"
+        );
+        assert!(suggestion.apply_to("Grüße", "wört").is_err());
+    }
+
+    #[test]
+    fn score() {
+        assert_eq!(Score::new(0.5).map(|score| score.get()), Some(0.5));
+        assert!(Score::new(1.5).is_none());
+        assert!(Score::new(-0.1).is_none());
+        assert!(Score::new(f32::NAN).is_none());
+        assert!(Score::new(0.25).unwrap() < Score::new(0.5).unwrap());
+        assert_eq!(Score::new(0.125).unwrap().to_string(), "0.12");
+    }
+
+    #[test]
+    fn detector_names() {
+        for detector in Detector::ALL {
+            assert_eq!(detector.to_string().parse::<Detector>().unwrap(), *detector);
+            let json = serde_json::to_string(detector).unwrap();
+            assert_eq!(json, format!("\"{}\"", detector));
+            assert_eq!(serde_json::from_str::<Detector>(&json).unwrap(), *detector);
+        }
+        assert_eq!(
+            "LanguageTool".parse::<Detector>().unwrap(),
+            Detector::LanguageTool
+        );
+        assert_eq!(Detector::RepeatedWord.to_string(), "repeated_words");
+        assert!("spelling".parse::<Detector>().is_err());
+        assert!(serde_json::from_str::<Detector>("\"spelling\"").is_err());
+    }
+
+    #[test]
+    fn unknown_words() {
+        let chunk = single_line_chunk("Teh quikc Teh", 1, 0);
+        let origin = ContentOrigin::TestEntity;
+        let mut set = SuggestionSet::new();
+        for range in vec![0..3, 4..9, 10..13] {
+            set.add(
+                origin.clone(),
+                dummy_suggestion(origin.clone(), &chunk, range),
+            );
+        }
+        assert_eq!(
+            set.unknown_words().into_iter().collect::<Vec<_>>(),
+            vec!["Teh".to_owned(), "quikc".to_owned()]
+        );
+        assert_eq!(
+            set.unknown_word_counts(),
+            vec![(2, "Teh".to_owned()), (1, "quikc".to_owned())]
+        );
+    }
+
+    #[test]
+    fn rules() {
+        let chunk = single_line_chunk("a apple a apple", 1, 0);
+        let origin = ContentOrigin::TestEntity;
+        let mut set = SuggestionSet::new();
+        for (range, rule_id) in vec![
+            (0..1, Some("EN_A_VS_AN")),
+            (2..7, None),
+            (8..9, Some("EN_A_VS_AN")),
+            (0..15, Some("REPEATED")),
+        ] {
+            set.add(
+                origin.clone(),
+                Suggestion {
+                    rule_id: rule_id.map(ToOwned::to_owned),
+                    ..dummy_suggestion(origin.clone(), &chunk, range)
+                },
+            );
+        }
+        assert_eq!(
+            set.rule_counts(),
+            vec![(2, "EN_A_VS_AN".to_owned()), (1, "REPEATED".to_owned())]
+        );
+
+        let suggestions = set.iter().next().unwrap().1;
+        let display = |suggestion: &Suggestion| {
+            console::strip_ansi_codes(suggestion.to_string().as_str()).into_owned()
+        };
+        assert!(display(&suggestions[0]).starts_with("error: spellcheck(dummy, rule EN_A_VS_AN)\n"));
+        assert!(display(&suggestions[1]).starts_with("error: spellcheck(dummy)\n"));
+
+        set.disable_rules("EN_A_VS_AN, ,UNKNOWN_RULE");
+        assert_eq!(set.rule_counts(), vec![(1, "REPEATED".to_owned())]);
+        assert_eq!(set.total_count(), 2);
+        set.disable_rules("REPEATED");
+        assert_eq!(set.rule_counts(), vec![]);
+        assert_eq!(set.total_count(), 1);
+    }
+
+    #[test]
+    fn merge_adjacent() {
+        let chunk = single_line_chunk("Teh quikc fox jmups", 1, 0);
+        let origin = ContentOrigin::TestEntity;
+        let suggestion = |range: Range, replacements: &[&str]| Suggestion {
+            replacements: replacements.iter().map(|r| (*r).to_owned()).collect(),
+            ..dummy_suggestion(origin.clone(), &chunk, range)
+        };
+
+        let mut set = SuggestionSet::new();
+        set.add(origin.clone(), suggestion(0..3, &["The", "Ten"]));
+        set.add(origin.clone(), suggestion(4..9, &["quick"]));
+        set.add(origin.clone(), suggestion(14..19, &[]));
+        set.merge_adjacent();
+
+        let merged = set.iter_for_origin(&origin).collect::<Vec<_>>();
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].range, 0..9);
+        assert_eq!(merged[0].span.end.column, 8);
+        assert_eq!(merged[0].replacements, vec!["The quick", "Ten quick"]);
+        // separated by a correct word, so not merged
+        assert_eq!(merged[1].range, 14..19);
+
+        // overlapping
+        let a = suggestion(4..9, &["quick"]);
+        let b = suggestion(6..13, &["ick fox"]);
+        let merged = a.merge(&b).expect("Overlapping suggestions merge");
+        assert_eq!(merged.range, 4..13);
+        assert_eq!(merged.replacements, vec!["quick fox"]);
+    }
+
+    #[test]
+    fn narrow_terminal() {
+        const LINE: &str =
+            "Pre Mistkae and a lot of context following the mistake, which does not fit";
+
+        // context is cut on both sides, the mistake stays visible
+        let (formatted, offset, marker_size) = condition_display_content(40, 4, LINE, 4..11, 7, 7);
+        assert_eq!(formatted, "Pre Mistkae and a lot of ..");
+        assert_eq!((offset, marker_size), (4, 7));
+
+        for terminal_size in 0..(LINE.len() + 10) {
+            for mistake_range in vec![0..3, 4..11, 60..63, 71..74] {
+                let (formatted, offset, marker_size) = condition_display_content(
+                    terminal_size,
+                    4,
+                    LINE,
+                    mistake_range.clone(),
+                    7,
+                    mistake_range.len(),
+                );
+                assert_eq!(
+                    crate::util::sub_chars(formatted.as_str(), offset..(offset + marker_size)),
+                    crate::util::sub_chars(LINE, mistake_range)
+                );
+                assert!(formatted.len() + 7 <= cmp::max(terminal_size, 7 + marker_size + 4));
+            }
+        }
+    }
+
     #[test]
     fn multiline_is_dbg_printable() {
         let _ = env_logger::builder().is_test(true).try_init();
@@ -876,6 +1671,8 @@ mod tests {
             range: 2..6,
             replacements: vec!["whocares".to_owned()],
             description: None,
+            score: None,
+            rule_id: None,
         };
 
         let suggestion = dbg!(suggestion);
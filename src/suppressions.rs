@@ -0,0 +1,180 @@
+//! Suppress known false positives by their location, listed in the file
+//! given with `--suppressions`.
+//!
+//! Each line holds one entry, `path:line:column word` to match exactly, or
+//! `path:line word` to match the word anywhere on that line. Lines and
+//! columns start at 1, just as they are printed. A relative path matches
+//! any checked file ending with it. Blank lines and lines starting with
+//! `#` are ignored.
+
+use crate::{ContentOrigin, Suggestion};
+use anyhow::{anyhow, Result};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// A single suppressed mistake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suppression {
+    path: PathBuf,
+    line: usize,
+    /// Without a column, the word is suppressed anywhere on the line.
+    column: Option<usize>,
+    word: String,
+}
+
+impl Suppression {
+    /// Parse an entry, `path:line:column word` or `path:line word`.
+    fn parse(entry: &str) -> Option<Self> {
+        let (location, word) = entry.trim().rsplit_once(char::is_whitespace)?;
+        let (rest, last) = location.trim_end().rsplit_once(':')?;
+        let last = last.parse::<usize>().ok()?;
+        let (path, line, column) = match rest.rsplit_once(':') {
+            Some((path, line)) if line.parse::<usize>().is_ok() => {
+                (path, line.parse::<usize>().ok()?, Some(last))
+            }
+            _ => (rest, last, None),
+        };
+        if path.is_empty() || line == 0 || column == Some(0) {
+            return None;
+        }
+        Some(Self {
+            path: PathBuf::from(path),
+            line,
+            column,
+            word: word.to_owned(),
+        })
+    }
+
+    fn matches_path(&self, path: &Path) -> bool {
+        if self.path.is_absolute() {
+            self.path == path
+        } else {
+            path.ends_with(&self.path)
+        }
+    }
+
+    fn matches(&self, origin: &ContentOrigin, suggestion: &Suggestion) -> bool {
+        self.matches_path(origin.as_path())
+            && self.line == suggestion.lines().0
+            && self
+                .column
+                .map(|column| column == suggestion.span.start.column + 1)
+                .unwrap_or(true)
+            && self.word == suggestion.word()
+    }
+}
+
+impl fmt::Display for Suppression {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{}:{}", self.path.display(), self.line)?;
+        if let Some(column) = self.column {
+            write!(formatter, ":{}", column)?;
+        }
+        write!(formatter, " {}", self.word)
+    }
+}
+
+/// All entries of a suppressions file, tracking which of them matched.
+#[derive(Debug, Default)]
+pub struct Suppressions {
+    entries: Vec<(Suppression, bool)>,
+}
+
+impl Suppressions {
+    /// Load the suppressions file at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = crate::util::read_file(path)?;
+        Self::parse(&content)
+            .map_err(|e| e.context(anyhow!("Invalid suppressions file {}", path.display())))
+    }
+
+    fn parse(content: &str) -> Result<Self> {
+        let mut entries = Vec::new();
+        for (idx, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let suppression = Suppression::parse(line).ok_or_else(|| {
+                anyhow!(
+                    "Line {} is not of the form `path:line:column word` or `path:line word`: {}",
+                    idx + 1,
+                    line
+                )
+            })?;
+            entries.push((suppression, false));
+        }
+        Ok(Self { entries })
+    }
+
+    /// Check if the `suggestion` of `origin` is suppressed, and remember
+    /// the matching entries as used.
+    pub fn suppresses(&mut self, origin: &ContentOrigin, suggestion: &Suggestion) -> bool {
+        let mut suppressed = false;
+        for (suppression, used) in self.entries.iter_mut() {
+            if suppression.matches(origin, suggestion) {
+                *used = true;
+                suppressed = true;
+            }
+        }
+        suppressed
+    }
+
+    /// Entries of files which were `checked`, but which did not match any
+    /// mistake, since the file or the mistake changed.
+    pub fn stale<'a>(&'a self, checked: &'a [&Path]) -> impl Iterator<Item = &'a Suppression> + 'a {
+        self.entries
+            .iter()
+            .filter(|(_suppression, used)| !used)
+            .map(|(suppression, _used)| suppression)
+            .filter(move |suppression| checked.iter().any(|path| suppression.matches_path(path)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::suggestion::{dummy_suggestion, single_line_chunk};
+
+    #[test]
+    fn entries() {
+        let mut suppressions = Suppressions::parse(
+            "# known false positives\n\nsrc/lib.rs:3:7 wrold\nsrc/lib.rs:5 tokio\n/abs/README.md:1:1 Fooo\n",
+        )
+        .unwrap();
+        assert_eq!(suppressions.entries.len(), 3);
+        assert_eq!(suppressions.entries[1].0.to_string(), "src/lib.rs:5 tokio");
+        assert!(Suppressions::parse("src/lib.rs wrold").is_err());
+        assert!(Suppressions::parse("src/lib.rs:0:1 wrold").is_err());
+
+        // the same content on lines 3 to 5
+        let chunks = (3..=5)
+            .map(|line| single_line_chunk("A wrold of tokio", line, 4))
+            .collect::<Vec<_>>();
+        let origin = ContentOrigin::RustSourceFile(PathBuf::from("/project/src/lib.rs"));
+        let suggestion = |range: crate::Range, line: usize| {
+            dummy_suggestion(origin.clone(), &chunks[line - 3], range)
+        };
+        // column 7 is the 1-based column of `wrold`
+        assert!(suppressions.suppresses(&origin, &suggestion(2..7, 3)));
+        assert!(!suppressions.suppresses(&origin, &suggestion(2..7, 4)));
+        // any column of line 5
+        assert!(suppressions.suppresses(&origin, &suggestion(11..16, 5)));
+        assert!(!suppressions.suppresses(&origin, &suggestion(2..7, 5)));
+        let other = ContentOrigin::RustSourceFile(PathBuf::from("/project/src/main.rs"));
+        assert!(!suppressions.suppresses(&other, &suggestion(2..7, 3)));
+
+        let checked = [
+            Path::new("/project/src/lib.rs"),
+            Path::new("/abs/README.md"),
+        ];
+        assert_eq!(
+            suppressions
+                .stale(&checked)
+                .map(|suppression| suppression.to_string())
+                .collect::<Vec<_>>(),
+            vec!["/abs/README.md:1:1 Fooo"]
+        );
+        assert_eq!(suppressions.stale(&checked[..1]).count(), 0);
+    }
+}
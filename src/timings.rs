@@ -0,0 +1,76 @@
+//! Wall clock durations of the phases of a run, reported with `--timings`.
+
+use std::time::{Duration, Instant};
+
+/// Durations of the phases of a single run, in the order they were measured.
+#[derive(Debug, Default)]
+pub struct Timings {
+    phases: Vec<(String, Duration)>,
+}
+
+impl Timings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the `duration` of `phase`.
+    pub fn record(&mut self, phase: impl Into<String>, duration: Duration) {
+        self.phases.push((phase.into(), duration));
+    }
+
+    /// Run `f` and record how long it took as `phase`.
+    pub fn measure<T>(&mut self, phase: impl Into<String>, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.record(phase, start.elapsed());
+        result
+    }
+
+    /// Render a table of all phases, one per line, followed by the total.
+    pub fn table(&self) -> String {
+        let total = self.phases.iter().map(|(_, duration)| *duration).sum();
+        let width = self
+            .phases
+            .iter()
+            .map(|(phase, _)| phase.chars().count())
+            .chain(std::iter::once("total".len()))
+            .max()
+            .unwrap_or(0);
+        let mut table = String::from("Timings:\n");
+        for (phase, duration) in self
+            .phases
+            .iter()
+            .map(|(phase, duration)| (phase.as_str(), *duration))
+            .chain(std::iter::once(("total", total)))
+        {
+            table.push_str(&format!(
+                "  {:<width$}  {:>10.3}ms\n",
+                phase,
+                duration.as_secs_f64() * 1000.,
+                width = width
+            ));
+        }
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table() {
+        let mut timings = Timings::new();
+        timings.record("traversal", Duration::from_millis(12));
+        assert_eq!(timings.measure("checker hunspell", || 7), 7);
+        timings.record("action", Duration::from_micros(1500));
+        let table = timings.table();
+        let lines = table.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], "Timings:");
+        assert_eq!(lines[1], "  traversal             12.000ms");
+        assert!(lines[2].starts_with("  checker hunspell  "));
+        assert_eq!(lines[3], "  action                 1.500ms");
+        assert!(lines[4].starts_with("  total             "));
+    }
+}
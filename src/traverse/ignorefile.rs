@@ -0,0 +1,194 @@
+//! Exclude files from the traversal with a `.spellcheckignore` file
+//!
+//! The file uses gitignore syntax, patterns are relative to the directory
+//! containing it. `.gitignore` files are not consulted at all, so files
+//! ignored by git but reachable via a manifest or module are checked,
+//! unless listed in `.spellcheckignore` as well.
+
+use anyhow::{anyhow, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use log::debug;
+use std::path::{Path, PathBuf};
+
+/// Name of the ignore file, which is looked up in the manifest directory
+/// of each checked path and all of its ancestors.
+pub(crate) const IGNORE_FILE_NAME: &str = ".spellcheckignore";
+
+/// The patterns of a `.spellcheckignore` file.
+#[derive(Debug, Clone)]
+pub(crate) struct IgnoreFile {
+    gitignore: Gitignore,
+}
+
+impl IgnoreFile {
+    /// Load the patterns of the ignore file at `path`.
+    pub(crate) fn load(path: &Path) -> Result<Self> {
+        let path = path
+            .canonicalize()
+            .map_err(|e| anyhow!("Failed to resolve {}", path.display()).context(e))?;
+        let root = path.parent().unwrap_or_else(|| Path::new("/"));
+        let mut builder = GitignoreBuilder::new(root);
+        if let Some(e) = builder.add(&path) {
+            return Err(anyhow!("Failed to parse {}", path.display()).context(e));
+        }
+        let gitignore = builder
+            .build()
+            .map_err(|e| anyhow!("Failed to parse {}", path.display()).context(e))?;
+        debug!(
+            "Loaded {} patterns from {}",
+            gitignore.len(),
+            path.display()
+        );
+        Ok(Self { gitignore })
+    }
+
+    /// Find the nearest ignore file within `dir` or one of its ancestors.
+    pub(crate) fn discover(dir: &Path) -> Result<Option<Self>> {
+        dir.ancestors()
+            .map(|dir| dir.join(IGNORE_FILE_NAME))
+            .find(|path| path.is_file())
+            .map(|path| Self::load(&path))
+            .transpose()
+    }
+
+    /// Check if `path` or one of its parent directories is ignored.
+    ///
+    /// Paths outside of the directory containing the ignore file are
+    /// never ignored.
+    pub(crate) fn is_ignored(&self, path: &Path) -> bool {
+        if !path.starts_with(self.gitignore.path()) {
+            return false;
+        }
+        self.gitignore
+            .matched_path_or_any_parents(path, path.is_dir())
+            .is_ignore()
+    }
+
+    /// Check if `path` is ignored, unless it is one of the `explicit` paths
+    /// passed on the command line.
+    pub(crate) fn excludes(&self, path: &Path, explicit: &[PathBuf]) -> bool {
+        if explicit.iter().any(|explicit| explicit == path) {
+            return false;
+        }
+        let ignored = self.is_ignored(path);
+        if ignored {
+            debug!("Skipping {} listed in {}", path.display(), IGNORE_FILE_NAME);
+        }
+        ignored
+    }
+}
+
+/// The ignore files of all projects the checked paths belong to.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct IgnoreFiles {
+    files: Vec<IgnoreFile>,
+    /// Paths passed on the command line, which are never ignored.
+    explicit: Vec<PathBuf>,
+}
+
+impl IgnoreFiles {
+    /// Find the ignore file of each of the `explicit` paths, looking in
+    /// the directory of the closest manifest and its ancestors, or those
+    /// of the path itself if it is not part of any crate.
+    pub(crate) fn discover(explicit: &[PathBuf]) -> Result<Self> {
+        let mut files = Vec::<IgnoreFile>::with_capacity(1);
+        for path in explicit {
+            let dir = path
+                .ancestors()
+                .find(|dir| dir.join("Cargo.toml").is_file())
+                .or_else(|| {
+                    if path.is_dir() {
+                        Some(path.as_path())
+                    } else {
+                        path.parent()
+                    }
+                });
+            let dir = if let Some(dir) = dir { dir } else { continue };
+            if let Some(file) = IgnoreFile::discover(dir)? {
+                if files
+                    .iter()
+                    .all(|known| known.gitignore.path() != file.gitignore.path())
+                {
+                    files.push(file);
+                }
+            }
+        }
+        Ok(Self {
+            files,
+            explicit: explicit.to_vec(),
+        })
+    }
+
+    /// Check if `path` is ignored by any of the ignore files, unless it
+    /// was passed explicitly.
+    pub(crate) fn excludes(&self, path: &Path) -> bool {
+        self.files
+            .iter()
+            .any(|file| file.excludes(path, &self.explicit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn patterns() {
+        let root =
+            std::env::temp_dir().join(format!("cargo-spellcheck-ignore-{}", std::process::id()));
+        fs::create_dir_all(root.join("src/generated")).unwrap();
+        fs::create_dir_all(root.join("docs/nested")).unwrap();
+        fs::write(
+            root.join(IGNORE_FILE_NAME),
+            "# generated code\nsrc/generated/\nCHANGELOG.md\ndocs/**/*.rst\n!docs/nested/keep.rst\n",
+        )
+        .unwrap();
+        let root = root.canonicalize().unwrap();
+
+        let discovered = IgnoreFile::discover(&root.join("docs/nested"));
+        let loaded = IgnoreFile::load(&root.join(IGNORE_FILE_NAME));
+        let _ = fs::remove_dir_all(&root);
+        let ignore_file = loaded.unwrap();
+        assert!(discovered.unwrap().is_some());
+
+        assert!(ignore_file.is_ignored(&root.join("src/generated/bindings.rs")));
+        assert!(ignore_file.is_ignored(&root.join("CHANGELOG.md")));
+        assert!(ignore_file.is_ignored(&root.join("docs/nested/CHANGELOG.md")));
+        assert!(ignore_file.is_ignored(&root.join("docs/nested/guide.rst")));
+        assert!(!ignore_file.is_ignored(&root.join("docs/nested/keep.rst")));
+        assert!(!ignore_file.is_ignored(&root.join("src/lib.rs")));
+        assert!(!ignore_file.is_ignored(Path::new("/elsewhere/CHANGELOG.md")));
+
+        let changelog = root.join("CHANGELOG.md");
+        assert!(!ignore_file.excludes(&changelog, &[changelog.clone()]));
+        assert!(ignore_file.excludes(&changelog, &[root.join("README.md")]));
+    }
+
+    #[test]
+    fn per_project() {
+        let root = std::env::temp_dir().join(format!(
+            "cargo-spellcheck-ignore-projects-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&root);
+        for project in &["a", "b"] {
+            fs::create_dir_all(root.join(project).join("src")).unwrap();
+            fs::write(root.join(project).join("Cargo.toml"), "").unwrap();
+        }
+        fs::write(root.join("a").join(IGNORE_FILE_NAME), "CHANGELOG.md\n").unwrap();
+        fs::write(root.join("b").join(IGNORE_FILE_NAME), "NOTES.md\n").unwrap();
+        let root = root.canonicalize().unwrap();
+
+        // from a file within the project, up to the manifest directory
+        let lib = root.join("a/src/lib.rs");
+        let ignore_files = IgnoreFiles::discover(&[lib.clone(), root.join("b")]);
+        let _ = fs::remove_dir_all(&root);
+        let ignore_files = ignore_files.unwrap();
+        assert_eq!(ignore_files.files.len(), 2);
+        assert!(ignore_files.excludes(&root.join("a/CHANGELOG.md")));
+        assert!(ignore_files.excludes(&root.join("b/NOTES.md")));
+        assert!(!ignore_files.excludes(&root.join("a/NOTES.md")));
+        assert!(!ignore_files.excludes(&lib));
+    }
+}
@@ -1,9 +1,8 @@
 use super::*;
+use crate::config::RustConfig;
 use crate::Documentation;
 
-use std::fs;
-
-use log::{trace, warn};
+use log::{debug, trace, warn};
 
 use std::path::{Path, PathBuf};
 
@@ -16,6 +15,12 @@ pub struct TraverseModulesIter {
     queue: VecDeque<(PathBuf, usize)>,
     /// zero limits to the provided path, if it is a directory, all children are collected
     max_depth: usize,
+    /// canonical paths of all yielded files, so symlinks can not cause loops
+    visited: HashSet<PathBuf>,
+    /// take symlinked files and module directories into account
+    follow_symlinks: bool,
+    /// files which are neither yielded nor searched for modules
+    ignored: IgnoreFiles,
 }
 
 impl Default for TraverseModulesIter {
@@ -23,6 +28,9 @@ impl Default for TraverseModulesIter {
         Self {
             max_depth: usize::MAX,
             queue: VecDeque::with_capacity(128),
+            visited: HashSet::new(),
+            follow_symlinks: false,
+            ignored: IgnoreFiles::default(),
         }
     }
 }
@@ -44,6 +52,7 @@ impl TraverseModulesIter {
         } else if meta.is_dir() {
             walkdir::WalkDir::new(path)
                 .max_depth(1)
+                .follow_links(self.follow_symlinks)
                 .same_file_system(true)
                 .into_iter()
                 .filter_map(|entry| {
@@ -80,9 +89,16 @@ impl TraverseModulesIter {
         Ok(me)
     }
 
-    pub fn with_depth_limit<P: AsRef<Path>>(path: P, max_depth: usize) -> Result<Self> {
+    /// Symlinked files and module directories are skipped, unless
+    /// `follow_symlinks` is set.
+    pub fn with_depth_limit<P: AsRef<Path>>(
+        path: P,
+        max_depth: usize,
+        follow_symlinks: bool,
+    ) -> Result<Self> {
         let mut me = Self {
             max_depth,
+            follow_symlinks,
             ..Default::default()
         };
         me.add_initial_path(path, 0)?;
@@ -92,15 +108,25 @@ impl TraverseModulesIter {
     /// Create a new path with (almost) infinite depth bounds
     #[allow(unused)]
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
-        Self::with_depth_limit(path, usize::MAX)
+        Self::with_depth_limit(path, usize::MAX, false)
     }
 
     pub fn collect_modules(&mut self, path: &Path, level: usize) -> Result<()> {
         if path.is_file() {
             trace!("collecting mods declared in file {}", path.display());
+            let base = path.parent().unwrap_or(path);
+            let follow_symlinks = self.follow_symlinks;
             self.queue.extend(
                 extract_modules_from_file(path)?
                     .into_iter()
+                    .filter(|module| {
+                        if !follow_symlinks && is_symlinked_below(base, module) {
+                            debug!("Skipping symlinked module {}", module.display());
+                            false
+                        } else {
+                            true
+                        }
+                    })
                     .map(|item| (item, level)),
             );
         } else {
@@ -113,44 +139,78 @@ impl TraverseModulesIter {
 impl Iterator for TraverseModulesIter {
     type Item = PathBuf;
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some((path, level)) = self.queue.pop_front() {
+        while let Some((path, level)) = self.queue.pop_front() {
+            let path = path.canonicalize().unwrap_or(path);
+            if !self.visited.insert(path.clone()) {
+                debug!("Skipping already visited {}", path.display());
+                continue;
+            }
+            if self.ignored.excludes(&path) {
+                continue;
+            }
             if level < self.max_depth {
                 // ignore the error here, there is nothing we can do really
                 // @todo potentially consider returning a result covering this
                 let _ = self.collect_modules(path.as_path(), level + 1);
             }
-            Some(path)
-        } else {
-            None
+            return Some(path);
         }
+        None
     }
 }
 
 /// traverse path with a depth limit, if the path is a directory all its children will be collected
 /// instead
-pub(crate) fn traverse(path: &Path) -> Result<impl Iterator<Item = Documentation>> {
-    traverse_with_depth_limit(path, usize::MAX)
+pub(crate) fn traverse(
+    path: &Path,
+    follow_symlinks: bool,
+    ignored: &IgnoreFiles,
+    config: &RustConfig,
+) -> Result<impl Iterator<Item = Documentation>> {
+    traverse_with_depth_limit(path, usize::MAX, follow_symlinks, ignored, config)
 }
 
 /// traverse path with a depth limit, if the path is a directory all its children will be collected
 /// as depth 0 instead
+///
+/// Modules excluded by one of the `ignored` files are skipped, along with their own modules.
 pub(crate) fn traverse_with_depth_limit(
     path: &Path,
     max_depth: usize,
+    follow_symlinks: bool,
+    ignored: &IgnoreFiles,
+    config: &RustConfig,
 ) -> Result<impl Iterator<Item = Documentation>> {
-    let it = TraverseModulesIter::with_depth_limit(path, max_depth)?
-        .filter_map(|path: PathBuf| -> Option<Documentation> {
-            fs::read_to_string(&path).ok().map(|content| {
-                Documentation::from((ContentOrigin::RustSourceFile(path), content.as_str()))
+    let config = config.clone();
+    let mut iter = TraverseModulesIter::with_depth_limit(path, max_depth, follow_symlinks)?;
+    iter.ignored = ignored.clone();
+    let it = iter
+        .filter_map(move |path: PathBuf| -> Option<Documentation> {
+            match crate::util::read_file(&path) {
+                Ok(content) => Some(content),
+                Err(e) => {
+                    warn!("Skipping module: {:#}", e);
+                    None
+                }
+            }
+            .map(|content| {
+                Documentation::load_from_str(
+                    ContentOrigin::RustSourceFile(path),
+                    content.as_str(),
+                    &config,
+                )
             })
         })
-        .filter(|documentation| !documentation.is_empty());
+        .filter(|documentation| {
+            !documentation.is_empty() || !documentation.unparsable().is_empty()
+        });
     Ok(it)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fs;
 
     fn demo_dir() -> PathBuf {
         manifest_dir().join("demo")
@@ -194,4 +254,38 @@ mod tests {
 
         assert_eq!(found.len(), expect.len());
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn module_symlink_loop() {
+        let dir = std::env::temp_dir().join(format!(
+            "cargo-spellcheck-module-loop-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("a")).unwrap();
+        fs::create_dir_all(dir.join("x")).unwrap();
+        fs::write(dir.join("a.rs"), "mod b;\n").unwrap();
+        fs::write(dir.join("a/b.rs"), "mod c;\n").unwrap();
+        fs::write(dir.join("x/c.rs"), "mod b;\n").unwrap();
+        // `a/b/c.rs` is `x/c.rs`, which declares `a/b/c/b.rs`, which is `a/b.rs` again
+        std::os::unix::fs::symlink(dir.join("x"), dir.join("a/b")).unwrap();
+        std::os::unix::fs::symlink(dir.join("a"), dir.join("x/c")).unwrap();
+        let dir = dir.canonicalize().unwrap();
+
+        let found = TraverseModulesIter::with_depth_limit(dir.join("a.rs"), usize::MAX, true)
+            .unwrap()
+            .take(10)
+            .collect::<Vec<PathBuf>>();
+        assert_eq!(
+            found,
+            vec![dir.join("a.rs"), dir.join("a/b.rs"), dir.join("x/c.rs")]
+        );
+        // symlinked module directories are not entered by default
+        let found = TraverseModulesIter::new(dir.join("a.rs"))
+            .unwrap()
+            .collect::<Vec<PathBuf>>();
+        assert_eq!(found, vec![dir.join("a.rs"), dir.join("a/b.rs")]);
+        let _ = fs::remove_dir_all(&dir);
+    }
 }
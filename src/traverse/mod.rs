@@ -7,10 +7,52 @@ use crate::Documentation;
 
 use anyhow::{anyhow, bail, Error, Result};
 use log::{debug, trace, warn};
-use std::convert::TryFrom;
-use std::fs;
 use std::path::{Path, PathBuf};
 
+/// The kinds of files which are checked, besides `Cargo.toml`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FileKind {
+    Markdown,
+    Source,
+    AsciiDoc,
+    RestructuredText,
+    /// Only checked if enabled.
+    PlainText,
+}
+
+/// Extensions of the files which are checked, and their kind.
+pub(crate) const CHECKED_EXTENSIONS: &[(&str, FileKind)] = &[
+    ("md", FileKind::Markdown),
+    ("rs", FileKind::Source),
+    ("adoc", FileKind::AsciiDoc),
+    ("asciidoc", FileKind::AsciiDoc),
+    ("rst", FileKind::RestructuredText),
+    ("txt", FileKind::PlainText),
+];
+
+impl FileKind {
+    /// The kind of the file at `path` by its extension, if checked at all.
+    pub(crate) fn of(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?;
+        CHECKED_EXTENSIONS
+            .iter()
+            .find(|(checked, _kind)| *checked == extension)
+            .map(|(_extension, kind)| *kind)
+    }
+
+    /// The entity to check the file at `path` of this kind, `recurse` into
+    /// the modules declared by a rust source.
+    fn entity(self, path: PathBuf, recurse: bool) -> CheckEntity {
+        match self {
+            Self::Markdown => CheckEntity::Markdown(path),
+            Self::Source => CheckEntity::Source(path, recurse),
+            Self::AsciiDoc => CheckEntity::AsciiDoc(path),
+            Self::RestructuredText => CheckEntity::RestructuredText(path),
+            Self::PlainText => CheckEntity::PlainText(path),
+        }
+    }
+}
+
 fn cwd() -> Result<PathBuf> {
     std::env::current_dir().map_err(|_e| anyhow::anyhow!("Missing cwd!"))
 }
@@ -20,11 +62,14 @@ fn manifest_dir() -> PathBuf {
     PathBuf::from(env!("CARGO_MANIFEST_DIR"))
 }
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 mod iter;
 pub use iter::*;
 
+mod ignorefile;
+use ignorefile::IgnoreFiles;
+
 use proc_macro2::Spacing;
 use proc_macro2::TokenStream;
 use proc_macro2::TokenTree;
@@ -124,9 +169,7 @@ fn extract_modules_inner<P: AsRef<Path>>(path: P, stream: TokenStream) -> Result
 pub(crate) fn extract_modules_from_file<P: AsRef<Path>>(path: P) -> Result<Vec<PathBuf>> {
     let path: &Path = path.as_ref();
     if let Some(path_str) = path.to_str() {
-        let s = std::fs::read_to_string(path_str).map_err(|e| {
-            Error::from(e).context(anyhow!("Failed to read file content of {}", path_str))
-        })?;
+        let s = crate::util::read_file(path)?;
         let stream = syn::parse_str::<proc_macro2::TokenStream>(s.as_str())
             .map_err(|e| Error::from(e).context(anyhow!("File {} has syntax errors", path_str)))?;
         extract_modules_inner(path.to_owned(), stream)
@@ -138,17 +181,33 @@ pub(crate) fn extract_modules_from_file<P: AsRef<Path>>(path: P) -> Result<Vec<P
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum CheckEntity {
     Markdown(PathBuf),
+    AsciiDoc(PathBuf),
+    RestructuredText(PathBuf),
+    PlainText(PathBuf),
     Source(PathBuf, bool), // recurse is the bool
-    ManifestDescription(String),
+    /// The `Cargo.toml` with a `description` or `keywords`.
+    ManifestMetadata(PathBuf),
+}
+
+impl CheckEntity {
+    /// The file path of the entity, if it is backed by a file.
+    fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Markdown(path)
+            | Self::AsciiDoc(path)
+            | Self::RestructuredText(path)
+            | Self::PlainText(path)
+            | Self::Source(path, _)
+            | Self::ManifestMetadata(path) => Some(path.as_path()),
+        }
+    }
 }
 
 fn load_manifest<P: AsRef<Path>>(manifest_dir: P) -> Result<cargo_toml::Manifest> {
     let manifest_dir = manifest_dir.as_ref();
     let manifest_file = manifest_dir.join("Cargo.toml");
     // read to str first to provide better error messages
-    let manifest_content = std::fs::read_to_string(&manifest_file).map_err(|e| {
-        anyhow::anyhow!("Failed to open manifest file {}", manifest_file.display()).context(e)
-    })?;
+    let manifest_content = crate::util::read_file(&manifest_file)?;
     let mut manifest = cargo_toml::Manifest::from_str(manifest_content.as_str()).map_err(|e| {
         anyhow::anyhow!("Failed to parse manifest file {}", manifest_file.display()).context(e)
     })?;
@@ -182,9 +241,13 @@ fn to_manifest_dir<P: AsRef<Path>>(manifest_dir: P) -> Result<PathBuf> {
 }
 
 /// Extract all cargo manifest products / build targets.
+///
+/// Products located in a symlinked directory are skipped, unless
+/// `follow_symlinks` is set.
 fn extract_products(
     manifest: &cargo_toml::Manifest,
     manifest_dir: &Path,
+    follow_symlinks: bool,
 ) -> Result<Vec<CheckEntity>> {
     let iter = manifest
         .bin
@@ -197,6 +260,15 @@ fn extract_products(
         .filter_map(|product| product.path)
         // cargo_toml's complete is not very truthfull
         .filter(|path_str| manifest_dir.join(path_str).is_file())
+        .filter(|path_str| {
+            let path = manifest_dir.join(path_str);
+            if !follow_symlinks && is_symlinked_below(manifest_dir, &path) {
+                debug!("Skipping symlinked product {}", path.display());
+                false
+            } else {
+                true
+            }
+        })
         .map(|path_str| CheckEntity::Source(manifest_dir.join(path_str), true))
         .collect::<Vec<CheckEntity>>();
 
@@ -212,7 +284,8 @@ fn extract_readme(
     if let Some(package) = manifest.package.clone() {
         if let Some(readme) = package.readme {
             let readme = PathBuf::from(readme);
-            if readme.is_file() {
+            // relative to the manifest, not the current working directory
+            if manifest_dir.join(&readme).is_file() {
                 acc.push(CheckEntity::Markdown(manifest_dir.join(readme)));
             } else {
                 warn!(
@@ -221,14 +294,20 @@ fn extract_readme(
                 );
             }
         }
-        if let Some(description) = package.description {
-            acc.push(CheckEntity::ManifestDescription(description.to_owned()));
+        if package.description.is_some() || !package.keywords.is_empty() {
+            acc.push(CheckEntity::ManifestMetadata(
+                manifest_dir.join("Cargo.toml"),
+            ));
         }
     }
     Ok(acc)
 }
 
-fn handle_manifest<P: AsRef<Path>>(manifest_dir: P, skip_readme: bool) -> Result<Vec<CheckEntity>> {
+fn handle_manifest<P: AsRef<Path>>(
+    manifest_dir: P,
+    skip_readme: bool,
+    follow_symlinks: bool,
+) -> Result<Vec<CheckEntity>> {
     let manifest_dir = to_manifest_dir(manifest_dir)?;
     trace!("Handle manifest in dir: {}", manifest_dir.display());
 
@@ -241,7 +320,7 @@ fn handle_manifest<P: AsRef<Path>>(manifest_dir: P, skip_readme: bool) -> Result
         .context(e)
     })?;
 
-    let mut acc = extract_products(&manifest, &manifest_dir).map_err(|e| {
+    let mut acc = extract_products(&manifest, &manifest_dir, follow_symlinks).map_err(|e| {
         anyhow!(
             "Failed to extract products from manifest {}",
             manifest_dir.display()
@@ -279,11 +358,20 @@ fn handle_manifest<P: AsRef<Path>>(manifest_dir: P, skip_readme: bool) -> Result
                     )
                     .context(e)
                 }) {
-                    if let Ok(member) = extract_products(&member_manifest, &member_dir) {
+                    if let Ok(member) =
+                        extract_products(&member_manifest, &member_dir, follow_symlinks)
+                    {
                         acc.extend(member.into_iter());
                     } else {
                         warn!("Workspace member {} product extraction failed", item);
                     }
+                    if !skip_readme {
+                        if let Ok(member) = extract_readme(&member_manifest, &member_dir) {
+                            acc.extend(member.into_iter());
+                        } else {
+                            warn!("Workspace member {} readme extraction failed", item);
+                        }
+                    }
                 } else {
                     warn!("Opening manifest from member failed {}", item);
                 }
@@ -293,12 +381,363 @@ fn handle_manifest<P: AsRef<Path>>(manifest_dir: P, skip_readme: bool) -> Result
     Ok(acc)
 }
 
+/// Drop all but the first common mark entity referring to the same file,
+/// i.e. a README shared by all members of a workspace, so each file is
+/// checked exactly once.
+fn dedup_markdown(entities: Vec<CheckEntity>) -> Vec<CheckEntity> {
+    let mut seen = HashSet::new();
+    entities
+        .into_iter()
+        .filter(|entity| match entity {
+            CheckEntity::Markdown(path) => {
+                let canonical = path.canonicalize().unwrap_or_else(|_| path.to_owned());
+                if seen.insert(canonical) {
+                    true
+                } else {
+                    debug!("Markdown file {} is already checked", path.display());
+                    false
+                }
+            }
+            _ => true,
+        })
+        .collect()
+}
+
+/// Names of the package and its dependencies declared in `manifest`, in
+/// lower case.
+///
+/// Each name is included with hyphens and with underscores, as used in
+/// paths, and so are its parts, which the tokenizer splits at hyphens.
+fn manifest_names(manifest: &cargo_toml::Manifest) -> HashSet<String> {
+    let mut names = HashSet::new();
+    let package = manifest
+        .package
+        .as_ref()
+        .map(|package| package.name.clone());
+    let dependencies = manifest.dependencies.iter().flat_map(|(name, dependency)| {
+        std::iter::once(name.clone()).chain(
+            dependency
+                .detail()
+                .and_then(|detail| detail.package.clone())
+                .into_iter(),
+        )
+    });
+    for name in package.into_iter().chain(dependencies) {
+        let name = name.to_lowercase();
+        names.extend(
+            name.split(|c| c == '-' || c == '_')
+                .filter(|part| !part.is_empty())
+                .map(str::to_owned),
+        );
+        names.insert(name.replace('-', "_"));
+        names.insert(name.replace('_', "-"));
+    }
+    names
+}
+
+/// Find the directory of the manifest closest to `path`, searching upwards.
+fn nearest_manifest_dir(path: &Path) -> Option<&Path> {
+    path.ancestors()
+        .skip(1)
+        .find(|dir| dir.join("Cargo.toml").is_file())
+}
+
+/// Accept the names of the package and its dependencies for every origin
+/// in `docs`, as declared by the nearest manifest of the origin.
+fn accept_dependency_names(docs: &mut Documentation) {
+    let mut cache = HashMap::<PathBuf, HashSet<String>>::new();
+    let origins = docs
+        .iter()
+        .map(|(origin, _chunks)| origin.clone())
+        .collect::<Vec<_>>();
+    for origin in origins {
+        let manifest_dir = if let Some(manifest_dir) = nearest_manifest_dir(origin.as_path()) {
+            manifest_dir
+        } else {
+            debug!("No manifest found for {}", origin.as_path().display());
+            continue;
+        };
+        let names = cache.entry(manifest_dir.to_owned()).or_insert_with(|| {
+            match load_manifest(manifest_dir) {
+                Ok(manifest) => manifest_names(&manifest),
+                Err(e) => {
+                    warn!(
+                        "Failed to load manifest from {} to accept dependency names: {}",
+                        manifest_dir.display(),
+                        e
+                    );
+                    HashSet::new()
+                }
+            }
+        });
+        trace!(
+            "Accepting {} names for {}",
+            names.len(),
+            origin.as_path().display()
+        );
+        docs.accept_words(&origin, names.iter().cloned());
+    }
+}
+
+/// Hidden files and directories start with a `.`.
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|file_name| file_name.to_str())
+        .map(|file_name| file_name.starts_with('.'))
+        .unwrap_or(false)
+}
+
+/// Whether `path` leads through a symlink below `root`, either being one
+/// itself or located in a symlinked directory.
+fn is_symlinked_below(root: &Path, path: &Path) -> bool {
+    let relative = if let Ok(relative) = path.strip_prefix(root) {
+        relative
+    } else {
+        return false;
+    };
+    let mut current = root.to_owned();
+    relative.components().any(|component| {
+        current.push(component);
+        current
+            .symlink_metadata()
+            .map(|meta| meta.file_type().is_symlink())
+            .unwrap_or(false)
+    })
+}
+
+/// Collect the configured include paths, files are taken as they are,
+/// directories are searched for all files with a known extension.
+///
+/// Hidden entries below an included directory are skipped unless `hidden`
+/// is set. Rust sources are never recursed into, and manifests are not resolved.
+/// Plain text files are only included with `plain_text`.
+///
+/// Symlinked directories are only descended into with `follow_symlinks`,
+/// symlink loops are reported and skipped. Each file is returned once,
+/// by its canonical path, regardless of how many links lead to it.
+fn extract_include_paths(
+    include_paths: &[PathBuf],
+    hidden: bool,
+    follow_symlinks: bool,
+    plain_text: bool,
+) -> Vec<CheckEntity> {
+    let classify = |path: PathBuf| -> Option<CheckEntity> {
+        match FileKind::of(&path) {
+            Some(FileKind::PlainText) if !plain_text => None,
+            Some(kind) => Some(kind.entity(path, false)),
+            None => {
+                trace!(
+                    "Skipping included file with unknown type {}",
+                    path.display()
+                );
+                None
+            }
+        }
+    };
+
+    let mut acc = Vec::with_capacity(include_paths.len());
+    let mut visited = HashSet::new();
+    for path in include_paths {
+        let path = if let Ok(path) = path.canonicalize() {
+            path
+        } else {
+            warn!("Configured include path does not exist: {}", path.display());
+            continue;
+        };
+        if path.is_dir() {
+            let mut files = Vec::new();
+            for entry in walkdir::WalkDir::new(&path)
+                .follow_links(follow_symlinks)
+                .into_iter()
+                .filter_entry(|entry| hidden || entry.depth() == 0 || !is_hidden(entry.path()))
+            {
+                match entry {
+                    Ok(entry) if entry.file_type().is_file() => files.push(entry.path().to_owned()),
+                    Ok(_) => {}
+                    Err(e) => match (e.path(), e.loop_ancestor()) {
+                        (Some(path), Some(ancestor)) => warn!(
+                            "Skipping symlink loop {} pointing to {}",
+                            path.display(),
+                            ancestor.display()
+                        ),
+                        _ => warn!("Failed to traverse included directory: {}", e),
+                    },
+                }
+            }
+            files.sort();
+            acc.extend(
+                files
+                    .into_iter()
+                    .filter_map(|path| path.canonicalize().ok())
+                    .filter(|path| visited.insert(path.clone()))
+                    .filter_map(classify),
+            );
+        } else if visited.insert(path.clone()) {
+            acc.extend(classify(path));
+        }
+    }
+    acc
+}
+
+/// Add the content of the common mark file at `path` as a single chunk.
+fn add_markdown(docs: &mut Documentation, path: PathBuf) -> Result<()> {
+    let content = crate::util::read_file(&path)?;
+    add_markdown_content(docs, path, content)
+}
+
+/// Add common mark `content` as a single chunk, attributed to `path`.
+fn add_markdown_content(docs: &mut Documentation, path: PathBuf, content: String) -> Result<()> {
+    if content.len() < 1 {
+        bail!("Common mark / markdown file {} is empty", path.display())
+    }
+    // extract the full content span and range
+    let start = LineColumn { line: 1, column: 0 };
+    let end = content
+        .lines()
+        .enumerate()
+        .last()
+        .map(|(idx, line)| (idx + 1, line))
+        .map(|(lineno, line)| LineColumn {
+            line: lineno,
+            column: line.chars().count(),
+        })
+        .ok_or_else(|| anyhow!("Common mark / markdown file does not contain a single line"))?;
+
+    let span = Span { start, end };
+    let source_mapping = indexmap::indexmap! {
+       0..content.chars().count() => span
+    };
+    docs.disable_regions(&path, content.as_str());
+    docs.add(
+        ContentOrigin::CommonMarkFile(path),
+        vec![CheckableChunk::from_string(content, source_mapping)],
+    );
+    Ok(())
+}
+
+/// Add the files included as documentation via `include_str!`, unless
+/// they are already part of `docs`, i.e. a `README.md` also listed in
+/// the manifest.
+fn add_includes(docs: &mut Documentation) -> Result<()> {
+    let mut known = docs
+        .iter()
+        .filter_map(|(origin, _)| origin.as_path().canonicalize().ok())
+        .collect::<HashSet<PathBuf>>();
+    for include in docs.includes().to_vec() {
+        let path = if let Ok(path) = include.canonicalize() {
+            path
+        } else {
+            warn!(
+                "Included documentation file {} does not exist",
+                include.display()
+            );
+            continue;
+        };
+        if !known.insert(path.clone()) {
+            trace!(
+                "Included documentation {} is already checked",
+                path.display()
+            );
+            continue;
+        }
+        add_markdown(docs, path)?;
+    }
+    Ok(())
+}
+
+/// Extract all chunks from `content`, which is not read from disk but
+/// attributed to `path`, i.e. for content passed via `stdin`.
+///
+/// The parser is chosen by the extension of `path`, anything not
+/// recognized is treated as common mark.
+pub(crate) fn extract_content(
+    path: PathBuf,
+    content: String,
+    config: &Config,
+) -> Result<Documentation> {
+    let mut docs = Documentation::new();
+    match FileKind::of(&path) {
+        Some(FileKind::Source) => docs.join(Documentation::load_from_str(
+            ContentOrigin::RustSourceFile(path),
+            content.as_str(),
+            &config.rust(),
+        )),
+        Some(FileKind::AsciiDoc) => {
+            let chunk = CheckableChunk::from_asciidoc(content.as_str());
+            docs.disable_regions(&path, content.as_str());
+            docs.add(ContentOrigin::AsciiDocFile(path), vec![chunk]);
+            &mut docs
+        }
+        Some(FileKind::RestructuredText) => {
+            let chunk = CheckableChunk::from_restructuredtext(content.as_str());
+            docs.disable_regions(&path, content.as_str());
+            docs.add(ContentOrigin::RestructuredTextFile(path), vec![chunk]);
+            &mut docs
+        }
+        Some(FileKind::PlainText) if config.check_plain_text() => {
+            let chunk = CheckableChunk::from_plain_text(content.as_str());
+            docs.disable_regions(&path, content.as_str());
+            docs.add(ContentOrigin::PlainTextFile(path), vec![chunk]);
+            &mut docs
+        }
+        extension => {
+            debug!(
+                "Treating content of {} with extension {:?} as common mark",
+                path.display(),
+                extension
+            );
+            add_markdown_content(&mut docs, path, content)?;
+            &mut docs
+        }
+    };
+
+    if let Some(ref hunspell) = config.hunspell {
+        if hunspell.allow_dependency_names() {
+            accept_dependency_names(&mut docs);
+        }
+    }
+    docs.configure_markdown(config.markdown());
+
+    Ok(docs)
+}
+
+/// Add the `description` and `keywords` of the manifest at `path`,
+/// as far as enabled by the configuration.
+fn add_manifest_metadata(docs: &mut Documentation, path: PathBuf, config: &Config) -> Result<()> {
+    let (check_description, check_keywords) = config
+        .manifest
+        .as_ref()
+        .map(|manifest| (manifest.check_description(), manifest.check_keywords()))
+        .unwrap_or_default();
+    if !check_description && !check_keywords {
+        trace!("Not checking the metadata of {}", path.display());
+        return Ok(());
+    }
+    let content = crate::util::read_file(&path)?;
+    let mut chunks = Vec::with_capacity(2);
+    if check_description {
+        chunks.extend(CheckableChunk::from_manifest_description(content.as_str()));
+    }
+    if check_keywords {
+        chunks.extend(CheckableChunk::from_manifest_keywords(content.as_str()));
+    }
+    if chunks.is_empty() {
+        debug!("Manifest {} has no checkable metadata", path.display());
+    } else {
+        docs.add(ContentOrigin::CargoManifest(path), chunks);
+    }
+    Ok(())
+}
+
 /// Extract all chunks from
 pub(crate) fn extract(
     mut paths: Vec<PathBuf>,
     mut recurse: bool,
     skip_readme: bool,
-    _config: &Config,
+    skip_markdown: bool,
+    hidden: bool,
+    follow_symlinks: bool,
+    config: &Config,
 ) -> Result<Documentation> {
     let cwd = cwd()?;
     // if there are no arguments, pretend to be told to check the whole project
@@ -313,8 +752,7 @@ pub(crate) fn extract(
     enum Extraction {
         Manifest(PathBuf),
         Missing(PathBuf),
-        Source(PathBuf),
-        Markdown(PathBuf),
+        File(FileKind, PathBuf),
     }
 
     // stage 1 - obtain canonical paths
@@ -330,17 +768,28 @@ pub(crate) fn extract(
     }));
 
     debug!("Running on absolute dirs {:?} ", &flow);
+    // explicitly passed paths are never ignored
+    let explicit = flow.iter().cloned().collect::<Vec<_>>();
+    let ignore_files = IgnoreFiles::discover(&explicit)?;
 
     // stage 2 - check for manifest, .rs , .md files and directories
     let mut files_to_check = Vec::with_capacity(64);
     while let Some(path) = flow.pop_front() {
         let x = if let Ok(meta) = path.metadata() {
             if meta.is_file() {
-                match path.file_name().map(|x| x.to_str()).flatten() {
-                    Some(file_name) if file_name == "Cargo.toml" => Extraction::Manifest(path),
-                    Some(file_name) if file_name.ends_with(".md") => Extraction::Markdown(path),
-                    Some(file_name) if file_name.ends_with(".rs") => Extraction::Source(path),
-                    _ => {
+                match FileKind::of(&path) {
+                    _ if path.file_name() == Some("Cargo.toml".as_ref()) => {
+                        Extraction::Manifest(path)
+                    }
+                    Some(FileKind::PlainText) if !config.check_plain_text() => {
+                        debug!(
+                            "Skipping plain text file {}, `plain_text.enabled` is not set",
+                            path.display()
+                        );
+                        continue;
+                    }
+                    Some(kind) => Extraction::File(kind, path),
+                    None => {
                         warn!("Unexpected item made it into the items {}", path.display());
                         continue;
                     }
@@ -354,8 +803,20 @@ pub(crate) fn extract(
 
                     // we know it's a directory, and we limit the entries to 0 levels,
                     // will cause to yield all "^.*\.rs$" files in that dir
-                    // which is what we want in this case
-                    flow.extend(TraverseModulesIter::with_depth_limit(&path, 0)?);
+                    // which is what we want in this case, the explicitly
+                    // passed directory itself is never considered hidden
+                    flow.extend(
+                        TraverseModulesIter::with_depth_limit(&path, 0, follow_symlinks)?.filter(
+                            |path| {
+                                if !hidden && is_hidden(path) {
+                                    debug!("Skipping hidden file {}", path.display());
+                                    false
+                                } else {
+                                    true
+                                }
+                            },
+                        ),
+                    );
                     continue;
                 }
             } else {
@@ -375,21 +836,64 @@ pub(crate) fn extract(
         .try_fold::<Vec<_>, _, Result<_>>(Vec::with_capacity(64), |mut acc, tagged_path| {
             match tagged_path {
                 Extraction::Manifest(ref cargo_toml_path) => {
-                    let manifest_list = handle_manifest(cargo_toml_path, skip_readme)?;
+                    let manifest_list =
+                        handle_manifest(cargo_toml_path, skip_readme, follow_symlinks)?;
                     acc.extend(manifest_list);
                 }
                 Extraction::Missing(ref missing_path) => warn!(
                     "File passed as argument or listed in Cargo.toml manifest does not exist: {}",
                     missing_path.display()
                 ),
-                Extraction::Source(path) => acc.push(CheckEntity::Source(path, recurse)),
-                Extraction::Markdown(path) => acc.push(CheckEntity::Markdown(path)),
+                Extraction::File(kind, path) => acc.push(kind.entity(path, recurse)),
             }
             Ok(acc)
         })?;
 
+    // stage 3.5 - add the configured include paths, which are not subject to traversal
+    let mut files_to_check = files_to_check;
+    if let Some(ref extra) = config.extra {
+        for entity in extract_include_paths(
+            extra.include_paths(),
+            hidden,
+            follow_symlinks,
+            config.check_plain_text(),
+        ) {
+            if files_to_check
+                .iter()
+                .any(|known| known.path() == entity.path())
+            {
+                trace!("Included {:?} is already checked", &entity);
+            } else {
+                files_to_check.push(entity);
+            }
+        }
+    }
+
+    // each README is checked once, even if shared by multiple manifests
+    let mut files_to_check = dedup_markdown(files_to_check);
+
+    // stage 3.75 - drop all common mark files, including manifest and included READMEs
+    if skip_markdown {
+        files_to_check.retain(|entity| match entity {
+            CheckEntity::Markdown(path) => {
+                debug!("Skipping markdown file {}", path.display());
+                false
+            }
+            _ => true,
+        });
+    }
+
+    // stage 3.8 - drop everything listed in `.spellcheckignore`
+    files_to_check.retain(|entity| {
+        !entity
+            .path()
+            .map(|path| ignore_files.excludes(path))
+            .unwrap_or(false)
+    });
+
     // stage 4 - expand from the passed source files, if recursive, recurse down the module train
-    let combined: Documentation = files_to_check
+    let rust = config.rust();
+    let mut combined: Documentation = files_to_check
         .into_iter()
         .try_fold::<Documentation, _, Result<Documentation>>(
             Documentation::new(),
@@ -397,68 +901,73 @@ pub(crate) fn extract(
                 match item {
                     CheckEntity::Source(path, recurse) => {
                         if recurse {
-                            let iter = traverse(path.as_path())?;
+                            let iter =
+                                traverse(path.as_path(), follow_symlinks, &ignore_files, &rust)?;
                             docs.extend(iter);
                         } else {
-                            let content: String = fs::read_to_string(&path).map_err(|e| {
-                                anyhow!("Failed to read {}", path.display()).context(e)
-                            })?;
-                            if let Ok(cluster) = Clusters::try_from(content.as_str()) {
-                                let chunks = Vec::<CheckableChunk>::from(cluster);
-                                docs.add(ContentOrigin::RustSourceFile(path.to_owned()), chunks);
-                            } else {
-                                log::error!("BUG: Failed to create cluster for {}", path.display());
-                            }
+                            let content = crate::util::read_file(&path)?;
+                            docs.join(Documentation::load_from_str(
+                                ContentOrigin::RustSourceFile(path.to_owned()),
+                                content.as_str(),
+                                &rust,
+                            ));
                         }
                     }
                     CheckEntity::Markdown(path) => {
-                        let content = std::fs::read_to_string(&path).map_err(|e| {
-                            anyhow!("Common mark / markdown file does not exist").context(e)
-                        })?;
-                        if content.len() < 1 {
-                            bail!("Common mark / markdown file is empty")
-                        }
-                        // extract the full content span and range
-                        let start = LineColumn { line: 1, column: 0 };
-                        let end = content
-                            .lines()
-                            .enumerate()
-                            .last()
-                            .map(|(idx, line)| (idx + 1, line))
-                            .map(|(lineno, line)| LineColumn {
-                                line: lineno,
-                                column: line.chars().count(),
-                            })
-                            .ok_or_else(|| {
-                                anyhow!(
-                                    "Common mark / markdown file does not contain a single line"
-                                )
-                            })?;
-
-                        let span = Span { start, end };
-                        let source_mapping = indexmap::indexmap! {
-                           0..content.chars().count() => span
-                        };
+                        add_markdown(&mut docs, path)?;
+                    }
+                    CheckEntity::AsciiDoc(path) => {
+                        let content = crate::util::read_file(&path)?;
+                        docs.disable_regions(&path, content.as_str());
+                        docs.add(
+                            ContentOrigin::AsciiDocFile(path.to_owned()),
+                            vec![CheckableChunk::from_asciidoc(content.as_str())],
+                        );
+                    }
+                    CheckEntity::RestructuredText(path) => {
+                        let content = crate::util::read_file(&path)?;
+                        docs.disable_regions(&path, content.as_str());
                         docs.add(
-                            ContentOrigin::CommonMarkFile(path.to_owned()),
-                            vec![CheckableChunk::from_string(content, source_mapping)],
+                            ContentOrigin::RestructuredTextFile(path.to_owned()),
+                            vec![CheckableChunk::from_restructuredtext(content.as_str())],
                         );
                     }
-                    other => {
-                        warn!("Did not impl handling of {:?} type files", other);
-                        // @todo generate Documentation structs from non-file sources
+                    CheckEntity::PlainText(path) => {
+                        let content = crate::util::read_file(&path)?;
+                        docs.disable_regions(&path, content.as_str());
+                        docs.add(
+                            ContentOrigin::PlainTextFile(path.to_owned()),
+                            vec![CheckableChunk::from_plain_text(content.as_str())],
+                        );
+                    }
+                    CheckEntity::ManifestMetadata(path) => {
+                        add_manifest_metadata(&mut docs, path, config)?;
                     }
                 }
                 Ok(docs)
             },
         )?;
 
+    // stage 4.5 - documentation included from external files
+    if !skip_markdown {
+        add_includes(&mut combined)?;
+    }
+
+    // stage 5 - accept the names of dependencies
+    if let Some(ref hunspell) = config.hunspell {
+        if hunspell.allow_dependency_names() {
+            accept_dependency_names(&mut combined);
+        }
+    }
+    combined.configure_markdown(config.markdown());
+
     Ok(combined)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fs;
 
     const TEST_FILE_FRAGMENTS: &str = "src/nested/fragments.rs";
     const TEST_FILE_SIMPLE: &str = "src/nested/fragments/simple.rs";
@@ -484,7 +993,7 @@ mod tests {
     fn manifest_entries() {
         let (manifest, dir) = demo_dir_manifest();
         assert_eq!(
-            extract_products(&manifest, &dir).expect("Must succeed"),
+            extract_products(&manifest, &dir, false).expect("Must succeed"),
             vec![
                 CheckEntity::Source(demo_dir().join("src/main.rs"), true),
                 CheckEntity::Source(demo_dir().join("src/lib.rs"), true),
@@ -494,13 +1003,377 @@ mod tests {
             extract_readme(&manifest, &dir).expect("Must succeed"),
             vec![
                 CheckEntity::Markdown(demo_dir().join("README.md")),
-                CheckEntity::ManifestDescription(
-                    "A silly demo with plenty of spelling mistakes for cargo-spellcheck demos and CI".to_string()
-                ),
+                CheckEntity::ManifestMetadata(demo_dir().join("Cargo.toml")),
             ]
         );
     }
 
+    #[test]
+    fn workspace_shared_readme() {
+        let workspace = demo_dir().join("workspace");
+        let readmes = |paths: Vec<PathBuf>| {
+            let docs = extract(paths, false, false, false, false, false, &Config::default())
+                .expect("Extraction of the workspace must succeed");
+            docs.iter()
+                .filter(|(origin, _)| matches!(origin, ContentOrigin::CommonMarkFile(_)))
+                .flat_map(|(origin, chunks)| {
+                    chunks.iter().map(move |_| origin.as_path().to_owned())
+                })
+                .collect::<Vec<_>>()
+        };
+        let expected = vec![workspace.join("README.md").canonicalize().unwrap()];
+        let canonical = |paths: Vec<PathBuf>| {
+            paths
+                .into_iter()
+                .map(|path| path.canonicalize().unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(canonical(readmes(vec![workspace.clone()])), expected);
+        assert_eq!(
+            canonical(readmes(vec![
+                workspace.join("alpha"),
+                workspace.join("beta")
+            ])),
+            expected
+        );
+    }
+
+    #[test]
+    fn include_paths() {
+        let entities = extract_include_paths(
+            &[
+                demo_dir().join("README.md"),
+                demo_dir().join("src/nested/fragments"),
+                demo_dir().join("does/not/exist.md"),
+            ],
+            false,
+            false,
+            false,
+        );
+        assert_eq!(
+            entities[0],
+            CheckEntity::Markdown(demo_dir().join("README.md"))
+        );
+        assert!(entities.len() > 1);
+        for entity in &entities[1..] {
+            match entity {
+                CheckEntity::Source(path, recurse) => {
+                    assert!(path.starts_with(demo_dir().join("src/nested/fragments")));
+                    assert!(!recurse);
+                }
+                other => panic!("Unexpected entity {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn plain_text() {
+        let dir =
+            std::env::temp_dir().join(format!("cargo-spellcheck-plain-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let notes = dir.join("notes.txt");
+        fs::write(&notes, "First paragraph.\n\nSecond *one*, not markdown.\n").unwrap();
+
+        let extract_notes = |config: &Config| {
+            extract(
+                vec![notes.clone()],
+                false,
+                false,
+                false,
+                false,
+                false,
+                config,
+            )
+            .expect("Extraction must succeed")
+        };
+        // skipped unless enabled
+        assert!(extract_notes(&Config::default()).iter().next().is_none());
+
+        let config = Config::parse("[plain_text]\nenabled = true\n").unwrap();
+        let docs = extract_notes(&config);
+        let (origin, chunks) = docs.iter().next().expect("Plain text file must be checked");
+        assert_eq!(
+            origin,
+            &ContentOrigin::PlainTextFile(notes.canonicalize().unwrap())
+        );
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(
+            chunks[0].as_str(),
+            "First paragraph.\n\nSecond *one*, not markdown."
+        );
+        assert_eq!(docs.erase_markdown(&chunks[0]).as_str(), chunks[0].as_str());
+
+        let entities = extract_include_paths(&[dir.clone()], false, false, true);
+        assert_eq!(
+            entities,
+            vec![CheckEntity::PlainText(notes.canonicalize().unwrap())]
+        );
+        assert!(extract_include_paths(&[dir.clone()], false, false, false).is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn hidden_include_paths() {
+        let fragments = demo_dir().join("src/nested/fragments");
+        let hidden = fragments.join(".hidden/hidden.rs");
+        let contains_hidden = |entities: Vec<CheckEntity>| {
+            entities
+                .iter()
+                .any(|entity| entity.path() == Some(hidden.as_path()))
+        };
+        assert!(!contains_hidden(extract_include_paths(
+            &[fragments.clone()],
+            false,
+            false,
+            false
+        )));
+        assert!(contains_hidden(extract_include_paths(
+            &[fragments.clone()],
+            true,
+            false,
+            false
+        )));
+        // explicitly listed, hence never skipped
+        assert!(contains_hidden(extract_include_paths(
+            &[fragments.join(".hidden")],
+            false,
+            false,
+            false
+        )));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_loop() {
+        let dir =
+            std::env::temp_dir().join(format!("cargo-spellcheck-symlinks-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("top.md"), "# Top\n").unwrap();
+        fs::write(dir.join("sub/nested.md"), "# Nested\n").unwrap();
+        std::os::unix::fs::symlink(&dir, dir.join("sub/loop")).unwrap();
+        std::os::unix::fs::symlink(dir.join("sub"), dir.join("alias")).unwrap();
+        let dir = dir.canonicalize().unwrap();
+
+        let expected = vec![
+            CheckEntity::Markdown(dir.join("sub/nested.md")),
+            CheckEntity::Markdown(dir.join("top.md")),
+        ];
+        for follow_symlinks in &[false, true] {
+            let mut entities = extract_include_paths(
+                &[dir.clone(), dir.join("alias/nested.md")],
+                false,
+                *follow_symlinks,
+                false,
+            );
+            entities.sort_by(|a, b| a.path().cmp(&b.path()));
+            assert_eq!(entities, expected);
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn symlinked_sources() {
+        let dir = std::env::temp_dir().join(format!(
+            "cargo-spellcheck-symlinked-src-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("shared")).unwrap();
+        fs::create_dir_all(dir.join("krate")).unwrap();
+        fs::create_dir_all(dir.join("other/src")).unwrap();
+        fs::write(
+            dir.join("shared/lib.rs"),
+            "mod inner;\n/// Shared.\nstruct S;\n",
+        )
+        .unwrap();
+        fs::write(dir.join("shared/inner.rs"), "/// Inner.\nstruct I;\n").unwrap();
+        const MANIFEST: &str =
+            "[package]\nname = \"krate\"\nversion = \"0.1.0\"\n\n[lib]\npath = \"src/lib.rs\"\n";
+        // a crate with a symlinked `src` directory
+        fs::write(dir.join("krate/Cargo.toml"), MANIFEST).unwrap();
+        std::os::unix::fs::symlink(dir.join("shared"), dir.join("krate/src")).unwrap();
+        // and one with a symlinked module
+        fs::write(dir.join("other/Cargo.toml"), MANIFEST).unwrap();
+        fs::write(
+            dir.join("other/src/lib.rs"),
+            "mod inner;\n/// Other.\nstruct O;\n",
+        )
+        .unwrap();
+        std::os::unix::fs::symlink(dir.join("shared/inner.rs"), dir.join("other/src/inner.rs"))
+            .unwrap();
+        let dir = dir.canonicalize().unwrap();
+
+        let origins = |path: PathBuf, follow_symlinks: bool| {
+            let docs = extract(
+                vec![path],
+                false,
+                false,
+                false,
+                false,
+                follow_symlinks,
+                &Config::default(),
+            )
+            .expect("Extraction must succeed");
+            let mut origins = docs
+                .iter()
+                .map(|(origin, _chunks)| origin.as_path().to_owned())
+                .collect::<Vec<_>>();
+            origins.sort();
+            origins
+        };
+
+        assert!(origins(dir.join("krate"), false).is_empty());
+        assert_eq!(
+            origins(dir.join("krate"), true),
+            vec![dir.join("shared/inner.rs"), dir.join("shared/lib.rs")]
+        );
+        assert_eq!(
+            origins(dir.join("other"), false),
+            vec![dir.join("other/src/lib.rs")]
+        );
+        assert_eq!(
+            origins(dir.join("other"), true),
+            vec![dir.join("other/src/lib.rs"), dir.join("shared/inner.rs")]
+        );
+        // the files of a directory without a manifest
+        assert_eq!(
+            origins(dir.join("other/src"), false),
+            vec![dir.join("other/src/lib.rs")]
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn ignore_file_of_project() {
+        let dir =
+            std::env::temp_dir().join(format!("cargo-spellcheck-ignored-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("src/generated")).unwrap();
+        fs::write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"ignored\"\nversion = \"0.1.0\"\n\n[lib]\npath = \"src/lib.rs\"\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("src/lib.rs"),
+            "mod generated;\nmod kept;\n/// Lib.\nstruct L;\n",
+        )
+        .unwrap();
+        fs::write(dir.join("src/kept.rs"), "/// Kept.\nstruct K;\n").unwrap();
+        fs::write(
+            dir.join("src/generated.rs"),
+            "mod nested;\n/// Generated.\nstruct G;\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("src/generated/nested.rs"),
+            "/// Nested.\nstruct N;\n",
+        )
+        .unwrap();
+        fs::write(dir.join(".spellcheckignore"), "src/generated.rs\n").unwrap();
+        let dir = dir.canonicalize().unwrap();
+
+        // the project is not the current working directory
+        assert!(!std::env::current_dir().unwrap().starts_with(&dir));
+        let docs = extract(
+            vec![dir.clone()],
+            false,
+            false,
+            false,
+            false,
+            false,
+            &Config::default(),
+        )
+        .expect("Extraction must succeed");
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut origins = docs
+            .iter()
+            .map(|(origin, _chunks)| origin.as_path().to_owned())
+            .collect::<Vec<_>>();
+        origins.sort();
+        // the modules of an ignored file are not traversed either
+        assert_eq!(
+            origins,
+            vec![dir.join("src/kept.rs"), dir.join("src/lib.rs")]
+        );
+    }
+
+    #[test]
+    fn included_readme_once() {
+        let dir =
+            std::env::temp_dir().join(format!("cargo-spellcheck-includes-{}", std::process::id()));
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("README.md"), "# Readme\n\nSome text.\n").unwrap();
+        let lib = dir.join("src").join("lib.rs");
+        let readme = dir.join("README.md").canonicalize().unwrap();
+
+        let mut docs = Documentation::new();
+        // as listed in the manifest
+        add_markdown(&mut docs, readme.clone()).unwrap();
+        docs.join(Documentation::from((
+            ContentOrigin::RustSourceFile(lib.clone()),
+            "#![doc = include_str!(\"../README.md\")]\n#![doc = include_str!(\"../README.md\")]\n",
+        )));
+        add_includes(&mut docs).unwrap();
+
+        let _ = fs::remove_dir_all(&dir);
+
+        let origins = docs
+            .iter()
+            .filter(|(_origin, chunks)| !chunks.is_empty())
+            .map(|(origin, chunks)| (origin.clone(), chunks.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(origins, vec![(ContentOrigin::CommonMarkFile(readme), 1)]);
+    }
+
+    #[test]
+    fn dependency_names() {
+        let manifest = cargo_toml::Manifest::from_str(
+            r#"
+[package]
+name = "foo-bar"
+version = "0.1.0"
+
+[dependencies]
+Serde = "1"
+async-std = "1"
+rand_core = { package = "rand-core", version = "0.5" }
+"#,
+        )
+        .expect("Manifest must parse");
+        assert_eq!(
+            manifest_names(&manifest),
+            into_hashset(
+                vec![
+                    "foo-bar",
+                    "foo_bar",
+                    "foo",
+                    "bar",
+                    "serde",
+                    "async-std",
+                    "async_std",
+                    "async",
+                    "std",
+                    "rand_core",
+                    "rand-core",
+                    "rand",
+                    "core",
+                ]
+                .into_iter()
+                .map(|name| name.to_owned())
+            )
+        );
+
+        let demo = demo_dir();
+        assert_eq!(
+            nearest_manifest_dir(&demo.join("src/lib.rs")),
+            Some(demo.as_path())
+        );
+    }
+
     fn demo_dir() -> PathBuf {
         manifest_dir().join("demo")
     }
@@ -557,6 +1430,9 @@ mod tests {
                 ],
                 $recurse,
                 false,
+                false,
+                false,
+                false,
                 &Config::default(),
             )
             .expect("Must be able to extract demo dir");
@@ -579,6 +1455,65 @@ mod tests {
         };
     }
 
+    #[test]
+    fn file_kinds() {
+        assert_eq!(
+            FileKind::of(Path::new("/tmp/docs/guide.asciidoc")),
+            Some(FileKind::AsciiDoc)
+        );
+        assert_eq!(
+            FileKind::of(Path::new("notes.txt")),
+            Some(FileKind::PlainText)
+        );
+        assert_eq!(FileKind::of(Path::new("Cargo.toml")), None);
+        assert_eq!(FileKind::of(Path::new("lib.rs.bak")), None);
+        assert_eq!(FileKind::of(Path::new("rs")), None);
+    }
+
+    #[test]
+    fn content_by_extension() {
+        let content = "/// Some docs\nstruct A;\n".to_owned();
+        let origins = |path: &str| {
+            extract_content(PathBuf::from(path), content.clone(), &Config::default())
+                .expect("Extraction must succeed")
+                .iter()
+                .map(|(origin, _)| origin.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            origins("src/lib.rs"),
+            vec![ContentOrigin::RustSourceFile(PathBuf::from("src/lib.rs"))]
+        );
+        assert_eq!(
+            origins("<stdin>"),
+            vec![ContentOrigin::CommonMarkFile(PathBuf::from("<stdin>"))]
+        );
+    }
+
+    #[test]
+    fn traverse_manifest_skip_markdown() {
+        let docs = extract(
+            vec![demo_dir().join("Cargo.toml")],
+            false,
+            false,
+            true,
+            false,
+            false,
+            &Config::default(),
+        )
+        .expect("Must be able to extract demo dir");
+        let origins = docs
+            .iter()
+            .map(|(origin, _)| origin.clone())
+            .collect::<Vec<_>>();
+        assert!(origins
+            .iter()
+            .all(|origin| !matches!(origin, ContentOrigin::CommonMarkFile(_))));
+        assert!(origins.contains(&ContentOrigin::RustSourceFile(
+            demo_dir().join("src/main.rs")
+        )));
+    }
+
     #[test]
     fn traverse_manifest_1() {
         extract_test!(["Cargo.toml"] + false => [
@@ -637,6 +1572,10 @@ mod tests {
         "src/nested/mod.rs",
     ]);
 
+    extract_test!(traverse_hidden_dir_explicit, ["src/nested/fragments/.hidden"] + true => [
+        "src/nested/fragments/.hidden/hidden.rs"
+    ]);
+
     extract_test!(traverse_nested_mod_rs_1, ["src/nested/mod.rs"] + false => [
         "src/nested/mod.rs"
     ]);
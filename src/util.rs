@@ -1,6 +1,14 @@
 use crate::{LineColumn, Range, Span};
 use anyhow::{anyhow, bail, Error, Result};
 use std::io::Read;
+use std::path::Path;
+
+/// Read the file at `path` to a `String`, the error names the path and
+/// carries the io error as cause, i.e. `Failed to read src/foo.rs: Permission denied`.
+pub fn read_file(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path)
+        .map_err(|e| Error::from(e).context(format!("Failed to read {}", path.display())))
+}
 
 /// Iterate over a str and annotate with line and column.
 ///
@@ -59,7 +67,7 @@ where
     let mut s = String::with_capacity(256);
     source
         .read_to_string(&mut s)
-        .expect("Must read successfully");
+        .map_err(|e| Error::from(e).context("Failed to read the source to load the span from"))?;
 
     let extraction = iter_with_line_column(s.as_str())
         .skip_while(|(_c, _idx, cursor)| {
@@ -100,6 +108,22 @@ mod tests {
             )
         };
     }
+    #[test]
+    fn read_file_names_the_path() {
+        let path = std::env::temp_dir().join(format!(
+            "cargo-spellcheck-missing-{}/src/foo.rs",
+            std::process::id()
+        ));
+        let e = read_file(&path).unwrap_err();
+        let message = format!("{:#}", e);
+        assert!(
+            message.starts_with(&format!("Failed to read {}: ", path.display())),
+            "{}",
+            message
+        );
+        assert!(e.root_cause().downcast_ref::<std::io::Error>().is_some());
+    }
+
     #[test]
     fn iter_chars() {
         const S: &'static str = r#"
@@ -0,0 +1,66 @@
+//! A regular run must not print anything to `stderr` beyond the configured
+//! log output, and `stdout` must only carry the report, otherwise it is
+//! unusable in pipelines.
+#![cfg(feature = "hunspell")]
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Check a readme with one mistake, using a dictionary in a temporary
+/// directory named after `name`.
+fn check(name: &str, args: &[&str]) -> std::process::Output {
+    let dir: PathBuf =
+        std::env::temp_dir().join(format!("cargo-spellcheck-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    fs::write(dir.join("en_US.aff"), "SET UTF-8\n").unwrap();
+    fs::write(dir.join("en_US.dic"), "4\nTest\nHello\nmy\nworld\n").unwrap();
+    fs::write(
+        dir.join("config.toml"),
+        format!(
+            "[hunspell]\nlang = \"en_US\"\nsearch_dirs = [{:?}]\n",
+            dir.display().to_string()
+        ),
+    )
+    .unwrap();
+    fs::write(dir.join("README.md"), "Hello my wrold\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cargo-spellcheck"))
+        .arg("check")
+        .arg(format!("--cfg={}", dir.join("config.toml").display()))
+        .args(args)
+        .arg(dir.join("README.md"))
+        .env_remove("CARGO_SPELLCHECK")
+        .env_remove("RUST_LOG")
+        .output()
+        .unwrap();
+
+    let _ = fs::remove_dir_all(&dir);
+    output
+}
+
+#[test]
+fn check_is_silent_on_stderr() {
+    let output = check("stderr", &[]);
+    assert!(output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stdout).contains("wrold"),
+        "The mistake must be reported on stdout"
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "");
+}
+
+#[test]
+fn checkstyle_stays_valid() {
+    let output = check(
+        "checkstyle",
+        &["--format=checkstyle", "--print-unknown-words", "--rules"],
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("<?xml"), "{}", stdout);
+    assert!(stdout.ends_with("</checkstyle>\n"), "{}", stdout);
+    assert!(stdout.contains("`wrold`"));
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "1\twrold\n");
+}